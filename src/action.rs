@@ -1,14 +1,26 @@
+use crate::updates::{DowngradeCandidate, PacnewFile};
+
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
     Quit,
     RunUpdate(Vec<String>),
     RunRebuild(String),
+    AddCheckTemplates(Vec<String>), // template names to append to checks.toml
     Uninstall(Vec<String>),
     UninstallWithDeps(Vec<String>),
     Reinstall(Vec<String>),
     ForceRebuild(Vec<String>),
     Install(Vec<String>),
+    MarkAsDeps(Vec<String>),
+    Downgrade(DowngradeCandidate, bool), // (version to install, add to hold list)
+    KeepCurrentConfig(PacnewFile),
+    TakeNewConfig(PacnewFile),
+    MergeConfig(PacnewFile),
+    DeletePacnew(Vec<String>),
+    RestartServices(Vec<String>),
+    TakeSnapshot, // runs ahead of a queued transaction
+    DeleteSnapshots(Vec<String>), // snapshot IDs
     CleanCache,
     ExportPackages,
     CopyPackages,
@@ -1,14 +1,29 @@
 use crate::action::Action;
-use crate::config::Config;
-use crate::rebuilds::{check_rebuilds, load_checks, RebuildCheck, RebuildIssue};
+use crate::backup::{import_entries, list_backups, parse_package_list, read_clipboard, Backup, ImportEntry};
+use crate::config::{Config, Hold};
+use crate::hooks::{run_hooks, HookContext, HookPhase};
+use crate::manifest::{check_drift, hostname, manifest_path, DriftEntry, DriftKind, Manifest};
+use crate::rebuilds::{
+    detect_packages, load_checks, merge_scanned, run_checks, scan_packages, suggest_templates,
+    CheckEvent, CheckResult, CheckStatus, CheckTemplate, RebuildCheck, RebuildIssue,
+};
+use crate::snapshot::Snapshot;
+use crate::state::State;
+use crate::transaction::Transaction;
 use crate::updates::{
-    check_aur_updates, check_pacman_updates, fetch_news, filter_items, find_related_packages,
-    get_installed_packages, get_orphan_packages, search_packages, InstalledPackage, NewsInfo,
-    NewsItem, Package, PackageInfo, PackageSource, SearchResult,
+    diff_pacnew, filter_items, find_pacnew_files, find_related_packages, last_full_upgrade,
+    built_revisions, check_restart, is_vcs_package, load_history, load_news, short_revision,
+    vercmp, DowngradeCandidate, HistoryEntry, InstalledPackage, NewsFeed, NewsInfo, NewsItem,
+    Package, PackageBackend, PackageInfo, PackageSource, PacnewFile, AurReview, AurStatus,
+    RestartStatus, SearchResult, SystemBackend, VcsHeads,
 };
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::widgets::ListState;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -32,9 +47,56 @@ pub enum Tab {
     Updates,
     Installed,
     Orphans,
+    Drift,
     Rebuilds,
+    Pacnew,
     Search,
     News,
+    History,
+    Snapshots,
+}
+
+impl Tab {
+    /// All tabs in display order
+    pub const ALL: [Tab; 10] = [
+        Tab::Updates,
+        Tab::Installed,
+        Tab::Orphans,
+        Tab::Drift,
+        Tab::Rebuilds,
+        Tab::Pacnew,
+        Tab::Search,
+        Tab::News,
+        Tab::History,
+        Tab::Snapshots,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Updates => "Updates",
+            Tab::Installed => "Installed",
+            Tab::Orphans => "Orphans",
+            Tab::Drift => "Drift",
+            Tab::Rebuilds => "Rebuilds",
+            Tab::Pacnew => "Pacnew",
+            Tab::Search => "Search",
+            Tab::News => "News",
+            Tab::History => "History",
+            Tab::Snapshots => "Snapshots",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|t| *t == self).unwrap_or(0)
+    }
+
+    pub fn next(self) -> Tab {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Tab {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -43,6 +105,22 @@ pub enum LoadingState {
     Loading,
 }
 
+/// Where one rebuild check is in the current run
+#[derive(Debug, Clone, Default)]
+pub struct CheckProgress {
+    pub status: CheckStatus,
+    /// When the check started running, for a live timer
+    pub started: Option<Instant>,
+    pub duration: Option<Duration>,
+}
+
+impl CheckProgress {
+    /// Final duration, or time spent so far while running
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.duration.or_else(|| self.started.map(|t| t.elapsed()))
+    }
+}
+
 /// Pending confirmation for an action
 #[derive(Debug, Clone)]
 pub struct ConfirmationState {
@@ -50,16 +128,111 @@ pub struct ConfirmationState {
     pub title: String,
     pub items: Vec<String>,
     pub message: String,
+    /// Unacknowledged news requiring manual intervention, which must be acknowledged first
+    pub news: Vec<NewsItem>,
+    /// Why the news could not be checked (feed still loading or failed); also needs
+    /// acknowledging
+    pub news_warning: Option<String>,
+    pub acknowledged: bool,
+    /// PKGBUILD revisions accepted in the review, recorded once the action is confirmed
+    pub reviewed: Vec<AurReview>,
+}
+
+impl ConfirmationState {
+    /// Whether the action is still blocked on acknowledging news
+    pub fn needs_acknowledgement(&self) -> bool {
+        (!self.news.is_empty() || self.news_warning.is_some()) && !self.acknowledged
+    }
+}
+
+/// Version picker for downgrading an installed package
+#[derive(Debug, Clone)]
+pub struct DowngradeState {
+    pub name: String,
+    pub installed_version: String,
+    pub candidates: Vec<DowngradeCandidate>,
+    pub list_state: ListState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+/// PKGBUILD review shown before an action that builds AUR packages
+#[derive(Debug, Clone)]
+pub struct ReviewState {
+    /// Confirmed next, once the PKGBUILDs are accepted
+    pub action: Action,
+    pub names: Vec<String>,
+    /// Parallel to `names`; None while still fetching
+    pub reviews: Vec<Option<Result<AurReview, String>>>,
+    pub current: usize,
+    pub scroll: u16,
+}
+
+/// Restoring packages from an exported list: pick the list, then what to install from it
+#[derive(Debug, Clone)]
+pub struct ImportState {
+    /// Exports in the backup directory, newest first; the clipboard is offered above them
+    pub backups: Vec<Backup>,
+    /// Where the list being previewed came from; None while picking one
+    pub source: Option<String>,
+    /// Listed packages that are not installed
+    pub entries: Vec<ImportEntry>,
+    /// Number of listed packages that are already installed
+    pub installed: usize,
+    pub list_state: ListState,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+/// Version typed in for holding an update until that version is offered
+#[derive(Debug, Clone)]
+pub struct HoldPrompt {
+    pub name: String,
+    pub version: String,
+}
+
+/// Picker for adding built-in check templates to checks.toml
+#[derive(Debug, Clone)]
+pub struct TemplatePickerState {
+    /// Templates matching installed packages that are not configured yet
+    pub templates: Vec<&'static CheckTemplate>,
+    pub chosen: Vec<bool>,
+    pub list_state: ListState,
+    pub loading: bool,
 }
 
 pub struct App {
     pub config: Config,
+    pub state: State,
+    backend: Arc<dyn PackageBackend>,
     pub tab: Tab,
     pub packages: Vec<Package>,
+    /// VCS packages with new upstream commits, kept apart so either check can finish first
+    devel_packages: Vec<Package>,
+    /// Upstream revisions seen by the last devel check
+    devel_heads: BTreeMap<String, VcsHeads>,
     pub installed_packages: Vec<InstalledPackage>,
     pub orphan_packages: Vec<InstalledPackage>,
+    /// Declared packages from packages.toml; None if there is none
+    pub manifest: Option<Manifest>,
+    /// Where the manifest is reloaded from on refresh; None keeps it fixed (used by tests)
+    manifest_path: Option<PathBuf>,
+    pub manifest_error: Option<String>,
+    /// Packages missing from or not declared in the manifest
+    pub drift: Vec<DriftEntry>,
+    pub drift_loading: bool,
+    pub drift_list_state: ListState,
+    /// AUR status of installed foreign packages, from the last successful refresh
+    pub aur_status: BTreeMap<String, AurStatus>,
+    /// Installed tab shows only AUR packages with a badge
+    pub aur_problems_only: bool,
     pub rebuild_issues: Vec<RebuildIssue>,
     pub rebuild_checks: Vec<RebuildCheck>,
+    /// Progress of each check in the current run, parallel to `rebuild_checks`
+    pub check_progress: Vec<CheckProgress>,
+    current_rebuild_run: u64,
+    /// Installed versions of all trigger packages at the start of the current run
+    trigger_versions: BTreeMap<String, String>,
     pub search_results: Vec<SearchResult>,
     pub search_query: String,
     pub search_loading: bool,
@@ -75,8 +248,22 @@ pub struct App {
     pub news_items: Vec<NewsItem>,
     pub news_loading: bool,
     pub news_error: bool,
+    /// Fetch error when the news shown came from the offline cache
+    pub news_offline: Option<String>,
     pub cached_news_info: Option<NewsInfo>,
     pub news_scroll: u16,
+    pub pacnew_files: Vec<PacnewFile>,
+    pub pacnew_list_state: ListState,
+    pub pacnew_loading: bool,
+    /// Diff of the selected pacnew file against the live config
+    pub pacnew_diff: Vec<String>,
+    pub pacnew_scroll: u16,
+    pub history: Vec<HistoryEntry>,
+    pub history_list_state: ListState,
+    pub history_loading: bool,
+    pub history_error: Option<String>,
+    pub snapshots_list_state: ListState,
+    pub snapshot_scroll: u16,
     pub loading: LoadingState,
     pub filter_mode: bool,
     pub filter_text: String,
@@ -87,36 +274,118 @@ pub struct App {
     info_debounce_until: Option<Instant>,
     current_info_id: u64,
     pub pending_confirmation: Option<ConfirmationState>,
+    pub downgrade: Option<DowngradeState>,
+    pub template_picker: Option<TemplatePickerState>,
+    pub review: Option<ReviewState>,
+    pub import: Option<ImportState>,
+    pub hold_prompt: Option<HoldPrompt>,
+    /// Stale kernel and services found by the last restart check
+    pub restart_status: Option<RestartStatus>,
+    pub show_restart: bool,
+    pub restart_scroll: u16,
+    pub transaction: Option<Transaction>,
+    /// Transaction waiting for its pre hooks, which run in the background
+    pub hooked_transaction: Option<HookedTransaction>,
+    /// Transaction waiting for the snapshot running ahead of it
+    queued_transaction: Option<QueuedTransaction>,
+    /// Post hook failures, shown once the transaction pane is closed
+    hook_errors: Option<String>,
     pub last_message: Option<String>,
     pending_tasks: usize,
     task_rx: Option<Receiver<TaskResult>>,
     task_tx: Sender<TaskResult>,
 }
 
+/// A transaction held back until its pre hooks have run
+pub struct HookedTransaction {
+    pub title: String,
+    pub phase: HookPhase,
+    action: Action,
+    argv: Vec<String>,
+    size: (u16, u16),
+}
+
+/// A transaction held back until its snapshot has been taken
+struct QueuedTransaction {
+    title: String,
+    action: Action,
+    argv: Vec<String>,
+    size: (u16, u16),
+    /// Command taking the snapshot, which the ID is parsed against
+    snapshot_argv: Vec<String>,
+}
+
 enum TaskResult {
     Updates(Vec<Package>, Vec<Package>),
     Installed(Vec<InstalledPackage>),
+    AurStatus(Result<BTreeMap<String, AurStatus>, String>),
+    /// Upstream revisions, and the installed versions of the packages they belong to
+    Devel(BTreeMap<String, VcsHeads>, BTreeMap<String, String>),
     Orphans(Vec<InstalledPackage>),
-    Rebuilds(Vec<RebuildIssue>),
-    Search(u64, Vec<SearchResult>),             // (search_id, results)
-    PackageInfo(u64, Box<Option<PackageInfo>>), // (info_id, info)
-    News(Result<Vec<NewsItem>, String>),        // Ok(items) or Err(error_message)
+    Drift(Vec<DriftEntry>),
+    TriggerVersions(u64, BTreeMap<String, String>), // (run_id, installed trigger packages)
+    Check(u64, CheckEvent),                         // (run_id, progress of one check)
+    Rebuilds(u64, Vec<RebuildIssue>),               // (run_id, built-in scan results) - ends a run
+    Search(u64, Vec<SearchResult>),                 // (search_id, results)
+    PackageInfo(u64, Box<Option<PackageInfo>>),     // (info_id, info)
+    News(Result<NewsFeed, String>),                 // Ok(feed) or Err(error_message)
+    History(Result<Vec<HistoryEntry>, String>),     // Ok(entries) or Err(error_message)
+    Versions(String, Result<Vec<DowngradeCandidate>, String>), // (package, candidates)
+    Templates(Vec<&'static CheckTemplate>),
+    Import(String, Result<(Vec<ImportEntry>, usize), String>), // (source, (entries, installed))
+    Review(String, Result<AurReview, String>), // (package, review)
+    Pacnew(Vec<PacnewFile>),
+    PacnewDiff(String, Vec<String>), // (pacnew path, diff lines)
+    Restart(RestartStatus),
+    PreHooks(Vec<String>),  // failures
+    PostHooks(Vec<String>), // failures
 }
 
 impl App {
     pub fn new() -> Self {
         let config = Config::load().unwrap_or_default();
         let rebuild_checks = load_checks().unwrap_or_default();
+        let backend = Arc::new(SystemBackend::new(&config.aur_helper));
+        let path = config.path.clone();
+        let mut app = Self::with_backend(config, rebuild_checks, backend);
+        app.config.path = path;
+        app.state = State::load();
+        app.manifest_path = Some(manifest_path());
+        app
+    }
+
+    /// Build an app on top of any package backend (tests use an in-memory fake)
+    pub fn with_backend(
+        config: Config,
+        rebuild_checks: Vec<RebuildCheck>,
+        backend: Arc<dyn PackageBackend>,
+    ) -> Self {
         let (tx, rx) = mpsc::channel();
 
         Self {
-            config,
+            // Kept in memory, like the state, unless `new` says where it was loaded from
+            config: Config { path: None, ..config },
+            state: State::default(),
+            backend,
             tab: Tab::Updates,
             packages: Vec::new(),
+            devel_packages: Vec::new(),
+            devel_heads: BTreeMap::new(),
             installed_packages: Vec::new(),
             orphan_packages: Vec::new(),
+            manifest: None,
+            manifest_path: None,
+            manifest_error: None,
+            drift: Vec::new(),
+            drift_loading: false,
+            drift_list_state: ListState::default(),
+            aur_status: BTreeMap::new(),
+            aur_problems_only: false,
             rebuild_issues: Vec::new(),
             rebuild_checks,
+            check_progress: Vec::new(),
+            current_rebuild_run: 0,
+            trigger_versions: BTreeMap::new(),
             search_results: Vec::new(),
             search_query: String::new(),
             search_loading: false,
@@ -132,8 +401,20 @@ impl App {
             news_items: Vec::new(),
             news_loading: false,
             news_error: false,
+            news_offline: None,
             cached_news_info: None,
             news_scroll: 0,
+            pacnew_files: Vec::new(),
+            pacnew_list_state: ListState::default(),
+            pacnew_loading: false,
+            pacnew_diff: Vec::new(),
+            pacnew_scroll: 0,
+            history: Vec::new(),
+            history_list_state: ListState::default(),
+            history_loading: false,
+            history_error: None,
+            snapshots_list_state: ListState::default(),
+            snapshot_scroll: 0,
             loading: LoadingState::Idle,
             filter_mode: false,
             filter_text: String::new(),
@@ -144,6 +425,18 @@ impl App {
             info_debounce_until: None,
             current_info_id: 0,
             pending_confirmation: None,
+            downgrade: None,
+            template_picker: None,
+            review: None,
+            import: None,
+            hold_prompt: None,
+            restart_status: None,
+            show_restart: false,
+            restart_scroll: 0,
+            transaction: None,
+            hooked_transaction: None,
+            queued_transaction: None,
+            hook_errors: None,
             last_message: None,
             pending_tasks: 0,
             task_rx: Some(rx),
@@ -153,76 +446,1233 @@ impl App {
 
     pub fn refresh(&mut self) {
         self.loading = LoadingState::Loading;
-        self.pending_tasks = 3;
+        self.pending_tasks += 3;
         let tx = self.task_tx.clone();
-        let checks = self.rebuild_checks.clone();
-        let aur_helper = self.config.aur_helper.clone();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
-            let pacman = check_pacman_updates();
-            let aur = check_aur_updates(&aur_helper);
+            let pacman = backend.pacman_updates();
+            let aur = backend.aur_updates();
             let _ = tx.send(TaskResult::Updates(pacman, aur));
 
-            let installed = get_installed_packages();
+            let installed = backend.installed();
             let _ = tx.send(TaskResult::Installed(installed));
-
-            let issues = check_rebuilds(&checks);
-            let _ = tx.send(TaskResult::Rebuilds(issues));
+            let _ = tx.send(TaskResult::AurStatus(backend.aur_status()));
         });
+
+        if self.config.devel {
+            self.pending_tasks += 1;
+            let tx = self.task_tx.clone();
+            let backend = Arc::clone(&self.backend);
+            let aur_url = self.config.aur_url.clone();
+            thread::spawn(move || {
+                let heads = backend.vcs_heads(&aur_url);
+                let names: Vec<String> = heads.keys().cloned().collect();
+                let versions = backend.package_versions(&names);
+                let _ = tx.send(TaskResult::Devel(heads, versions));
+            });
+        }
+
+        self.refresh_rebuilds();
     }
 
     pub fn refresh_installed(&mut self) {
         self.loading = LoadingState::Loading;
-        self.pending_tasks += 1;
+        self.pending_tasks += 2;
         let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
-            let installed = get_installed_packages();
+            let installed = backend.installed();
             let _ = tx.send(TaskResult::Installed(installed));
+            let _ = tx.send(TaskResult::AurStatus(backend.aur_status()));
         });
     }
 
+    /// Run the rebuild checks that are due in parallel, streaming each result, then the
+    /// built-in scans
     pub fn refresh_rebuilds(&mut self) {
+        self.run_rebuild_checks(false);
+    }
+
+    /// Run every check, including those whose trigger packages have not changed since they
+    /// last passed
+    pub fn force_rebuild_checks(&mut self) {
+        self.run_rebuild_checks(true);
+    }
+
+    fn run_rebuild_checks(&mut self, force: bool) {
         self.loading = LoadingState::Loading;
         self.pending_tasks += 1;
+        self.current_rebuild_run += 1;
+        let run_id = self.current_rebuild_run;
+        self.rebuild_issues.clear();
+        self.check_progress = vec![CheckProgress::default(); self.rebuild_checks.len()];
+
+        // Forget what checks that were since edited or removed passed with
+        let keys: Vec<String> = self.rebuild_checks.iter().map(|c| c.state_key()).collect();
+        let recorded = self.state.check_triggers.len();
+        self.state.check_triggers.retain(|key, _| keys.contains(key));
+        if self.state.check_triggers.len() != recorded {
+            self.save_state();
+        }
+
         let tx = self.task_tx.clone();
         let checks = self.rebuild_checks.clone();
+        let passed_with = self.state.check_triggers.clone();
+        let scan_with = self.library_scan_helper();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
-            let issues = check_rebuilds(&checks);
-            let _ = tx.send(TaskResult::Rebuilds(issues));
+            let mut triggers: Vec<String> =
+                checks.iter().flat_map(|c| c.triggers.clone()).collect();
+            triggers.sort();
+            triggers.dedup();
+            let installed = backend.package_versions(&triggers);
+
+            // Checks whose triggers are unchanged since they last passed are skipped
+            let (due, skipped): (Vec<usize>, Vec<usize>) = (0..checks.len()).partition(|&i| {
+                force || checks[i].is_due(passed_with.get(&checks[i].state_key()), &installed)
+            });
+            let _ = tx.send(TaskResult::TriggerVersions(run_id, installed));
+            for index in skipped {
+                let result = CheckResult {
+                    status: CheckStatus::Skipped,
+                    duration: Duration::ZERO,
+                    issue: None,
+                };
+                let _ = tx.send(TaskResult::Check(run_id, CheckEvent::Finished(index, result)));
+            }
+
+            let due_checks: Vec<RebuildCheck> = due.iter().map(|&i| checks[i].clone()).collect();
+            run_checks(&due_checks, |event| {
+                // Map indexes back into the full check list
+                let event = match event {
+                    CheckEvent::Started(i) => CheckEvent::Started(due[i]),
+                    CheckEvent::Finished(i, result) => CheckEvent::Finished(due[i], result),
+                };
+                let _ = tx.send(TaskResult::Check(run_id, event));
+            });
+            let scanned = scan_with.map(|helper| scan_packages(&helper)).unwrap_or_default();
+            let _ = tx.send(TaskResult::Rebuilds(run_id, scanned));
         });
     }
 
+    fn handle_check_event(&mut self, event: CheckEvent) {
+        match event {
+            CheckEvent::Started(index) => {
+                if let Some(progress) = self.check_progress.get_mut(index) {
+                    progress.status = CheckStatus::Running;
+                    progress.started = Some(Instant::now());
+                }
+            }
+            CheckEvent::Finished(index, result) => {
+                if let Some(progress) = self.check_progress.get_mut(index) {
+                    progress.status = result.status;
+                    progress.duration = Some(result.duration);
+                }
+                self.record_trigger_versions(index, result.status);
+                if let Some(issue) = result.issue {
+                    // Keep issues in config order as results arrive out of order
+                    let position = |name: &str| self.rebuild_checks.iter().position(|c| c.name == name);
+                    let at = self
+                        .rebuild_issues
+                        .iter()
+                        .take_while(|i| position(&i.name) < Some(index))
+                        .count();
+                    self.rebuild_issues.insert(at, issue);
+                    self.clamp_rebuilds_selection();
+                }
+            }
+        }
+    }
+
+    /// Remember the trigger versions a check passed with, so it is skipped until they change
+    /// Any other outcome forgets them and the check runs again next time
+    fn record_trigger_versions(&mut self, index: usize, status: CheckStatus) {
+        let Some(check) = self.rebuild_checks.get(index) else {
+            return;
+        };
+        if check.triggers.is_empty() {
+            return;
+        }
+
+        if !matches!(status, CheckStatus::Ok | CheckStatus::Failed | CheckStatus::TimedOut) {
+            return;
+        }
+        if status == CheckStatus::Ok {
+            let versions = check.trigger_versions(&self.trigger_versions);
+            self.state.check_triggers.insert(check.state_key(), versions);
+        } else {
+            self.state.check_triggers.remove(&check.state_key());
+        }
+        self.save_state();
+    }
+
+    /// AUR helper for generated rebuild commands, or None when the library scan is disabled
+    fn library_scan_helper(&self) -> Option<String> {
+        self.config.scan_libraries.then(|| self.config.aur_helper.clone())
+    }
+
     pub fn refresh_orphans(&mut self) {
         self.loading = LoadingState::Loading;
         self.pending_tasks += 1;
         let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
-            let orphans = get_orphan_packages();
+            let orphans = backend.orphans();
             let _ = tx.send(TaskResult::Orphans(orphans));
         });
     }
 
-    pub fn refresh_news(&mut self) {
-        self.news_loading = true;
-        self.news_error = false;
-        let tx = self.task_tx.clone();
-        // Get installed package names for matching
-        let installed_names: Vec<String> = self
-            .installed_packages
-            .iter()
-            .map(|p| p.name.clone())
-            .collect();
+    /// Reload packages.toml and compare it with the installed packages
+    pub fn refresh_drift(&mut self) {
+        if let Some(path) = &self.manifest_path {
+            match Manifest::load(path) {
+                Ok(manifest) => {
+                    self.manifest = manifest;
+                    self.manifest_error = None;
+                }
+                Err(e) => {
+                    self.manifest = None;
+                    self.manifest_error = Some(e);
+                }
+            }
+        }
+        let Some(manifest) = &self.manifest else {
+            self.drift.clear();
+            self.clamp_drift_selection();
+            return;
+        };
+
+        self.drift_loading = true;
+        let declared = manifest.declared(&hostname(), &self.config.roles);
+        let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
+
+        thread::spawn(move || {
+            let _ = tx.send(TaskResult::Drift(check_drift(backend.as_ref(), &declared)));
+        });
+    }
+
+    /// Write a packages.toml declaring the explicitly installed packages, if there is none yet
+    fn write_manifest(&mut self) {
+        let Some(path) = self.manifest_path.clone() else {
+            return;
+        };
+        if self.manifest.is_some() || self.manifest_error.is_some() {
+            self.last_message = Some(format!("{} already exists", path.display()));
+            return;
+        }
+        if self.installed_packages.is_empty() {
+            self.last_message = Some("Installed packages are still loading".to_string());
+            return;
+        }
+
+        let manifest = Manifest::from_installed(&self.installed_packages);
+        self.last_message = Some(match manifest.save(&path) {
+            Ok(()) => format!(
+                "Wrote {} + {} AUR to {}",
+                manifest.packages.len(),
+                manifest.aur.len(),
+                path.display()
+            ),
+            Err(e) => format!("Failed to write {}: {}", path.display(), e),
+        });
+        self.refresh_drift();
+    }
+
+    pub fn refresh_news(&mut self) {
+        self.news_loading = true;
+        self.news_error = false;
+        let tx = self.task_tx.clone();
+        // Get installed package names for matching
+        let installed_names: Vec<String> = self
+            .installed_packages
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+
+        thread::spawn(move || {
+            let news = load_news(&installed_names);
+            let _ = tx.send(TaskResult::News(news));
+        });
+    }
+
+    pub fn refresh_history(&mut self) {
+        self.history_loading = true;
+        self.history_error = None;
+        let tx = self.task_tx.clone();
+
+        thread::spawn(move || {
+            let _ = tx.send(TaskResult::History(load_history()));
+        });
+    }
+
+    pub fn refresh_pacnew(&mut self) {
+        self.pacnew_loading = true;
+        let tx = self.task_tx.clone();
+
+        thread::spawn(move || {
+            let _ = tx.send(TaskResult::Pacnew(find_pacnew_files()));
+        });
+    }
+
+    /// Load the diff for the pacnew file under the cursor
+    fn refresh_pacnew_diff(&mut self) {
+        self.pacnew_diff.clear();
+        self.pacnew_scroll = 0;
+        let Some(file) = self.selected_pacnew().cloned() else {
+            return;
+        };
+        let tx = self.task_tx.clone();
+
+        thread::spawn(move || {
+            let diff = diff_pacnew(&file);
+            let _ = tx.send(TaskResult::PacnewDiff(file.path, diff));
+        });
+    }
+
+    pub fn selected_pacnew(&self) -> Option<&PacnewFile> {
+        let idx = self.pacnew_list_state.selected()?;
+        self.pacnew_files.get(idx)
+    }
+
+    /// Look for a stale kernel and services still using replaced libraries
+    pub fn refresh_restart(&mut self) {
+        let tx = self.task_tx.clone();
+
+        thread::spawn(move || {
+            let _ = tx.send(TaskResult::Restart(check_restart()));
+        });
+    }
+
+    fn toggle_restart_panel(&mut self) {
+        self.show_restart = !self.show_restart;
+        self.restart_scroll = 0;
+        if self.show_restart {
+            self.refresh_restart();
+        }
+    }
+
+    fn handle_restart_key(&mut self, key: KeyCode) -> Action {
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('R') => {
+                self.show_restart = false;
+                Action::None
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.restart_scroll = self.restart_scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.restart_scroll = self.restart_scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Enter => {
+                let units = self
+                    .restart_status
+                    .as_ref()
+                    .map(|s| s.system_units())
+                    .unwrap_or_default();
+                if units.is_empty() {
+                    return Action::None;
+                }
+                self.show_restart = false;
+                self.maybe_confirm(Action::RestartServices(units))
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Open the version picker for the package under the cursor on the Installed tab
+    fn open_downgrade(&mut self) {
+        if self.tab != Tab::Installed {
+            return;
+        }
+        let Some(filter_idx) = self.installed_list_state.selected() else {
+            return;
+        };
+        let Some((_, pkg)) = self.filtered_installed().get(filter_idx).copied() else {
+            return;
+        };
+
+        let name = pkg.name.clone();
+        self.downgrade = Some(DowngradeState {
+            name: name.clone(),
+            installed_version: pkg.version.clone(),
+            candidates: Vec::new(),
+            list_state: ListState::default(),
+            loading: true,
+            error: None,
+        });
+
+        let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
+        let archive_url = self.config.archive_url.clone();
+
+        thread::spawn(move || {
+            let versions = backend.versions(&name, archive_url.as_deref());
+            let _ = tx.send(TaskResult::Versions(name, versions));
+        });
+    }
+
+    fn handle_downgrade_key(&mut self, key: KeyCode) -> Action {
+        let Some(state) = &mut self.downgrade else {
+            return Action::None;
+        };
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.downgrade = None;
+                Action::None
+            }
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Char('k') | KeyCode::Up => {
+                if !state.candidates.is_empty() {
+                    let delta = if matches!(key, KeyCode::Char('k') | KeyCode::Up) { -1 } else { 1 };
+                    let current = state.list_state.selected().unwrap_or(0) as i32;
+                    let new = (current + delta).clamp(0, state.candidates.len() as i32 - 1) as usize;
+                    state.list_state.select(Some(new));
+                }
+                Action::None
+            }
+            KeyCode::Enter | KeyCode::Char('h') => {
+                let hold = key == KeyCode::Char('h');
+                let candidate = state
+                    .list_state
+                    .selected()
+                    .and_then(|i| state.candidates.get(i))
+                    .cloned();
+                match candidate {
+                    Some(candidate) => {
+                        self.downgrade = None;
+                        self.maybe_confirm(Action::Downgrade(candidate, hold))
+                    }
+                    None => Action::None,
+                }
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Record maintainers and flag packages whose maintainer changed since the last refresh
+    fn apply_aur_status(&mut self, status: BTreeMap<String, AurStatus>) {
+        let mut changed = Vec::new();
+        for (name, aur) in status.iter().filter(|(_, aur)| !aur.deleted) {
+            let previous = self.state.aur_maintainers.insert(name.clone(), aur.maintainer.clone());
+            // Dropping a package (orphaning) is flagged on its own; a new owner is the risk
+            let adopted = previous.filter(|p| *p != aur.maintainer && aur.maintainer.is_some());
+            if let Some(previous) = adopted {
+                self.state.aur_maintainer_changes.insert(name.clone(), previous);
+                changed.push(name.clone());
+            }
+        }
+
+        // Forget packages that are no longer installed
+        self.state.aur_maintainers.retain(|name, _| status.contains_key(name));
+        self.state.aur_maintainer_changes.retain(|name, _| status.contains_key(name));
+        self.save_state();
+
+        if !changed.is_empty() {
+            self.last_message = Some(format!("AUR maintainer changed: {}", changed.join(", ")));
+        }
+        self.aur_status = status;
+        self.clamp_filter_selection();
+    }
+
+    /// Compare upstream revisions with the ones each VCS package was built from
+    /// Packages seen for the first time only have their revisions recorded
+    fn apply_devel_heads(
+        &mut self,
+        heads: BTreeMap<String, VcsHeads>,
+        versions: &BTreeMap<String, String>,
+    ) {
+        self.devel_packages.clear();
+        for (name, sources) in &heads {
+            if !self.state.devel_revisions.contains_key(name) {
+                let version = versions.get(name).map_or("", String::as_str);
+                match built_revisions(version, sources) {
+                    Some(seeded) => {
+                        self.state.devel_revisions.insert(name.clone(), seeded);
+                    }
+                    // Built before upkeep tracked it and its version does not tell from what
+                    None => {
+                        let head = sources.values().next().map(|h| short_revision(h));
+                        self.devel_packages.push(Package::new(
+                            name.clone(),
+                            "unknown".to_string(),
+                            head.unwrap_or_default(),
+                            PackageSource::Devel,
+                        ));
+                        continue;
+                    }
+                }
+            }
+
+            let recorded = self.state.devel_revisions.entry(name.clone()).or_default();
+            let changed = sources.iter().find_map(|(source, head)| {
+                let built = recorded.get(source)?;
+                (built != head).then_some((built.clone(), head))
+            });
+            if let Some((built, head)) = changed {
+                self.devel_packages.push(Package::new(
+                    name.clone(),
+                    short_revision(&built),
+                    short_revision(head),
+                    PackageSource::Devel,
+                ));
+            }
+            // Sources added to the PKGBUILD since start from their current revision
+            for (source, head) in sources {
+                recorded.entry(source.clone()).or_insert_with(|| head.clone());
+            }
+        }
+        self.save_state();
+        self.devel_heads = heads;
+    }
+
+    /// Add devel updates to the update list, unless the AUR already offers a new version
+    fn merge_devel_packages(&mut self) {
+        self.packages.retain(|p| p.source != PackageSource::Devel);
+        let devel: Vec<Package> = self
+            .devel_packages
+            .iter()
+            .filter(|d| !self.packages.iter().any(|p| p.name == d.name))
+            .cloned()
+            .collect();
+        self.packages.extend(devel);
+        self.clamp_list_selection();
+    }
+
+    /// Record the upstream revisions of VCS packages that were just built
+    /// Packages the devel check has not seen yet are recorded on its next run
+    fn record_devel_revisions(&mut self, names: &[String]) {
+        for name in names.iter().filter(|name| is_vcs_package(name)) {
+            match self.devel_heads.get(name) {
+                Some(heads) => self.state.devel_revisions.insert(name.clone(), heads.clone()),
+                None => self.state.devel_revisions.remove(name),
+            };
+        }
+        self.save_state();
+    }
+
+    /// Devel updates an update would include: all that are not held, or those among `names`
+    pub fn devel_updates(&self, names: &[String]) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|p| p.source == PackageSource::Devel)
+            .filter(|p| {
+                if names.is_empty() {
+                    self.package_hold(p).is_none()
+                } else {
+                    names.contains(&p.name)
+                }
+            })
+            .map(|p| p.name.clone())
+            .collect()
+    }
+
+    /// Badges for an installed package: AUR problems and maintainer changes
+    pub fn aur_badges(&self, name: &str) -> Vec<&'static str> {
+        let mut badges = self.aur_status.get(name).map(|s| s.badges()).unwrap_or_default();
+        if self.state.aur_maintainer_changes.contains_key(name) {
+            badges.push("new maintainer");
+        }
+        badges
+    }
+
+    /// AUR packages an action would build
+    fn aur_packages(&self, action: &Action) -> Vec<String> {
+        match action {
+            Action::RunUpdate(names) => self
+                .packages
+                .iter()
+                .filter(|p| p.source != PackageSource::Pacman)
+                .filter(|p| {
+                    if names.is_empty() {
+                        self.package_hold(p).is_none()
+                    } else {
+                        names.contains(&p.name)
+                    }
+                })
+                .map(|p| p.name.clone())
+                .collect(),
+            // From search, declared in packages.toml or restored from a backup
+            Action::Install(names) => names
+                .iter()
+                .filter(|name| {
+                    self.search_results.iter().any(|r| r.repository == "AUR" && r.name == **name)
+                        || self.drift.iter().any(|e| e.aur && e.name == **name)
+                        || self.import.as_ref().is_some_and(|i| {
+                            i.entries
+                                .iter()
+                                .any(|e| e.source == Some(PackageSource::Aur) && e.name == **name)
+                        })
+                })
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Fetch the PKGBUILDs of `names` for review before `action` is confirmed
+    fn open_review(&mut self, action: Action, names: Vec<String>) {
+        let previous: Vec<_> = names.iter().map(|n| self.state.reviewed_aur.get(n).cloned()).collect();
+        self.review = Some(ReviewState {
+            action,
+            names: names.clone(),
+            reviews: vec![None; names.len()],
+            current: 0,
+            scroll: 0,
+        });
+
+        let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
+        let aur_url = self.config.aur_url.clone();
+
+        thread::spawn(move || {
+            for (name, previous) in names.into_iter().zip(previous) {
+                let review = backend
+                    .aur_source(&name, &aur_url)
+                    .map(|source| AurReview::new(&name, source, previous));
+                let _ = tx.send(TaskResult::Review(name, review));
+            }
+        });
+    }
+
+    fn handle_review_key(&mut self, key: KeyCode) -> Action {
+        let Some(state) = &mut self.review else {
+            return Action::None;
+        };
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => self.review = None,
+            KeyCode::Char('j') | KeyCode::Down => state.scroll = state.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => state.scroll = state.scroll.saturating_sub(1),
+            KeyCode::PageDown => state.scroll = state.scroll.saturating_add(20),
+            KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(20),
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => {
+                state.current = (state.current + 1) % state.names.len();
+                state.scroll = 0;
+            }
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => {
+                state.current = (state.current + state.names.len() - 1) % state.names.len();
+                state.scroll = 0;
+            }
+            // `!` accepts even when some PKGBUILDs could not be fetched, building those unseen
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('!') => {
+                if state.reviews.iter().any(|r| r.is_none()) {
+                    self.last_message = Some("Still fetching PKGBUILDs".to_string());
+                    return Action::None;
+                }
+                let failed: Vec<&str> = state
+                    .names
+                    .iter()
+                    .zip(&state.reviews)
+                    .filter(|(_, review)| matches!(review, Some(Err(_))))
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                if !failed.is_empty() && key != KeyCode::Char('!') {
+                    self.last_message = Some(format!(
+                        "Could not fetch the PKGBUILD of {}; ! builds without reviewing it",
+                        failed.join(", ")
+                    ));
+                    return Action::None;
+                }
+
+                let state = self.review.take().unwrap();
+                let mut confirmation = self.build_confirmation(state.action);
+                confirmation.reviewed = state.reviews.into_iter().flatten().flatten().collect();
+                self.pending_confirmation = Some(confirmation);
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn open_template_picker(&mut self) {
+        self.template_picker = Some(TemplatePickerState {
+            templates: Vec::new(),
+            chosen: Vec::new(),
+            list_state: ListState::default(),
+            loading: true,
+        });
+
+        let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
+        let checks = self.rebuild_checks.clone();
+
+        thread::spawn(move || {
+            let installed = backend.package_versions(&detect_packages());
+            let _ = tx.send(TaskResult::Templates(suggest_templates(&installed, &checks)));
+        });
+    }
+
+    fn handle_template_key(&mut self, key: KeyCode) -> Action {
+        let Some(state) = &mut self.template_picker else {
+            return Action::None;
+        };
+
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.template_picker = None;
+                Action::None
+            }
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Char('k') | KeyCode::Up => {
+                if !state.templates.is_empty() {
+                    let delta = if matches!(key, KeyCode::Char('k') | KeyCode::Up) { -1 } else { 1 };
+                    let current = state.list_state.selected().unwrap_or(0) as i32;
+                    let new = (current + delta).clamp(0, state.templates.len() as i32 - 1) as usize;
+                    state.list_state.select(Some(new));
+                }
+                Action::None
+            }
+            KeyCode::Char(' ') => {
+                if let Some(chosen) = state.list_state.selected().and_then(|i| state.chosen.get_mut(i)) {
+                    *chosen = !*chosen;
+                }
+                Action::None
+            }
+            KeyCode::Enter => {
+                // Chosen templates, or the highlighted one when none are chosen
+                let mut names: Vec<String> = state
+                    .templates
+                    .iter()
+                    .zip(&state.chosen)
+                    .filter(|(_, chosen)| **chosen)
+                    .map(|(t, _)| t.name.to_string())
+                    .collect();
+                if names.is_empty() {
+                    let highlighted = state.list_state.selected().and_then(|i| state.templates.get(i));
+                    names.extend(highlighted.map(|t| t.name.to_string()));
+                }
+                if names.is_empty() {
+                    return Action::None;
+                }
+                self.template_picker = None;
+                Action::AddCheckTemplates(names)
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn open_import(&mut self) {
+        if self.tab != Tab::Installed {
+            return;
+        }
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        self.import = Some(ImportState {
+            backups: list_backups(),
+            source: None,
+            entries: Vec::new(),
+            installed: 0,
+            list_state,
+            loading: false,
+            error: None,
+        });
+    }
+
+    /// Read the list under the cursor and look up what is missing from it, in the background
+    fn load_import(&mut self) {
+        let Some(state) = &mut self.import else {
+            return;
+        };
+        let idx = state.list_state.selected().unwrap_or(0);
+        let backup = idx.checked_sub(1).and_then(|i| state.backups.get(i)).cloned();
+        let source = match &backup {
+            Some(backup) => format!("backup of {}", backup.date),
+            None => "clipboard".to_string(),
+        };
+        state.source = Some(source.clone());
+        state.entries.clear();
+        state.error = None;
+        state.loading = true;
+        state.list_state.select(None);
+
+        let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
+
+        thread::spawn(move || {
+            let result = match backup {
+                Some(backup) => backup.load(),
+                None => read_clipboard().map(|text| parse_package_list(&text, false)),
+            }
+            .and_then(|list| {
+                let names = list.names();
+                if names.is_empty() {
+                    return Err(format!("No package names found in the {}", source));
+                }
+                let installed = backend.package_versions(&names);
+                let missing: Vec<String> =
+                    names.into_iter().filter(|n| !installed.contains_key(n)).collect();
+                let located = backend.locate(&missing)?;
+                Ok((import_entries(&list, &installed, &located), installed.len()))
+            });
+            let _ = tx.send(TaskResult::Import(source, result));
+        });
+    }
+
+    fn handle_import_key(&mut self, key: KeyCode) -> Action {
+        let Some(state) = &mut self.import else {
+            return Action::None;
+        };
+        let previewing = state.source.is_some();
+
+        match key {
+            // Back to the list of backups
+            KeyCode::Esc if previewing => {
+                state.source = None;
+                state.entries.clear();
+                state.error = None;
+                state.loading = false;
+                state.list_state.select(Some(0));
+                Action::None
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.import = None;
+                Action::None
+            }
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Char('k') | KeyCode::Up => {
+                let len = if previewing { state.entries.len() } else { state.backups.len() + 1 };
+                if len > 0 {
+                    let delta = if matches!(key, KeyCode::Char('k') | KeyCode::Up) { -1 } else { 1 };
+                    let current = state.list_state.selected().unwrap_or(0) as i32;
+                    let new = (current + delta).clamp(0, len as i32 - 1) as usize;
+                    state.list_state.select(Some(new));
+                }
+                Action::None
+            }
+            KeyCode::Enter if !previewing => {
+                self.load_import();
+                Action::None
+            }
+            KeyCode::Char(' ') if previewing => {
+                let entry = state.list_state.selected().and_then(|i| state.entries.get_mut(i));
+                // Packages that no longer exist cannot be installed
+                if let Some(entry) = entry.filter(|e| e.source.is_some()) {
+                    entry.selected = !entry.selected;
+                }
+                Action::None
+            }
+            KeyCode::Char('a') | KeyCode::Char('n') if previewing => {
+                for entry in &mut state.entries {
+                    entry.selected = key == KeyCode::Char('a') && entry.source.is_some();
+                }
+                Action::None
+            }
+            KeyCode::Enter => {
+                let names: Vec<String> =
+                    state.entries.iter().filter(|e| e.selected).map(|e| e.name.clone()).collect();
+                if names.is_empty() {
+                    self.last_message = Some("Nothing selected to install".to_string());
+                    return Action::None;
+                }
+                // Reviewing the AUR packages needs to know which ones they are
+                let action = self.maybe_confirm(Action::Install(names));
+                self.import = None;
+                action
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Reload checks.toml after it changed and rerun the checks
+    pub fn reload_checks(&mut self) {
+        match load_checks() {
+            Ok(checks) => {
+                self.rebuild_checks = checks;
+                self.refresh_rebuilds();
+            }
+            Err(e) => self.last_message = Some(format!("Failed to load checks.toml: {}", e)),
+        }
+    }
+
+    /// Persist config changes made from the TUI (e.g. the hold list)
+    fn save_holds(&mut self) {
+        if let Err(e) = self.config.save_holds() {
+            self.last_message = Some(format!("Failed to save config: {}", e));
+        }
+    }
+
+    fn save_state(&mut self) {
+        if let Err(e) = self.state.save() {
+            self.last_message = Some(format!("Failed to save state: {}", e));
+        }
+    }
+
+    /// Hold or release the update under the cursor, optionally for a number of days
+    fn toggle_hold(&mut self, days: Option<u64>) {
+        if self.tab != Tab::Updates {
+            return;
+        }
+        let Some(filter_idx) = self.list_state.selected() else {
+            return;
+        };
+        let Some(real_idx) = self.filtered_updates().get(filter_idx).map(|(idx, _)| *idx) else {
+            return;
+        };
+
+        let pkg = &mut self.packages[real_idx];
+        let name = pkg.name.clone();
+        if self.config.active_hold(&name, Some(&pkg.new_version)).is_some() {
+            self.config.remove_hold(&name);
+            self.last_message = Some(format!("Released {}", name));
+        } else {
+            let until = days.map(|days| {
+                (chrono::Local::now().date_naive() + chrono::Days::new(days))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            });
+            // Held packages are never part of an update
+            pkg.selected = false;
+            self.last_message = Some(match &until {
+                Some(date) => format!("Holding {} until {}", name, date),
+                None => format!("Holding {}", name),
+            });
+            // A hold that has lapsed is replaced rather than extended
+            self.config.remove_hold(&name);
+            self.config.add_hold(&name, until, None);
+        }
+        self.save_holds();
+    }
+
+    /// Ask for the version the update under the cursor is held until
+    fn open_hold_prompt(&mut self) {
+        if self.tab != Tab::Updates {
+            return;
+        }
+        let Some(filter_idx) = self.list_state.selected() else {
+            return;
+        };
+        let Some(name) = self.filtered_updates().get(filter_idx).map(|(_, p)| p.name.clone()) else {
+            return;
+        };
+        let version = self
+            .config
+            .holds
+            .iter()
+            .find(|h| h.name == name)
+            .and_then(|h| h.until_version.clone())
+            .unwrap_or_default();
+        self.hold_prompt = Some(HoldPrompt { name, version });
+    }
+
+    fn handle_hold_prompt_key(&mut self, key: KeyCode) -> Action {
+        let Some(prompt) = &mut self.hold_prompt else {
+            return Action::None;
+        };
+        match key {
+            KeyCode::Esc => self.hold_prompt = None,
+            KeyCode::Backspace => {
+                prompt.version.pop();
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => prompt.version.push(c),
+            KeyCode::Enter if !prompt.version.is_empty() => {
+                let (name, version) = (prompt.name.clone(), prompt.version.clone());
+                self.hold_prompt = None;
+                self.hold_until_version(&name, version);
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Hold a package until `version` (or newer) is offered, keeping any date on its hold
+    fn hold_until_version(&mut self, name: &str, version: String) {
+        let offered = self.packages.iter().find(|p| p.name == name);
+        if offered.is_some_and(|p| vercmp(&p.new_version, &version) != Ordering::Less) {
+            self.last_message = Some(format!("{} {} is already offered", name, version));
+            return;
+        }
+        self.config.add_hold(name, None, Some(version.clone()));
+        self.save_holds();
+        if let Some(pkg) = self.packages.iter_mut().find(|p| p.name == name) {
+            pkg.selected = false;
+        }
+        self.last_message = Some(format!("Holding {} until {} is offered", name, version));
+    }
+
+    /// Run a command in the embedded terminal pane
+    /// Updates, removals and rebuilds wait for a snapshot first when a provider is configured
+    pub fn start_transaction(&mut self, title: &str, action: Action, argv: Vec<String>, size: (u16, u16)) {
+        if let Some(waiting) = &self.hooked_transaction {
+            self.last_message = Some(format!("Waiting for the hooks of {}", waiting.title));
+            return;
+        }
+
+        let pre = HookPhase::around(&action)
+            .map(|(pre, _)| pre)
+            .filter(|pre| self.config.hooks.iter().any(|h| h.phase == *pre));
+        let Some(pre) = pre else {
+            self.start_after_hooks(title, action, argv, size);
+            return;
+        };
+
+        // Hooks can take a while (shutting down a VM), so they run in the background and
+        // the transaction starts once they all passed
+        let hooks = self.config.hooks.clone();
+        let packages = self.affected_packages(&action);
+        let transaction = title.to_string();
+        let tx = self.task_tx.clone();
+        thread::spawn(move || {
+            let context = HookContext {
+                transaction: &transaction,
+                packages: &packages,
+                exit_code: None,
+            };
+            let _ = tx.send(TaskResult::PreHooks(run_hooks(&hooks, pre, &context)));
+        });
+        self.hooked_transaction = Some(HookedTransaction {
+            title: title.to_string(),
+            phase: pre,
+            action,
+            argv,
+            size,
+        });
+    }
+
+    /// Take the snapshot (if any) and run the transaction whose pre hooks have passed
+    fn start_after_hooks(&mut self, title: &str, action: Action, argv: Vec<String>, size: (u16, u16)) {
+        let takes_snapshot = matches!(
+            action,
+            Action::RunUpdate(_)
+                | Action::Uninstall(_)
+                | Action::UninstallWithDeps(_)
+                | Action::Reinstall(_)
+                | Action::ForceRebuild(_)
+                | Action::RunRebuild(_)
+                | Action::Downgrade(..)
+        );
+        let Some(snapshot) = self.config.snapshot.as_ref().filter(|_| takes_snapshot) else {
+            self.spawn_transaction(title, action, &argv, size);
+            return;
+        };
+
+        let label = format!("upkeep: {}", title);
+        match snapshot.create_command(&label, chrono::Local::now()) {
+            Ok(snapshot_argv) => {
+                let snapshot_title = format!("Snapshot before {}", title);
+                self.spawn_transaction(&snapshot_title, Action::TakeSnapshot, &snapshot_argv, size);
+                self.queued_transaction = Some(QueuedTransaction {
+                    title: title.to_string(),
+                    action,
+                    argv,
+                    size,
+                    snapshot_argv,
+                });
+            }
+            Err(e) => {
+                self.last_message = Some(format!("{} not run, no snapshot taken: {}", title, e))
+            }
+        }
+    }
+
+    fn spawn_transaction(&mut self, title: &str, action: Action, argv: &[String], size: (u16, u16)) {
+        match Transaction::spawn(title.to_string(), action, argv, size) {
+            Ok(transaction) => self.transaction = Some(transaction),
+            Err(e) => self.last_message = Some(format!("Failed to run {}: {}", argv.join(" "), e)),
+        }
+    }
+
+    /// Packages a transaction changes, as passed to its hooks
+    /// Rebuild commands are opaque, so they have none
+    fn affected_packages(&self, action: &Action) -> Vec<String> {
+        match action {
+            Action::RunUpdate(names) if names.is_empty() => self
+                .packages
+                .iter()
+                .filter(|p| self.package_hold(p).is_none())
+                .map(|p| p.name.clone())
+                .collect(),
+            Action::RunUpdate(names)
+            | Action::Install(names)
+            | Action::Reinstall(names)
+            | Action::ForceRebuild(names)
+            | Action::Uninstall(names)
+            | Action::UninstallWithDeps(names) => names.clone(),
+            Action::Downgrade(candidate, _) => vec![candidate.name.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Run the post hooks of the transaction that just exited, in the background
+    fn start_post_hooks(&mut self) {
+        let Some(transaction) = &self.transaction else {
+            return;
+        };
+        let Some((_, post)) = HookPhase::around(&transaction.action) else {
+            return;
+        };
+        if !self.config.hooks.iter().any(|h| h.phase == post) {
+            return;
+        }
+
+        let hooks = self.config.hooks.clone();
+        let packages = self.affected_packages(&transaction.action);
+        let title = transaction.title.clone();
+        let exit_code = transaction.exit_code();
+        let tx = self.task_tx.clone();
+        thread::spawn(move || {
+            let context = HookContext {
+                transaction: &title,
+                packages: &packages,
+                exit_code,
+            };
+            let _ = tx.send(TaskResult::PostHooks(run_hooks(&hooks, post, &context)));
+        });
+    }
+
+    /// Record the snapshot that just finished and start the transaction waiting on it
+    /// A failed snapshot stays on screen and the transaction is dropped
+    fn finish_snapshot(&mut self) {
+        let (Some(queued), Some(snapshot_run), Some(config)) =
+            (self.queued_transaction.take(), &self.transaction, &self.config.snapshot)
+        else {
+            return;
+        };
+        if !snapshot_run.succeeded() {
+            self.last_message = Some(format!("Snapshot failed, {} was not run", queued.title));
+            return;
+        }
+
+        let id = config.parse_id(&queued.snapshot_argv, &snapshot_run.output());
+        if let Some(id) = &id {
+            self.state.snapshots.push(Snapshot::new(id.clone(), config.provider, &queued.title));
+            self.save_state();
+        }
+        self.spawn_transaction(&queued.title, queued.action, &queued.argv, queued.size);
+        if let Some(transaction) = &mut self.transaction {
+            transaction.snapshot = id;
+        }
+    }
+
+    /// Close a finished transaction and refresh whatever it changed
+    fn close_transaction(&mut self) {
+        let Some(transaction) = self.transaction.take() else {
+            return;
+        };
+
+        let succeeded = transaction.succeeded();
+        self.last_message = Some(if succeeded {
+            format!("{} finished", transaction.title)
+        } else {
+            format!(
+                "{} exited with status {}",
+                transaction.title,
+                transaction.exit_code().unwrap_or(1)
+            )
+        });
+        if let Some(errors) = self.hook_errors.take() {
+            self.last_message = Some(errors);
+        }
+
+        if let Some(id) = &transaction.snapshot {
+            if let Some(snapshot) = self.state.snapshots.iter_mut().rev().find(|s| &s.id == id) {
+                snapshot.finish(succeeded);
+            }
+            self.save_state();
+        }
+
+        // Installs and removals change what differs from packages.toml
+        let explicit_changed = matches!(
+            transaction.action,
+            Action::Install(_)
+                | Action::Uninstall(_)
+                | Action::UninstallWithDeps(_)
+                | Action::MarkAsDeps(_)
+        );
+
+        match transaction.action {
+            Action::RunUpdate(packages) => {
+                if succeeded {
+                    let devel = self.devel_updates(&packages);
+                    self.record_devel_revisions(&devel);
+                }
+                // Only full upgrades move the news gate forward
+                if packages.is_empty() && succeeded {
+                    self.state.last_upgrade = Some(chrono::Local::now().timestamp());
+                    self.save_state();
+                }
+                self.refresh();
+            }
+            Action::RunRebuild(_) => self.refresh_rebuilds(),
+            Action::Uninstall(_) | Action::UninstallWithDeps(_) | Action::MarkAsDeps(_) => {
+                self.refresh_installed();
+                self.refresh_orphans();
+            }
+            Action::Reinstall(packages) | Action::ForceRebuild(packages) => {
+                if succeeded {
+                    self.record_devel_revisions(&packages);
+                }
+                self.refresh_installed();
+            }
+            Action::Install(packages) => {
+                if succeeded {
+                    self.record_devel_revisions(&packages);
+                }
+                self.refresh_installed();
+                // Re-run search to update installed status
+                self.do_search();
+            }
+            Action::KeepCurrentConfig(_)
+            | Action::TakeNewConfig(_)
+            | Action::MergeConfig(_)
+            | Action::DeletePacnew(_) => self.refresh_pacnew(),
+            Action::DeleteSnapshots(ids) if succeeded => {
+                self.state.snapshots.retain(|s| !ids.contains(&s.id));
+                self.save_state();
+                self.clamp_snapshots_selection();
+            }
+            Action::Downgrade(candidate, hold) => {
+                if hold && succeeded {
+                    // Keeps the bounds of an existing hold
+                    self.config.add_hold(&candidate.name, None, None);
+                    self.save_holds();
+                }
+                self.refresh();
+            }
+            _ => {}
+        }
+
+        if explicit_changed && self.manifest.is_some() {
+            self.refresh_drift();
+        }
+
+        // Package changes and service restarts both affect what still needs restarting
+        if succeeded {
+            self.refresh_restart();
+        }
+    }
+
+    fn handle_transaction_key(&mut self, key: KeyEvent) -> Action {
+        let Some(transaction) = &mut self.transaction else {
+            return Action::None;
+        };
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
 
-        thread::spawn(move || {
-            let news = fetch_news(&installed_names);
-            let _ = tx.send(TaskResult::News(news));
-        });
+        match key.code {
+            KeyCode::PageUp if shift || !transaction.is_running() => transaction.scroll(10),
+            KeyCode::PageDown if shift || !transaction.is_running() => transaction.scroll(-10),
+            _ if transaction.is_running() => transaction.send_key(key),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => self.close_transaction(),
+            _ => {}
+        }
+        Action::None
     }
 
     pub fn poll_tasks(&mut self) {
+        if let Some(transaction) = &mut self.transaction {
+            if transaction.poll() {
+                if matches!(transaction.action, Action::TakeSnapshot) {
+                    self.finish_snapshot();
+                } else {
+                    self.start_post_hooks();
+                }
+            }
+        }
+
         // Collect results first to avoid borrow issues
         let results: Vec<TaskResult> = if let Some(rx) = &self.task_rx {
             let mut collected = Vec::new();
@@ -241,7 +1691,7 @@ impl App {
                     self.pending_tasks = self.pending_tasks.saturating_sub(1);
                     self.packages = pacman;
                     self.packages.extend(aur);
-                    self.clamp_list_selection();
+                    self.merge_devel_packages();
                     if self.show_info_pane && self.tab == Tab::Updates {
                         self.refresh_package_info();
                     }
@@ -256,6 +1706,21 @@ impl App {
                     // Re-match news items now that we have installed packages
                     self.rematch_news_packages();
                 }
+                TaskResult::AurStatus(result) => {
+                    self.pending_tasks = self.pending_tasks.saturating_sub(1);
+                    // Offline: keep the badges from the last successful refresh
+                    if let Ok(status) = result {
+                        self.apply_aur_status(status);
+                    }
+                }
+                TaskResult::Devel(heads, versions) => {
+                    self.pending_tasks = self.pending_tasks.saturating_sub(1);
+                    self.apply_devel_heads(heads, &versions);
+                    self.merge_devel_packages();
+                    if self.show_info_pane && self.tab == Tab::Updates {
+                        self.refresh_package_info();
+                    }
+                }
                 TaskResult::Orphans(orphans) => {
                     self.pending_tasks = self.pending_tasks.saturating_sub(1);
                     self.orphan_packages = orphans;
@@ -264,14 +1729,36 @@ impl App {
                         self.refresh_package_info();
                     }
                 }
-                TaskResult::Rebuilds(issues) => {
-                    self.pending_tasks = self.pending_tasks.saturating_sub(1);
-                    self.rebuild_issues = issues;
-                    self.clamp_rebuilds_selection();
-                    if self.show_info_pane && self.tab == Tab::Rebuilds {
+                TaskResult::Drift(drift) => {
+                    self.drift_loading = false;
+                    self.drift = drift;
+                    self.clamp_drift_selection();
+                    if self.show_info_pane && self.tab == Tab::Drift {
                         self.refresh_package_info();
                     }
                 }
+                TaskResult::TriggerVersions(run_id, versions) => {
+                    if run_id == self.current_rebuild_run {
+                        self.trigger_versions = versions;
+                    }
+                }
+                TaskResult::Check(run_id, event) => {
+                    // Ignore progress from a run that has since been restarted
+                    if run_id == self.current_rebuild_run {
+                        self.handle_check_event(event);
+                    }
+                }
+                TaskResult::Rebuilds(run_id, scanned) => {
+                    // Every run ends with one of these, stale or not
+                    self.pending_tasks = self.pending_tasks.saturating_sub(1);
+                    if run_id == self.current_rebuild_run {
+                        merge_scanned(&mut self.rebuild_issues, scanned);
+                        self.clamp_rebuilds_selection();
+                        if self.show_info_pane && self.tab == Tab::Rebuilds {
+                            self.refresh_package_info();
+                        }
+                    }
+                }
                 TaskResult::Search(search_id, results) => {
                     // Only use results if this is the current search (ignore stale results)
                     if search_id == self.current_search_id {
@@ -300,8 +1787,9 @@ impl App {
                 TaskResult::News(result) => {
                     self.news_loading = false;
                     match result {
-                        Ok(items) => {
-                            self.news_items = items;
+                        Ok(feed) => {
+                            self.news_items = feed.items;
+                            self.news_offline = feed.offline;
                             self.news_error = false;
                             self.clamp_news_selection();
                             // Auto-select first item if none selected
@@ -310,6 +1798,9 @@ impl App {
                             {
                                 self.news_list_state.select(Some(0));
                             }
+                            if self.tab == Tab::News {
+                                self.mark_selected_news_read();
+                            }
                             if self.show_info_pane && self.tab == Tab::News {
                                 self.refresh_news_info();
                             }
@@ -319,6 +1810,111 @@ impl App {
                         }
                     }
                 }
+                TaskResult::History(result) => {
+                    self.history_loading = false;
+                    match result {
+                        Ok(entries) => {
+                            self.history = entries;
+                            self.clamp_filter_selection();
+                            if self.history_list_state.selected().is_none() && !self.history.is_empty() {
+                                self.history_list_state.select(Some(0));
+                            }
+                        }
+                        Err(e) => self.history_error = Some(e),
+                    }
+                }
+                TaskResult::Pacnew(files) => {
+                    self.pacnew_loading = false;
+                    self.pacnew_files = files;
+                    clamp_selection(&mut self.pacnew_list_state, self.pacnew_files.len());
+                    self.refresh_pacnew_diff();
+                }
+                TaskResult::PreHooks(errors) => {
+                    if let Some(hooked) = self.hooked_transaction.take() {
+                        match errors.first() {
+                            Some(error) => {
+                                let message = format!("{} not run: {}", hooked.title, error);
+                                self.last_message = Some(message);
+                            }
+                            None => self.start_after_hooks(
+                                &hooked.title,
+                                hooked.action,
+                                hooked.argv,
+                                hooked.size,
+                            ),
+                        }
+                    }
+                }
+                TaskResult::PostHooks(errors) => {
+                    if !errors.is_empty() {
+                        let message = errors.join("; ");
+                        // Closing the pane would replace the message right away
+                        if self.transaction.is_some() {
+                            self.hook_errors = Some(message);
+                        } else {
+                            self.last_message = Some(message);
+                        }
+                    }
+                }
+                TaskResult::Restart(status) => {
+                    self.restart_status = Some(status);
+                }
+                TaskResult::PacnewDiff(path, diff) => {
+                    // Ignore diffs for a file that is no longer under the cursor
+                    if self.selected_pacnew().is_some_and(|f| f.path == path) {
+                        self.pacnew_diff = diff;
+                    }
+                }
+                TaskResult::Review(name, result) => {
+                    if let Some(state) = &mut self.review {
+                        if let Some(i) = state.names.iter().position(|n| *n == name) {
+                            state.reviews[i] = Some(result);
+                        }
+                    }
+                }
+                TaskResult::Import(source, result) => {
+                    // Ignore a list the picker has since been closed on
+                    let Some(state) = self.import.as_mut().filter(|s| s.source == Some(source))
+                    else {
+                        continue;
+                    };
+                    state.loading = false;
+                    match result {
+                        Ok((entries, installed)) => {
+                            state.list_state.select((!entries.is_empty()).then_some(0));
+                            state.entries = entries;
+                            state.installed = installed;
+                        }
+                        Err(e) => state.error = Some(e),
+                    }
+                }
+                TaskResult::Templates(templates) => {
+                    if let Some(state) = &mut self.template_picker {
+                        state.chosen = vec![false; templates.len()];
+                        state.list_state.select((!templates.is_empty()).then_some(0));
+                        state.templates = templates;
+                        state.loading = false;
+                    }
+                }
+                TaskResult::Versions(name, result) => {
+                    // Ignore results for a picker that was closed or reopened on another package
+                    let Some(state) = self.downgrade.as_mut().filter(|s| s.name == name) else {
+                        continue;
+                    };
+                    state.loading = false;
+                    match result {
+                        Ok(candidates) => {
+                            state.candidates = candidates
+                                .into_iter()
+                                .filter(|c| c.version != state.installed_version)
+                                .collect();
+                            if !state.candidates.is_empty() {
+                                state.list_state.select(Some(0));
+                            }
+                        }
+                        Err(e) => state.error = Some(e),
+                    }
+                }
             }
         }
 
@@ -343,15 +1939,28 @@ impl App {
         clamp_selection(&mut self.orphans_list_state, self.orphan_packages.len());
     }
 
+    fn clamp_drift_selection(&mut self) {
+        clamp_selection(&mut self.drift_list_state, self.drift.len());
+    }
+
     fn clamp_news_selection(&mut self) {
         clamp_selection(&mut self.news_list_state, self.news_items.len());
     }
 
     fn load_tab_data(&mut self) {
+        // Switching to the News tab shows (and so reads) the selected item
+        if self.tab == Tab::News {
+            self.mark_selected_news_read();
+        }
+
         match self.tab {
             Tab::Installed if self.installed_packages.is_empty() => self.refresh_installed(),
             Tab::Orphans if self.orphan_packages.is_empty() => self.refresh_orphans(),
+            Tab::Drift if self.drift.is_empty() => self.refresh_drift(),
             Tab::News if self.news_items.is_empty() => self.refresh_news(),
+            Tab::History if self.history.is_empty() => self.refresh_history(),
+            Tab::Pacnew if self.pacnew_files.is_empty() => self.refresh_pacnew(),
+            Tab::Snapshots => self.clamp_snapshots_selection(),
             _ => {}
         }
     }
@@ -366,7 +1975,17 @@ impl App {
                 let len = self.filtered_installed().len();
                 clamp_selection(&mut self.installed_list_state, len);
             }
-            Tab::Orphans | Tab::Rebuilds | Tab::Search | Tab::News => {}
+            Tab::History => {
+                let len = self.filtered_history().len();
+                clamp_selection(&mut self.history_list_state, len);
+            }
+            Tab::Orphans
+            | Tab::Drift
+            | Tab::Rebuilds
+            | Tab::Pacnew
+            | Tab::Search
+            | Tab::News
+            | Tab::Snapshots => {}
         }
     }
 
@@ -380,6 +1999,14 @@ impl App {
                 | Action::Reinstall(_)
                 | Action::ForceRebuild(_)
                 | Action::Install(_)
+                | Action::MarkAsDeps(_)
+                | Action::Downgrade(..)
+                | Action::KeepCurrentConfig(_)
+                | Action::TakeNewConfig(_)
+                | Action::MergeConfig(_)
+                | Action::DeletePacnew(_)
+                | Action::RestartServices(_)
+                | Action::DeleteSnapshots(_)
                 | Action::RunRebuild(_)
                 | Action::CleanCache
         )
@@ -390,17 +2017,23 @@ impl App {
         let (title, items, message) = match &action {
             Action::RunUpdate(pkgs) => {
                 if pkgs.is_empty() {
-                    // Update all
+                    // Update all (held packages are ignored)
                     let items: Vec<String> = self
                         .packages
                         .iter()
+                        .filter(|p| self.package_hold(p).is_none())
                         .map(|p| format!("{} {} → {}", p.name, p.old_version, p.new_version))
                         .collect();
-                    let count = self.packages.len();
+                    let count = items.len();
+                    let held = self.held_count();
                     (
                         "Update All Packages".to_string(),
                         items,
-                        format!("{} package(s) will be updated", count),
+                        if held > 0 {
+                            format!("{} package(s) will be updated, {} held", count, held)
+                        } else {
+                            format!("{} package(s) will be updated", count)
+                        },
                     )
                 } else {
                     // Update selected
@@ -443,6 +2076,72 @@ impl App {
                 pkgs.clone(),
                 format!("{} package(s) will be installed", pkgs.len()),
             ),
+            Action::MarkAsDeps(pkgs) => (
+                "Mark as Dependencies".to_string(),
+                pkgs.clone(),
+                format!(
+                    "{} package(s) will be marked as dependencies; unneeded ones become orphans",
+                    pkgs.len()
+                ),
+            ),
+            Action::Downgrade(candidate, hold) => {
+                let installed = self
+                    .installed_packages
+                    .iter()
+                    .find(|p| p.name == candidate.name)
+                    .map(|p| p.version.as_str())
+                    .unwrap_or("?");
+                (
+                    "Downgrade Package".to_string(),
+                    vec![format!(
+                        "{} {} → {} ({})",
+                        candidate.name,
+                        installed,
+                        candidate.version,
+                        candidate.source.label()
+                    )],
+                    if *hold {
+                        "Package will be installed with pacman -U and held".to_string()
+                    } else {
+                        "Package will be installed with pacman -U".to_string()
+                    },
+                )
+            }
+            Action::KeepCurrentConfig(file) => (
+                "Keep Current Config".to_string(),
+                vec![file.path.clone()],
+                format!("The {} file will be deleted", file.kind.label()),
+            ),
+            Action::TakeNewConfig(file) => (
+                "Take New Config".to_string(),
+                vec![format!("{} → {}", file.path, file.target)],
+                "The live config will be replaced".to_string(),
+            ),
+            Action::MergeConfig(file) => (
+                "Merge Config".to_string(),
+                vec![file.target.clone(), file.path.clone()],
+                "Both files will be opened in $DIFFPROG (or $EDITOR via sudoedit)".to_string(),
+            ),
+            Action::DeletePacnew(paths) => (
+                "Delete Files".to_string(),
+                paths.clone(),
+                format!("{} file(s) will be deleted", paths.len()),
+            ),
+            Action::RestartServices(units) => (
+                "Restart Services".to_string(),
+                units.clone(),
+                format!("{} service(s) will be restarted with systemctl", units.len()),
+            ),
+            Action::DeleteSnapshots(ids) => (
+                "Delete Snapshots".to_string(),
+                self.state
+                    .snapshots
+                    .iter()
+                    .filter(|s| ids.contains(&s.id))
+                    .map(|s| format!("{} ({})", s.id, s.transaction))
+                    .collect(),
+                format!("{} snapshot(s) will be deleted", ids.len()),
+            ),
             Action::RunRebuild(cmd) => (
                 "Run Rebuild Command".to_string(),
                 vec![cmd.clone()],
@@ -456,18 +2155,46 @@ impl App {
             _ => unreachable!(),
         };
 
+        // Full and partial upgrades are gated on unacknowledged manual-intervention news,
+        // and on the feed having loaded: no news is not the same as news that failed to load
+        let (news, news_warning) = match &action {
+            Action::RunUpdate(_) => {
+                let warning = if self.news_loading {
+                    Some("The news feed is still loading; manual interventions are unchecked")
+                } else if self.news_error {
+                    Some("The news feed could not be fetched; manual interventions are unchecked")
+                } else {
+                    None
+                };
+                (
+                    self.unacknowledged_news().into_iter().cloned().collect(),
+                    warning.map(String::from),
+                )
+            }
+            _ => (Vec::new(), None),
+        };
+
         ConfirmationState {
             action,
             title,
             items,
             message,
+            news,
+            news_warning,
+            acknowledged: false,
+            reviewed: Vec::new(),
         }
     }
 
     /// Wrap an action with confirmation if required
     fn maybe_confirm(&mut self, action: Action) -> Action {
         if Self::requires_confirmation(&action) {
-            self.pending_confirmation = Some(self.build_confirmation(action));
+            let aur = self.aur_packages(&action);
+            if self.config.review_aur && !aur.is_empty() {
+                self.open_review(action, aur);
+            } else {
+                self.pending_confirmation = Some(self.build_confirmation(action));
+            }
             Action::None
         } else {
             action
@@ -475,10 +2202,37 @@ impl App {
     }
 
     fn handle_confirmation_key(&mut self, key: KeyEvent) -> Action {
+        let needs_acknowledgement = self
+            .pending_confirmation
+            .as_ref()
+            .is_some_and(|c| c.needs_acknowledgement());
+
         match key.code {
+            KeyCode::Char('a') => {
+                if let Some(confirmation) = &mut self.pending_confirmation {
+                    confirmation.acknowledged = true;
+                }
+                Action::None
+            }
+            KeyCode::Enter | KeyCode::Char('y') if needs_acknowledgement => {
+                self.last_message = Some("Read and acknowledge the news first (a)".to_string());
+                Action::None
+            }
             KeyCode::Enter | KeyCode::Char('y') => {
-                let state = self.pending_confirmation.take().unwrap();
-                state.action
+                let confirmation = self.pending_confirmation.take().unwrap();
+                if confirmation.news.is_empty() && confirmation.reviewed.is_empty() {
+                    return confirmation.action;
+                }
+                for item in &confirmation.news {
+                    self.state.acknowledge_news(&item.link);
+                }
+                // Accepted revisions are what the next review diffs against
+                for review in confirmation.reviewed {
+                    self.state.aur_maintainer_changes.remove(&review.name);
+                    self.state.reviewed_aur.insert(review.name, review.source);
+                }
+                self.save_state();
+                confirmation.action
             }
             KeyCode::Esc | KeyCode::Char('n') => {
                 self.pending_confirmation = None;
@@ -492,11 +2246,40 @@ impl App {
         // Clear any flash message on key press
         self.last_message = None;
 
+        // Keys go to the embedded terminal while a transaction is shown
+        if self.transaction.is_some() {
+            return self.handle_transaction_key(key);
+        }
+
         // Handle confirmation mode first
         if self.pending_confirmation.is_some() {
             return self.handle_confirmation_key(key);
         }
 
+        if self.downgrade.is_some() {
+            return self.handle_downgrade_key(key.code);
+        }
+
+        if self.template_picker.is_some() {
+            return self.handle_template_key(key.code);
+        }
+
+        if self.review.is_some() {
+            return self.handle_review_key(key.code);
+        }
+
+        if self.import.is_some() {
+            return self.handle_import_key(key.code);
+        }
+
+        if self.hold_prompt.is_some() {
+            return self.handle_hold_prompt_key(key.code);
+        }
+
+        if self.show_restart {
+            return self.handle_restart_key(key.code);
+        }
+
         // Handle filter mode input
         if self.filter_mode {
             match key.code {
@@ -537,6 +2320,12 @@ impl App {
             self.handle_search_key(key.code)
         } else if self.tab == Tab::News {
             self.handle_news_key(key)
+        } else if self.tab == Tab::Pacnew {
+            self.handle_pacnew_key(key)
+        } else if self.tab == Tab::Snapshots {
+            self.handle_snapshots_key(key)
+        } else if self.tab == Tab::Drift {
+            self.handle_drift_key(key.code)
         } else {
             self.handle_normal_key(key.code)
         }
@@ -555,7 +2344,7 @@ impl App {
                 }
             }
             KeyCode::Tab => {
-                self.tab = Tab::News;
+                self.tab = self.tab.next();
                 self.load_tab_data();
                 if self.show_info_pane {
                     self.refresh_news_info();
@@ -563,7 +2352,7 @@ impl App {
                 Action::None
             }
             KeyCode::BackTab => {
-                self.tab = Tab::Rebuilds;
+                self.tab = self.tab.prev();
                 self.load_tab_data();
                 if self.show_info_pane {
                     self.refresh_package_info();
@@ -619,7 +2408,7 @@ impl App {
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
             KeyCode::Tab => {
-                self.tab = Tab::Updates;
+                self.tab = self.tab.next();
                 self.load_tab_data();
                 if self.show_info_pane {
                     self.refresh_package_info();
@@ -627,7 +2416,7 @@ impl App {
                 Action::None
             }
             KeyCode::BackTab => {
-                self.tab = Tab::Search;
+                self.tab = self.tab.prev();
                 self.load_tab_data();
                 if self.show_info_pane {
                     self.refresh_package_info();
@@ -668,6 +2457,14 @@ impl App {
                 self.refresh_news();
                 Action::None
             }
+            KeyCode::Char('M') => {
+                self.mark_all_news_read();
+                Action::None
+            }
+            KeyCode::Char('R') => {
+                self.toggle_restart_panel();
+                Action::None
+            }
             KeyCode::Char('?') => {
                 self.show_info_pane = !self.show_info_pane;
                 if self.show_info_pane {
@@ -681,18 +2478,220 @@ impl App {
         }
     }
 
+    fn handle_pacnew_key(&mut self, key: KeyEvent) -> Action {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down if shift => {
+                self.pacnew_scroll = self.pacnew_scroll.saturating_add(3);
+                self.clamp_pacnew_scroll();
+                Action::None
+            }
+            KeyCode::Char('k') | KeyCode::Up if shift => {
+                self.pacnew_scroll = self.pacnew_scroll.saturating_sub(3);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.pacnew_scroll = self.pacnew_scroll.saturating_add(10);
+                self.clamp_pacnew_scroll();
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.pacnew_scroll = self.pacnew_scroll.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('o') => match self.selected_pacnew() {
+                Some(file) => {
+                    let action = Action::KeepCurrentConfig(file.clone());
+                    self.maybe_confirm(action)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('t') => match self.selected_pacnew() {
+                Some(file) => {
+                    let action = Action::TakeNewConfig(file.clone());
+                    self.maybe_confirm(action)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('e') => match self.selected_pacnew() {
+                Some(file) => {
+                    let action = Action::MergeConfig(file.clone());
+                    self.maybe_confirm(action)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('d') => {
+                let action = self.delete_selected_pacnew();
+                self.maybe_confirm(action)
+            }
+            // Navigation, selection, refresh and tab switching work as on other tabs
+            code => self.handle_normal_key(code),
+        }
+    }
+
+    fn handle_snapshots_key(&mut self, key: KeyEvent) -> Action {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down if shift => {
+                self.snapshot_scroll = self.snapshot_scroll.saturating_add(3);
+                Action::None
+            }
+            KeyCode::Char('k') | KeyCode::Up if shift => {
+                self.snapshot_scroll = self.snapshot_scroll.saturating_sub(3);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.snapshot_scroll = self.snapshot_scroll.saturating_add(10);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.snapshot_scroll = self.snapshot_scroll.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('d') => match self.selected_snapshot() {
+                Some(snapshot) => {
+                    let ids = vec![snapshot.id.clone()];
+                    if let Err(e) = self.snapshot_delete_command(&ids) {
+                        self.last_message = Some(format!("Cannot delete snapshot: {}", e));
+                        return Action::None;
+                    }
+                    self.maybe_confirm(Action::DeleteSnapshots(ids))
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('P') => {
+                let expired = self.expired_snapshots();
+                if expired.is_empty() {
+                    self.last_message = Some("Nothing to prune".to_string());
+                    return Action::None;
+                }
+                self.maybe_confirm(Action::DeleteSnapshots(expired))
+            }
+            code => self.handle_normal_key(code),
+        }
+    }
+
+    fn handle_drift_key(&mut self, key: KeyCode) -> Action {
+        match key {
+            KeyCode::Char('i') => {
+                let missing = self.missing_targets();
+                if missing.is_empty() {
+                    self.last_message = Some("Nothing missing".to_string());
+                    return Action::None;
+                }
+                self.maybe_confirm(Action::Install(missing))
+            }
+            // A partial manifest leaves base, the kernel and the bootloader undeclared, so
+            // these only act on what was selected
+            KeyCode::Char('m') | KeyCode::Char('d') | KeyCode::Char('D') => {
+                let extra: Vec<String> = self
+                    .drift
+                    .iter()
+                    .filter(|e| e.kind == DriftKind::Extra && e.selected)
+                    .map(|e| e.name.clone())
+                    .collect();
+                if extra.is_empty() {
+                    self.last_message =
+                        Some("Select the undeclared packages to act on (Space)".to_string());
+                    return Action::None;
+                }
+                let action = match key {
+                    KeyCode::Char('m') => Action::MarkAsDeps(extra),
+                    KeyCode::Char('d') => Action::Uninstall(extra),
+                    _ => Action::UninstallWithDeps(extra),
+                };
+                self.maybe_confirm(action)
+            }
+            KeyCode::Char('w') => {
+                self.write_manifest();
+                Action::None
+            }
+            code => self.handle_normal_key(code),
+        }
+    }
+
+    /// Selected missing packages, or all of them if none are selected
+    fn missing_targets(&self) -> Vec<String> {
+        let entries: Vec<&DriftEntry> =
+            self.drift.iter().filter(|e| e.kind == DriftKind::Missing).collect();
+        let selected: Vec<&DriftEntry> = entries.iter().copied().filter(|e| e.selected).collect();
+        let targets = if selected.is_empty() { entries } else { selected };
+        targets.iter().map(|e| e.name.clone()).collect()
+    }
+
+    /// Snapshots newest first, as listed on the Snapshots tab
+    pub fn snapshot_list(&self) -> Vec<&Snapshot> {
+        self.state.snapshots.iter().rev().collect()
+    }
+
+    pub fn selected_snapshot(&self) -> Option<&Snapshot> {
+        let idx = self.snapshots_list_state.selected()?;
+        self.snapshot_list().get(idx).copied()
+    }
+
+    /// IDs of the snapshots beyond the configured number to keep, oldest first
+    /// Only snapshots of the configured provider count, as only those can be deleted
+    fn expired_snapshots(&self) -> Vec<String> {
+        let Some(config) = &self.config.snapshot else {
+            return Vec::new();
+        };
+        let own: Vec<&Snapshot> =
+            self.state.snapshots.iter().filter(|s| s.provider == config.provider).collect();
+        let excess = own.len().saturating_sub(config.keep);
+        own[..excess].iter().map(|s| s.id.clone()).collect()
+    }
+
+    /// Command deleting snapshots upkeep took
+    /// Snapshots of a provider that is no longer configured are refused: their IDs mean
+    /// nothing to the current one (a snapper number is not a btrfs path)
+    pub fn snapshot_delete_command(&self, ids: &[String]) -> Result<Vec<String>, String> {
+        let config = self.config.snapshot.as_ref().ok_or("no snapshot provider is configured")?;
+        let foreign: Vec<String> = self
+            .state
+            .snapshots
+            .iter()
+            .filter(|s| ids.contains(&s.id) && s.provider != config.provider)
+            .map(|s| format!("{} was taken with {}", s.id, s.provider.label()))
+            .collect();
+        if !foreign.is_empty() {
+            return Err(format!("{}, which is no longer configured", foreign.join(", ")));
+        }
+        config.delete_command(ids)
+    }
+
+    fn clamp_snapshots_selection(&mut self) {
+        clamp_selection(&mut self.snapshots_list_state, self.state.snapshots.len());
+    }
+
+    fn clamp_pacnew_scroll(&mut self) {
+        let max_scroll = self.pacnew_diff.len().saturating_sub(3) as u16;
+        self.pacnew_scroll = self.pacnew_scroll.min(max_scroll);
+    }
+
+    fn delete_selected_pacnew(&self) -> Action {
+        let selected: Vec<String> = self
+            .pacnew_files
+            .iter()
+            .filter(|f| f.selected)
+            .map(|f| f.path.clone())
+            .collect();
+
+        if !selected.is_empty() {
+            return Action::DeletePacnew(selected);
+        }
+        match self.selected_pacnew() {
+            Some(file) => Action::DeletePacnew(vec![file.path.clone()]),
+            None => Action::None,
+        }
+    }
+
     fn handle_normal_key(&mut self, key: KeyCode) -> Action {
         match key {
             KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
             KeyCode::Tab => {
-                self.tab = match self.tab {
-                    Tab::Updates => Tab::Installed,
-                    Tab::Installed => Tab::Orphans,
-                    Tab::Orphans => Tab::Rebuilds,
-                    Tab::Rebuilds => Tab::Search,
-                    Tab::Search => Tab::News,
-                    Tab::News => Tab::Updates,
-                };
+                self.tab = self.tab.next();
                 self.filter_mode = false;
                 self.filter_text.clear();
                 self.load_tab_data();
@@ -702,14 +2701,7 @@ impl App {
                 Action::None
             }
             KeyCode::BackTab => {
-                self.tab = match self.tab {
-                    Tab::Updates => Tab::News,
-                    Tab::Installed => Tab::Updates,
-                    Tab::Orphans => Tab::Installed,
-                    Tab::Rebuilds => Tab::Orphans,
-                    Tab::Search => Tab::Rebuilds,
-                    Tab::News => Tab::Search,
-                };
+                self.tab = self.tab.prev();
                 self.filter_mode = false;
                 self.filter_text.clear();
                 self.load_tab_data();
@@ -743,7 +2735,11 @@ impl App {
                     Tab::Updates => self.refresh(),
                     Tab::Installed => self.refresh_installed(),
                     Tab::Orphans => self.refresh_orphans(),
+                    Tab::Drift => self.refresh_drift(),
                     Tab::Rebuilds => self.refresh_rebuilds(),
+                    Tab::Pacnew => self.refresh_pacnew(),
+                    Tab::History => self.refresh_history(),
+                    Tab::Snapshots => self.clamp_snapshots_selection(),
                     Tab::Search | Tab::News => {} // Search has its own refresh, News handled by handle_news_key
                 }
                 Action::None
@@ -780,8 +2776,50 @@ impl App {
                 let action = self.reinstall_selected(true);
                 self.maybe_confirm(action)
             }
+            KeyCode::Char('h') => {
+                self.toggle_hold(None);
+                Action::None
+            }
+            KeyCode::Char('H') => {
+                self.toggle_hold(Some(7));
+                Action::None
+            }
+            KeyCode::Char('V') => {
+                self.open_hold_prompt();
+                Action::None
+            }
+            KeyCode::Char('v') => {
+                self.open_downgrade();
+                Action::None
+            }
+            KeyCode::Char('b') => {
+                self.open_import();
+                Action::None
+            }
+            KeyCode::Char('p') if self.tab == Tab::Installed => {
+                self.aur_problems_only = !self.aur_problems_only;
+                self.clamp_filter_selection();
+                Action::None
+            }
+            KeyCode::Char('A') if self.tab == Tab::Rebuilds => {
+                let issues: Vec<&RebuildIssue> = self.rebuild_issues.iter().collect();
+                let action = self.rebuild_action(&issues);
+                self.maybe_confirm(action)
+            }
+            KeyCode::Char('F') if self.tab == Tab::Rebuilds => {
+                self.force_rebuild_checks();
+                Action::None
+            }
+            KeyCode::Char('t') if self.tab == Tab::Rebuilds => {
+                self.open_template_picker();
+                Action::None
+            }
+            KeyCode::Char('R') => {
+                self.toggle_restart_panel();
+                Action::None
+            }
             KeyCode::Char('f') => {
-                if self.tab == Tab::Updates || self.tab == Tab::Installed {
+                if matches!(self.tab, Tab::Updates | Tab::Installed | Tab::History) {
                     self.filter_mode = true;
                 }
                 Action::None
@@ -836,6 +2874,14 @@ impl App {
                     (current + delta).clamp(0, self.orphan_packages.len() as i32 - 1) as usize;
                 self.orphans_list_state.select(Some(new));
             }
+            Tab::Drift => {
+                if self.drift.is_empty() {
+                    return;
+                }
+                let current = self.drift_list_state.selected().unwrap_or(0) as i32;
+                let new = (current + delta).clamp(0, self.drift.len() as i32 - 1) as usize;
+                self.drift_list_state.select(Some(new));
+            }
             Tab::Rebuilds => {
                 if self.rebuild_issues.is_empty() {
                     return;
@@ -845,14 +2891,47 @@ impl App {
                     (current + delta).clamp(0, self.rebuild_issues.len() as i32 - 1) as usize;
                 self.rebuilds_list_state.select(Some(new));
             }
-            Tab::Search => {
-                if self.search_results.is_empty() {
+            Tab::Search => {
+                if self.search_results.is_empty() {
+                    return;
+                }
+                let current = self.search_list_state.selected().unwrap_or(0) as i32;
+                let new =
+                    (current + delta).clamp(0, self.search_results.len() as i32 - 1) as usize;
+                self.search_list_state.select(Some(new));
+            }
+            Tab::Pacnew => {
+                if self.pacnew_files.is_empty() {
+                    return;
+                }
+                let current = self.pacnew_list_state.selected().unwrap_or(0) as i32;
+                let new = (current + delta).clamp(0, self.pacnew_files.len() as i32 - 1) as usize;
+                self.pacnew_list_state.select(Some(new));
+                // The diff pane replaces the package info pane here
+                self.refresh_pacnew_diff();
+                return;
+            }
+            Tab::History => {
+                let filtered = self.filtered_history();
+                if filtered.is_empty() {
+                    return;
+                }
+                let current = self.history_list_state.selected().unwrap_or(0) as i32;
+                let new = (current + delta).clamp(0, filtered.len() as i32 - 1) as usize;
+                self.history_list_state.select(Some(new));
+                // History info pane is built from the log, no fetch needed
+                return;
+            }
+            Tab::Snapshots => {
+                let len = self.state.snapshots.len();
+                if len == 0 {
                     return;
                 }
-                let current = self.search_list_state.selected().unwrap_or(0) as i32;
-                let new =
-                    (current + delta).clamp(0, self.search_results.len() as i32 - 1) as usize;
-                self.search_list_state.select(Some(new));
+                let current = self.snapshots_list_state.selected().unwrap_or(0) as i32;
+                let new = (current + delta).clamp(0, len as i32 - 1) as usize;
+                self.snapshots_list_state.select(Some(new));
+                self.snapshot_scroll = 0;
+                return;
             }
             Tab::News => {
                 // News uses move_news_selection instead
@@ -896,6 +2975,13 @@ impl App {
                     }
                 }
             }
+            Tab::Drift => {
+                if let Some(i) = self.drift_list_state.selected() {
+                    if let Some(entry) = self.drift.get_mut(i) {
+                        entry.selected = !entry.selected;
+                    }
+                }
+            }
             Tab::Rebuilds => {
                 if let Some(i) = self.rebuilds_list_state.selected() {
                     if let Some(issue) = self.rebuild_issues.get_mut(i) {
@@ -903,6 +2989,13 @@ impl App {
                     }
                 }
             }
+            Tab::Pacnew => {
+                if let Some(i) = self.pacnew_list_state.selected() {
+                    if let Some(file) = self.pacnew_files.get_mut(i) {
+                        file.selected = !file.selected;
+                    }
+                }
+            }
             Tab::Search => {
                 if let Some(i) = self.search_list_state.selected() {
                     if let Some(result) = self.search_results.get_mut(i) {
@@ -910,14 +3003,21 @@ impl App {
                     }
                 }
             }
-            Tab::News => {} // News items are not selectable
+            // News, history and snapshots are not selectable
+            Tab::News | Tab::History | Tab::Snapshots => {}
         }
     }
 
     fn select_all(&mut self) {
         match self.tab {
             Tab::Updates => {
-                let indices: Vec<usize> = self.filtered_updates().iter().map(|(i, _)| *i).collect();
+                // Held packages stay out of bulk selection
+                let indices: Vec<usize> = self
+                    .filtered_updates()
+                    .iter()
+                    .filter(|(_, p)| self.package_hold(p).is_none())
+                    .map(|(i, _)| *i)
+                    .collect();
                 for idx in indices {
                     if let Some(pkg) = self.packages.get_mut(idx) {
                         pkg.selected = true;
@@ -938,11 +3038,21 @@ impl App {
                     pkg.selected = true;
                 }
             }
+            Tab::Drift => {
+                for entry in &mut self.drift {
+                    entry.selected = true;
+                }
+            }
             Tab::Rebuilds => {
                 for issue in &mut self.rebuild_issues {
                     issue.selected = true;
                 }
             }
+            Tab::Pacnew => {
+                for file in &mut self.pacnew_files {
+                    file.selected = true;
+                }
+            }
             Tab::Search => {
                 for result in &mut self.search_results {
                     if !result.installed {
@@ -950,7 +3060,8 @@ impl App {
                     }
                 }
             }
-            Tab::News => {} // News items are not selectable
+            // News, history and snapshots are not selectable
+            Tab::News | Tab::History | Tab::Snapshots => {}
         }
     }
 
@@ -978,17 +3089,28 @@ impl App {
                     pkg.selected = false;
                 }
             }
+            Tab::Drift => {
+                for entry in &mut self.drift {
+                    entry.selected = false;
+                }
+            }
             Tab::Rebuilds => {
                 for issue in &mut self.rebuild_issues {
                     issue.selected = false;
                 }
             }
+            Tab::Pacnew => {
+                for file in &mut self.pacnew_files {
+                    file.selected = false;
+                }
+            }
             Tab::Search => {
                 for result in &mut self.search_results {
                     result.selected = false;
                 }
             }
-            Tab::News => {} // News items are not selectable
+            // News, history and snapshots are not selectable
+            Tab::News | Tab::History | Tab::Snapshots => {}
         }
     }
 
@@ -1115,8 +3237,8 @@ impl App {
                 // Enter = update all
                 Action::RunUpdate(Vec::new())
             }
-            Tab::Installed | Tab::Orphans => {
-                // Enter does nothing on installed/orphans tab - use specific keys
+            Tab::Installed | Tab::Orphans | Tab::Drift => {
+                // Enter does nothing on installed/orphans/drift tab - use specific keys
                 Action::None
             }
             Tab::Rebuilds => {
@@ -1125,12 +3247,10 @@ impl App {
                     self.rebuild_issues.iter().filter(|i| i.selected).collect();
 
                 if !selected.is_empty() {
-                    let commands: Vec<String> =
-                        selected.iter().map(|i| i.rebuild_command.clone()).collect();
-                    Action::RunRebuild(commands.join(" && "))
+                    self.rebuild_action(&selected)
                 } else if let Some(i) = self.rebuilds_list_state.selected() {
                     if let Some(issue) = self.rebuild_issues.get(i) {
-                        Action::RunRebuild(issue.rebuild_command.clone())
+                        self.rebuild_action(&[issue])
                     } else {
                         Action::None
                     }
@@ -1138,9 +3258,9 @@ impl App {
                     Action::None
                 }
             }
-            Tab::Search | Tab::News => {
+            Tab::Pacnew | Tab::Search | Tab::News | Tab::History | Tab::Snapshots => {
                 // Enter = install selected (handled by handle_search_key)
-                // News has no action on Enter
+                // Pacnew uses specific keys, news and history have no action on Enter
                 Action::None
             }
         }
@@ -1156,10 +3276,60 @@ impl App {
     pub fn aur_count(&self) -> usize {
         self.packages
             .iter()
-            .filter(|p| p.source == PackageSource::Aur)
+            .filter(|p| p.source != PackageSource::Pacman)
             .count()
     }
 
+    /// Active hold on a pending update, if any
+    pub fn package_hold(&self, pkg: &Package) -> Option<&Hold> {
+        self.config.active_hold(&pkg.name, Some(&pkg.new_version))
+    }
+
+    /// One command fixing all given issues
+    /// Packages found by the built-in scans share a single AUR helper run, so the helper
+    /// can resolve build order between them; hand-written commands follow
+    fn rebuild_action(&self, issues: &[&RebuildIssue]) -> Action {
+        let packages: Vec<&str> = issues.iter().filter_map(|i| i.package.as_deref()).collect();
+        let mut commands = Vec::new();
+        if !packages.is_empty() {
+            commands.push(format!("{} -S --rebuild {}", self.config.aur_helper, packages.join(" ")));
+        }
+        commands.extend(
+            issues
+                .iter()
+                .filter(|i| i.package.is_none())
+                .map(|i| i.rebuild_command.clone()),
+        );
+
+        if commands.is_empty() {
+            Action::None
+        } else {
+            Action::RunRebuild(commands.join(" && "))
+        }
+    }
+
+    /// Pending updates currently held back
+    pub fn held_count(&self) -> usize {
+        self.packages.iter().filter(|p| self.package_hold(p).is_some()).count()
+    }
+
+    /// Held packages to pass as --ignore on a full upgrade
+    pub fn ignored_packages(&self) -> Vec<String> {
+        self.config
+            .holds
+            .iter()
+            .filter(|hold| {
+                let available = self
+                    .packages
+                    .iter()
+                    .find(|p| p.name == hold.name)
+                    .map(|p| p.new_version.as_str());
+                self.config.active_hold(&hold.name, available).is_some()
+            })
+            .map(|hold| hold.name.clone())
+            .collect()
+    }
+
     pub fn installed_count(&self) -> usize {
         self.installed_packages.len()
     }
@@ -1176,13 +3346,32 @@ impl App {
     }
 
     pub fn filtered_installed(&self) -> Vec<(usize, &InstalledPackage)> {
-        filter_items(&self.installed_packages, &self.filter_text)
+        let mut filtered = filter_items(&self.installed_packages, &self.filter_text);
+        if self.aur_problems_only {
+            filtered.retain(|(_, pkg)| !self.aur_badges(&pkg.name).is_empty());
+        }
+        filtered
     }
 
     pub fn filtered_updates(&self) -> Vec<(usize, &Package)> {
         filter_items(&self.packages, &self.filter_text)
     }
 
+    pub fn filtered_history(&self) -> Vec<(usize, &HistoryEntry)> {
+        filter_items(&self.history, &self.filter_text)
+    }
+
+    /// History entry under the cursor (accounting for the filter)
+    pub fn selected_history_entry(&self) -> Option<&HistoryEntry> {
+        let filter_idx = self.history_list_state.selected()?;
+        self.filtered_history().get(filter_idx).map(|(_, entry)| *entry)
+    }
+
+    /// All recorded changes for a package, newest first
+    pub fn package_history(&self, name: &str) -> Vec<&HistoryEntry> {
+        self.history.iter().filter(|e| e.name == name).collect()
+    }
+
     fn refresh_package_info(&mut self) {
         // For Search tab, prepare fallback from SearchResult (for uninstalled AUR packages)
         if self.tab == Tab::Search {
@@ -1248,6 +3437,10 @@ impl App {
                 let idx = self.orphans_list_state.selected()?;
                 self.orphan_packages.get(idx).map(|p| p.name.clone())
             }
+            Tab::Drift => {
+                let idx = self.drift_list_state.selected()?;
+                self.drift.get(idx).map(|e| e.name.clone())
+            }
             Tab::Rebuilds => {
                 let idx = self.rebuilds_list_state.selected()?;
                 self.rebuild_issues.get(idx).map(|i| i.name.clone())
@@ -1256,7 +3449,8 @@ impl App {
                 let idx = self.search_list_state.selected()?;
                 self.search_results.get(idx).map(|r| r.name.clone())
             }
-            Tab::News => None, // News items are not packages
+            // Info panes built locally, not fetched
+            Tab::Pacnew | Tab::News | Tab::History | Tab::Snapshots => None,
         }
     }
 
@@ -1306,9 +3500,10 @@ impl App {
         let search_id = self.current_search_id;
         let query = query.to_string();
         let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
-            let results = search_packages(&query);
+            let results = backend.search(&query);
             let _ = tx.send(TaskResult::Search(search_id, results));
         });
     }
@@ -1338,10 +3533,11 @@ impl App {
         let info_id = self.current_info_id;
         let name = name.to_string();
         let tx = self.task_tx.clone();
+        let backend = Arc::clone(&self.backend);
 
         thread::spawn(move || {
             // Try pacman first, fall back to provided fallback (for uninstalled AUR packages)
-            let info = PackageInfo::fetch(&name).or(fallback);
+            let info = backend.info(&name).or(fallback);
             let _ = tx.send(TaskResult::PackageInfo(info_id, Box::new(info)));
         });
     }
@@ -1354,6 +3550,7 @@ impl App {
         let new = (current + delta).clamp(0, self.news_items.len() as i32 - 1) as usize;
         self.news_list_state.select(Some(new));
         self.news_scroll = 0; // Reset scroll when changing selection
+        self.mark_selected_news_read();
 
         if self.show_info_pane {
             self.refresh_news_info();
@@ -1410,6 +3607,58 @@ impl App {
         }
     }
 
+    fn mark_selected_news_read(&mut self) {
+        let Some(link) = self
+            .news_list_state
+            .selected()
+            .and_then(|idx| self.news_items.get(idx))
+            .map(|item| item.link.clone())
+        else {
+            return;
+        };
+        if !self.state.is_news_read(&link) {
+            self.state.mark_news_read(&link);
+            self.save_state();
+        }
+    }
+
+    fn mark_all_news_read(&mut self) {
+        for item in &self.news_items {
+            self.state.mark_news_read(&item.link);
+        }
+        self.save_state();
+    }
+
+    pub fn unread_news_count(&self) -> usize {
+        self.news_items
+            .iter()
+            .filter(|item| !self.state.is_news_read(&item.link))
+            .count()
+    }
+
+    /// When the system was last fully upgraded, from upkeep's own record or pacman.log
+    fn last_upgrade_time(&self) -> Option<i64> {
+        let logged = last_full_upgrade(&self.history);
+        match (self.state.last_upgrade, logged) {
+            (Some(recorded), Some(logged)) => Some(recorded.max(logged)),
+            (recorded, logged) => recorded.or(logged),
+        }
+    }
+
+    /// Unacknowledged attention-flagged news published since the last full upgrade
+    pub fn unacknowledged_news(&self) -> Vec<&NewsItem> {
+        let since = self.last_upgrade_time();
+        self.news_items
+            .iter()
+            .filter(|item| item.requires_attention && !self.state.is_news_acknowledged(&item.link))
+            .filter(|item| match (item.published, since) {
+                (Some(published), Some(since)) => published > since,
+                // Without dates to compare, err on the side of showing it
+                _ => true,
+            })
+            .collect()
+    }
+
     pub fn news_attention_count(&self) -> usize {
         self.news_items.iter().filter(|n| n.requires_attention).count()
     }
@@ -1445,3 +3694,1007 @@ impl App {
         Action::Install(selected)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::Hook;
+    use crate::manifest::ManifestGroup;
+    use crate::snapshot::{SnapshotConfig, SnapshotProvider};
+    use crate::updates::{AurSource, FakeBackend, PacnewKind, StaleUnit, VersionSource};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn fake_backend() -> FakeBackend {
+        FakeBackend {
+            updates: vec![
+                Package::new("linux".into(), "6.7.1-1".into(), "6.7.2-1".into(), PackageSource::Pacman),
+                Package::new("mesa".into(), "24.0.1-1".into(), "24.0.2-1".into(), PackageSource::Pacman),
+            ],
+            aur_updates: vec![Package::new(
+                "paru".into(),
+                "2.0.0-1".into(),
+                "2.0.1-1".into(),
+                PackageSource::Aur,
+            )],
+            aur_status: BTreeMap::new(),
+            installed: vec![
+                InstalledPackage::new("firefox".into(), "122.0-1".into(), PackageSource::Pacman),
+                InstalledPackage::new("neovim".into(), "0.9.5-1".into(), PackageSource::Pacman),
+                InstalledPackage::new("paru".into(), "2.0.0-1".into(), PackageSource::Aur),
+            ],
+            orphans: vec![InstalledPackage::new("libfoo".into(), "1.0-1".into(), PackageSource::Pacman)],
+            available: vec![SearchResult {
+                name: "ripgrep".into(),
+                version: "14.1.0-1".into(),
+                description: "Search tool".into(),
+                repository: "extra".into(),
+                installed: false,
+                selected: false,
+            }],
+            versions: ["0.9.5-1", "0.9.4-2"]
+                .iter()
+                .map(|version| DowngradeCandidate {
+                    name: "neovim".into(),
+                    version: version.to_string(),
+                    location: format!("/var/cache/pacman/pkg/neovim-{}-x86_64.pkg.tar.zst", version),
+                    source: VersionSource::Cache,
+                })
+                .collect(),
+            aur_sources: vec![(
+                "paru".into(),
+                paru_source("pkgver=2.0.1\nbuild() {\n  cargo build\n}\n"),
+            )],
+            vcs_heads: BTreeMap::new(),
+        }
+    }
+
+    fn paru_source(pkgbuild: &str) -> AurSource {
+        AurSource {
+            pkgbase: "paru".into(),
+            pkgbuild: pkgbuild.into(),
+            srcinfo: "pkgbase = paru\n\tsource = https://github.com/Morganamilo/paru/archive/v2.0.1.tar.gz\n"
+                .into(),
+            maintainer: Some("Morganamilo".into()),
+        }
+    }
+
+    fn fake_app() -> App {
+        // The library scan reads the real package database; AUR review is tested on its own
+        let config = Config {
+            scan_libraries: false,
+            review_aur: false,
+            ..Config::default()
+        };
+        App::with_backend(config, Vec::new(), Arc::new(fake_backend()))
+    }
+
+    /// Drive background tasks and debounce timers until everything has settled
+    fn settle(app: &mut App) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            app.check_search_debounce();
+            app.check_info_debounce();
+            app.poll_tasks();
+            let idle = app.loading == LoadingState::Idle
+                && !app.search_loading
+                && !app.info_loading
+                && !app.drift_loading
+                && !app.import.as_ref().is_some_and(|i| i.loading)
+                && app.hooked_transaction.is_none()
+                && app.pending_search.is_none()
+                && app.pending_info_fetch.is_none();
+            if idle {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("background tasks did not settle");
+    }
+
+    #[test]
+    fn test_refresh_loads_updates_and_installed() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+
+        assert_eq!(app.pacman_count(), 2);
+        assert_eq!(app.aur_count(), 1);
+        assert_eq!(app.installed_count(), 3);
+        assert_eq!(app.installed_aur_count(), 1);
+        assert_eq!(app.list_state.selected(), Some(0));
+        assert_eq!(app.cached_pkg_info.as_ref().map(|i| i.name.as_str()), Some("linux"));
+
+        // Tasks already in flight are still counted
+        app.refresh_rebuilds();
+        app.refresh();
+        assert_eq!(app.pending_tasks, 5); // two rebuild runs and the three refresh tasks
+        settle(&mut app);
+        assert_eq!(app.pending_tasks, 0);
+        assert_eq!(app.loading, LoadingState::Idle);
+    }
+
+    #[test]
+    fn test_update_selected_requires_confirmation() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Char(' ')));
+        assert!(matches!(app.handle_key(key(KeyCode::Char('u'))), Action::None));
+
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.items, vec!["mesa 24.0.1-1 → 24.0.2-1".to_string()]);
+
+        match app.handle_key(key(KeyCode::Char('y'))) {
+            Action::RunUpdate(packages) => assert_eq!(packages, vec!["mesa".to_string()]),
+            other => panic!("unexpected action {:?}", other),
+        }
+        assert!(app.pending_confirmation.is_none());
+    }
+
+    #[test]
+    fn test_update_all_can_be_cancelled() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.pending_confirmation.as_ref().map(|c| c.items.len()), Some(3));
+        assert!(matches!(app.handle_key(key(KeyCode::Esc)), Action::None));
+        assert!(app.pending_confirmation.is_none());
+    }
+
+    #[test]
+    fn test_filter_and_remove_installed() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.tab, Tab::Installed);
+
+        app.handle_key(key(KeyCode::Char('f')));
+        for c in "vim".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.filtered_installed().len(), 1);
+        app.handle_key(key(KeyCode::Char('F')));
+
+        app.handle_key(key(KeyCode::Char('d')));
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::Uninstall(packages) => assert_eq!(packages, vec!["neovim".to_string()]),
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_orphans_load_on_tab_switch() {
+        let mut app = fake_app();
+        app.handle_key(key(KeyCode::Tab));
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.tab, Tab::Orphans);
+        settle(&mut app);
+
+        assert_eq!(app.orphan_count(), 1);
+        assert_eq!(app.cached_pkg_info.as_ref().map(|i| i.name.as_str()), Some("libfoo"));
+    }
+
+    #[test]
+    fn test_search_and_install() {
+        let mut app = fake_app();
+        app.tab = Tab::Search;
+        for c in "rip".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        settle(&mut app);
+
+        assert_eq!(app.search_results.len(), 1);
+        assert_eq!(app.cached_pkg_info.as_ref().map(|i| i.version.as_str()), Some("14.1.0-1"));
+
+        app.handle_key(key(KeyCode::Enter));
+        match app.handle_key(key(KeyCode::Char('y'))) {
+            Action::Install(packages) => assert_eq!(packages, vec!["ripgrep".to_string()]),
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_downgrade_picker() {
+        let mut app = fake_app();
+        app.tab = Tab::Installed;
+        app.refresh_installed();
+        settle(&mut app);
+
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Char('v')));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.downgrade.as_ref().is_some_and(|d| d.loading) && Instant::now() < deadline {
+            app.poll_tasks();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // The installed version is not offered
+        let state = app.downgrade.as_ref().expect("picker shown");
+        assert_eq!(state.candidates.len(), 1);
+        assert_eq!(state.candidates[0].version, "0.9.4-2");
+
+        app.handle_key(key(KeyCode::Char('h')));
+        assert!(app.downgrade.is_none());
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.items, vec!["neovim 0.9.5-1 → 0.9.4-2 (cache)".to_string()]);
+
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::Downgrade(candidate, hold) => {
+                assert_eq!(candidate.version, "0.9.4-2");
+                assert!(hold);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_held_packages_skipped_on_full_upgrade() {
+        let mut app = fake_app();
+        app.config.add_hold("mesa", None, None);
+        app.refresh();
+        settle(&mut app);
+
+        assert_eq!(app.held_count(), 1);
+        assert_eq!(app.ignored_packages(), vec!["mesa".to_string()]);
+
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(app.packages.iter().all(|p| p.selected == (p.name != "mesa")));
+
+        app.handle_key(key(KeyCode::Enter));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.items.len(), 2);
+        assert_eq!(confirmation.message, "2 package(s) will be updated, 1 held");
+    }
+
+    #[test]
+    fn test_hold_until_version() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+        app.config.add_hold("mesa", Some("2099-01-01".into()), None);
+        let index = app.filtered_updates().iter().position(|(_, p)| p.name == "mesa");
+        app.list_state.select(index);
+
+        let type_version = |app: &mut App, version: &str| {
+            app.handle_key(key(KeyCode::Char('V')));
+            for c in version.chars() {
+                app.handle_key(key(KeyCode::Char(c)));
+            }
+            app.handle_key(key(KeyCode::Enter));
+        };
+
+        // Would lapse straight away
+        type_version(&mut app, "24.0.2");
+        assert!(app.hold_prompt.is_none());
+        assert_eq!(app.last_message.as_deref(), Some("mesa 24.0.2 is already offered"));
+        assert_eq!(app.config.holds[0].until_version, None);
+
+        // The date already set is kept
+        type_version(&mut app, "24.1");
+        let hold = &app.config.holds[0];
+        assert_eq!(hold.until.as_deref(), Some("2099-01-01"));
+        assert_eq!(hold.until_version.as_deref(), Some("24.1"));
+        let mesa = app.packages.iter().find(|p| p.name == "mesa").unwrap();
+        assert!(app.package_hold(mesa).is_some());
+    }
+
+    fn news_item(title: &str, published: i64, requires_attention: bool) -> NewsItem {
+        NewsItem {
+            title: title.into(),
+            link: format!("https://archlinux.org/news/{}/", title),
+            description: String::new(),
+            author: String::new(),
+            pub_date: String::new(),
+            published: Some(published),
+            requires_attention,
+            related_packages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_news_gate_requires_acknowledgement() {
+        let mut app = fake_app();
+        app.refresh();
+        settle(&mut app);
+
+        app.state.last_upgrade = Some(1_000);
+        app.news_items = vec![
+            news_item("old-intervention", 500, true),
+            news_item("new-intervention", 2_000, true),
+            news_item("new-announcement", 2_000, false),
+        ];
+
+        // Viewing the news on the News tab does not acknowledge it
+        app.tab = Tab::Search;
+        app.handle_key(key(KeyCode::Tab));
+        app.news_list_state.select(Some(1));
+        app.handle_key(key(KeyCode::Up));
+        app.handle_key(key(KeyCode::Down));
+        assert!(app.state.is_news_read("https://archlinux.org/news/new-intervention/"));
+        app.tab = Tab::Updates;
+
+        app.handle_key(key(KeyCode::Enter));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.news.len(), 1);
+        assert_eq!(confirmation.news[0].title, "new-intervention");
+
+        // Confirming is blocked until the news is acknowledged
+        assert!(matches!(app.handle_key(key(KeyCode::Enter)), Action::None));
+        assert!(app.pending_confirmation.is_some());
+
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(matches!(app.handle_key(key(KeyCode::Enter)), Action::RunUpdate(_)));
+        assert!(app.state.is_news_acknowledged("https://archlinux.org/news/new-intervention/"));
+        assert!(app.unacknowledged_news().is_empty());
+
+        // A feed that has not loaded is not taken as "no news"
+        app.news_loading = true;
+        app.handle_key(key(KeyCode::Enter));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert!(confirmation.news.is_empty() && confirmation.news_warning.is_some());
+        assert!(matches!(app.handle_key(key(KeyCode::Enter)), Action::None));
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(matches!(app.handle_key(key(KeyCode::Enter)), Action::RunUpdate(_)));
+    }
+
+    #[test]
+    fn test_news_marked_read_when_viewed() {
+        let mut app = fake_app();
+        app.news_items = vec![
+            news_item("first", 2_000, false),
+            news_item("second", 1_000, false),
+            news_item("third", 500, false),
+        ];
+        app.news_list_state.select(Some(0));
+        assert_eq!(app.unread_news_count(), 3);
+
+        app.tab = Tab::Search;
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.tab, Tab::News);
+        assert_eq!(app.unread_news_count(), 2);
+
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.unread_news_count(), 1);
+        assert!(app.state.is_news_read("https://archlinux.org/news/second/"));
+
+        app.handle_key(key(KeyCode::Char('M')));
+        assert_eq!(app.unread_news_count(), 0);
+    }
+
+    #[test]
+    fn test_pacnew_actions() {
+        let mut app = fake_app();
+        app.tab = Tab::Pacnew;
+        let file = |target: &str, kind: PacnewKind| PacnewFile {
+            path: format!("{}.{}", target, kind.label()),
+            target: target.to_string(),
+            kind,
+            selected: false,
+        };
+        app.pacnew_files = vec![
+            file("/etc/pacman.conf", PacnewKind::Pacnew),
+            file("/etc/ssh/sshd_config", PacnewKind::Pacsave),
+        ];
+        app.pacnew_list_state.select(Some(0));
+
+        app.handle_key(key(KeyCode::Char('t')));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.items, vec!["/etc/pacman.conf.pacnew → /etc/pacman.conf".to_string()]);
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::TakeNewConfig(file) => assert_eq!(file.target, "/etc/pacman.conf"),
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        // Deleting acts on the selection rather than the cursor when there is one
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Char(' ')));
+        app.handle_key(key(KeyCode::Up));
+        app.handle_key(key(KeyCode::Char('d')));
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::DeletePacnew(paths) => {
+                assert_eq!(paths, vec!["/etc/ssh/sshd_config.pacsave".to_string()])
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_restart_services_from_panel() {
+        let mut app = fake_app();
+        app.restart_status = Some(RestartStatus {
+            kernel: None,
+            units: vec![
+                StaleUnit {
+                    unit: Some("sshd.service".to_string()),
+                    user: false,
+                    processes: vec![(412, "sshd".to_string())],
+                    files: ["/usr/lib/libcrypto.so.3".to_string()].into(),
+                },
+                StaleUnit {
+                    unit: Some("pipewire.service".to_string()),
+                    user: true,
+                    processes: vec![(1800, "pipewire".to_string())],
+                    files: ["/usr/lib/libc.so.6".to_string()].into(),
+                },
+                StaleUnit {
+                    unit: Some("systemd-logind.service".to_string()),
+                    user: false,
+                    processes: vec![(380, "systemd-logind".to_string())],
+                    files: ["/usr/lib/libc.so.6".to_string()].into(),
+                },
+            ],
+        });
+
+        app.handle_key(key(KeyCode::Char('R')));
+        assert!(app.show_restart);
+
+        // User services are left to the user manager, logind to a reboot
+        app.handle_key(key(KeyCode::Enter));
+        assert!(!app.show_restart);
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(confirmation.items, vec!["sshd.service".to_string()]);
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::RestartServices(units) => assert_eq!(units, vec!["sshd.service".to_string()]),
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rebuild_all_batches_packages() {
+        let mut app = fake_app();
+        app.tab = Tab::Rebuilds;
+        let issue = |name: &str, package: bool| RebuildIssue {
+            name: name.to_string(),
+            rebuild_command: format!("rebuild-{}", name),
+            detail: "needs rebuild".to_string(),
+            package: package.then(|| name.to_string()),
+            selected: false,
+        };
+        app.rebuild_issues = vec![issue("obs-studio", false), issue("python-foo", true), issue("libbar", true)];
+
+        app.handle_key(key(KeyCode::Char('A')));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert_eq!(
+            confirmation.items,
+            vec!["yay -S --rebuild python-foo libbar && rebuild-obs-studio".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rebuild_checks_stream_progress() {
+        let check = |name: &str, script: &str| -> RebuildCheck {
+            toml::from_str(&format!(
+                "name = '{}'\ncommand = ['sh', '-c', '{}']\nerror_patterns = ['broken']\nrebuild = 'true'",
+                name, script
+            ))
+            .unwrap()
+        };
+        let checks = vec![
+            check("slow", "sleep 0.2; echo broken >&2"),
+            check("healthy", "true"),
+            check("fast", "echo broken >&2"),
+        ];
+        let mut app = App::with_backend(fake_app().config, checks, Arc::new(fake_backend()));
+
+        app.refresh_rebuilds();
+        assert!(app.check_progress.iter().all(|p| p.status == CheckStatus::Pending));
+        settle(&mut app);
+
+        let statuses: Vec<CheckStatus> = app.check_progress.iter().map(|p| p.status).collect();
+        assert_eq!(statuses, vec![CheckStatus::Failed, CheckStatus::Ok, CheckStatus::Failed]);
+        assert!(app.check_progress.iter().all(|p| p.duration.is_some()));
+
+        // Config order, even though "fast" finished first
+        let names: Vec<&str> = app.rebuild_issues.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["slow", "fast"]);
+        assert_eq!(app.rebuild_issues[0].detail, "broken");
+    }
+
+    #[test]
+    fn test_check_triggers_skip_unchanged() {
+        let check: RebuildCheck = toml::from_str(
+            "name = 'nvim-plugins'\ncommand = ['true']\ntriggers = ['neovim', 'lua']\nrebuild = 'true'",
+        )
+        .unwrap();
+        let state_key = check.state_key();
+        // Stored in state.json, so it must not change between builds
+        assert_eq!(state_key, "nvim-plugins#68d07dca78cbf063");
+        let mut app =
+            App::with_backend(fake_app().config, vec![check.clone()], Arc::new(fake_backend()));
+
+        // First run: nothing recorded, so it runs and remembers the installed trigger versions
+        app.refresh_rebuilds();
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Ok);
+        let recorded = app.state.check_triggers.get(&state_key).expect("versions recorded");
+        assert_eq!(recorded.get("neovim").map(String::as_str), Some("0.9.5-1"));
+        assert!(!recorded.contains_key("lua")); // not installed
+
+        // Unchanged triggers: skipped
+        app.refresh_rebuilds();
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Skipped);
+
+        // Forced
+        app.tab = Tab::Rebuilds;
+        app.handle_key(key(KeyCode::Char('F')));
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Ok);
+
+        // A trigger was updated since the check last passed
+        app.state
+            .check_triggers
+            .get_mut(&state_key)
+            .unwrap()
+            .insert("neovim".to_string(), "0.9.4-1".to_string());
+        app.refresh_rebuilds();
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Ok);
+
+        // The check was edited: it runs again, and the old definition's record is dropped
+        app.refresh_rebuilds();
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Skipped);
+        app.rebuild_checks[0].error_patterns.push("E5113".to_string());
+        app.refresh_rebuilds();
+        settle(&mut app);
+        assert_eq!(app.check_progress[0].status, CheckStatus::Ok);
+        let keys: Vec<&String> = app.state.check_triggers.keys().collect();
+        assert_eq!(keys, vec![&app.rebuild_checks[0].state_key()]);
+        assert_ne!(keys, vec![&state_key]);
+    }
+
+    #[test]
+    fn test_template_picker() {
+        let mut backend = fake_backend();
+        backend.installed.push(InstalledPackage::new("dkms".into(), "3.0.12-1".into(), PackageSource::Pacman));
+        backend.orphans.push(InstalledPackage::new("qt6-base".into(), "6.7.2-1".into(), PackageSource::Pacman));
+        let mut app = App::with_backend(fake_app().config, Vec::new(), Arc::new(backend));
+        app.tab = Tab::Rebuilds;
+
+        app.handle_key(key(KeyCode::Char('t')));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.template_picker.as_ref().is_some_and(|p| p.loading) && Instant::now() < deadline {
+            app.poll_tasks();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let state = app.template_picker.as_ref().expect("picker shown");
+        let names: Vec<&str> = state.templates.iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["qt6-plugins", "dkms"]);
+
+        // Choose the second one only
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Char(' ')));
+        match app.handle_key(key(KeyCode::Enter)) {
+            Action::AddCheckTemplates(names) => assert_eq!(names, vec!["dkms".to_string()]),
+            other => panic!("unexpected action {:?}", other),
+        }
+        assert!(app.template_picker.is_none());
+    }
+
+    #[test]
+    fn test_aur_review_before_update() {
+        let config = Config {
+            scan_libraries: false,
+            ..Config::default()
+        };
+        let mut app = App::with_backend(config, Vec::new(), Arc::new(fake_backend()));
+        app.refresh();
+        settle(&mut app);
+        let wait_for_review = |app: &mut App| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while app.review.as_ref().is_some_and(|r| r.reviews.iter().any(Option::is_none))
+                && Instant::now() < deadline
+            {
+                app.poll_tasks();
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+
+        // Update all: the AUR package is reviewed first, in full
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.pending_confirmation.is_none());
+        wait_for_review(&mut app);
+        let review = app.review.as_ref().expect("review shown");
+        assert_eq!(review.names, vec!["paru".to_string()]);
+        let paru = review.reviews[0].as_ref().unwrap().as_ref().unwrap();
+        assert!(paru.previous.is_none());
+        assert!(paru.warnings.is_empty());
+
+        // Accepting moves on to the usual confirmation; cancelling that records nothing
+        app.state.aur_maintainer_changes.insert("paru".into(), Some("Morganamilo".into()));
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.review.is_none());
+        assert!(app.pending_confirmation.is_some());
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.state.reviewed_aur.contains_key("paru"));
+        assert!(app.state.aur_maintainer_changes.contains_key("paru"));
+
+        // Confirming records the revision
+        app.handle_key(key(KeyCode::Enter));
+        wait_for_review(&mut app);
+        app.handle_key(key(KeyCode::Enter));
+        app.handle_key(key(KeyCode::Char('a')));
+        assert!(matches!(app.handle_key(key(KeyCode::Enter)), Action::RunUpdate(_)));
+        assert!(app.state.reviewed_aur.contains_key("paru"));
+        assert!(!app.state.aur_maintainer_changes.contains_key("paru"));
+
+        // The next review diffs against the accepted revision
+        let accepted = paru_source("pkgver=2.0.0\nbuild() {\n  cargo build\n}\n");
+        app.state.reviewed_aur.insert("paru".into(), accepted);
+        app.handle_key(key(KeyCode::Enter));
+        wait_for_review(&mut app);
+        let review = app.review.as_ref().expect("review shown");
+        let paru = review.reviews[0].as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(paru.diff[..3], ["@@ line 1 @@", "-pkgver=2.0.0", "+pkgver=2.0.1"]);
+
+        // Cancelling drops the action
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.review.is_none());
+        assert!(app.pending_confirmation.is_none());
+
+        // A PKGBUILD that could not be fetched is not accepted along with the others
+        app.open_review(Action::Install(vec!["ghost".into()]), vec!["ghost".into()]);
+        wait_for_review(&mut app);
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.review.is_some() && app.pending_confirmation.is_none());
+        app.handle_key(key(KeyCode::Char('!')));
+        assert!(app.review.is_none());
+        assert!(!app.state.reviewed_aur.contains_key("ghost"));
+        assert!(app.pending_confirmation.is_some());
+    }
+
+    #[test]
+    fn test_aur_status_badges() {
+        let mut backend = fake_backend();
+        backend.aur_status.insert(
+            "paru".into(),
+            AurStatus {
+                out_of_date: Some(1_700_000_000),
+                maintainer: Some("mallory".into()),
+                ..AurStatus::default()
+            },
+        );
+        let mut app = App::with_backend(fake_app().config, Vec::new(), Arc::new(backend));
+        app.state.aur_maintainers.insert("paru".into(), Some("Morganamilo".into()));
+        app.tab = Tab::Installed;
+
+        app.refresh_installed();
+        settle(&mut app);
+        assert_eq!(app.aur_badges("paru"), vec!["out of date", "new maintainer"]);
+        assert_eq!(app.state.aur_maintainer_changes.get("paru"), Some(&Some("Morganamilo".into())));
+        assert_eq!(app.last_message.as_deref(), Some("AUR maintainer changed: paru"));
+
+        // Still flagged on the next refresh
+        app.refresh_installed();
+        settle(&mut app);
+        assert!(app.aur_badges("paru").contains(&"new maintainer"));
+
+        app.handle_key(key(KeyCode::Char('p')));
+        let names: Vec<&str> = app.filtered_installed().iter().map(|(_, p)| p.name.as_str()).collect();
+        assert_eq!(names, vec!["paru"]);
+        app.handle_key(key(KeyCode::Char('p')));
+        assert_eq!(app.filtered_installed().len(), 3);
+    }
+
+    #[test]
+    fn test_devel_updates_from_upstream_commits() {
+        let source = "git+https://github.com/neovim/neovim.git";
+        let head = "9f8e7d6c5b4a39281706f5e4d3c2b1a098765432";
+        let config = Config {
+            devel: true,
+            ..fake_app().config
+        };
+        // A new app, with no revisions recorded, seeing neovim-git installed at `version`
+        let fresh = |version: &str| {
+            let mut backend = fake_backend();
+            backend.vcs_heads.insert(
+                "neovim-git".into(),
+                [(source.to_string(), head.to_string())].into_iter().collect(),
+            );
+            backend.installed.push(InstalledPackage::new(
+                "neovim-git".into(),
+                version.into(),
+                PackageSource::Aur,
+            ));
+            let mut app = App::with_backend(config.clone(), Vec::new(), Arc::new(backend));
+            app.refresh();
+            settle(&mut app);
+            app
+        };
+
+        // Not built from an older commit than upstream's: nothing to update
+        let app = fresh("0.11.0.r45.g9f8e7d6-1");
+        assert_eq!(app.state.devel_revisions["neovim-git"][source], head);
+        assert!(app.devel_updates(&[]).is_empty());
+
+        // Already behind when first seen
+        let app = fresh("0.11.0.r40.g1a2b3c4-1");
+        assert_eq!(app.state.devel_revisions["neovim-git"][source], "1a2b3c4");
+        assert_eq!(app.devel_updates(&[]), vec!["neovim-git"]);
+
+        // The version does not say what it was built from
+        let app = fresh("0.11.0-1");
+        let pkg = app.packages.iter().find(|p| p.name == "neovim-git").unwrap();
+        assert_eq!((pkg.old_version.as_str(), pkg.new_version.as_str()), ("unknown", "9f8e7d6"));
+        assert!(!app.state.devel_revisions.contains_key("neovim-git"));
+
+        let mut app = fresh("0.11.0.r45.g9f8e7d6-1");
+
+        // Built from an older commit
+        app.state
+            .devel_revisions
+            .insert("neovim-git".into(), [(source.to_string(), "1a2b3c4d5e".to_string())].into());
+        app.refresh();
+        settle(&mut app);
+        let pkg = app.packages.iter().find(|p| p.name == "neovim-git").unwrap();
+        assert_eq!(pkg.source, PackageSource::Devel);
+        assert_eq!((pkg.old_version.as_str(), pkg.new_version.as_str()), ("1a2b3c4", "9f8e7d6"));
+        assert_eq!(pkg.source_label(), " (devel)");
+        assert_eq!(app.aur_count(), 2);
+        assert_eq!(app.devel_updates(&[]), vec!["neovim-git"]);
+
+        // Building it records the revision it was built from
+        app.record_devel_revisions(&["neovim-git".to_string()]);
+        assert_eq!(app.state.devel_revisions["neovim-git"][source], head);
+        app.refresh();
+        settle(&mut app);
+        assert!(app.devel_updates(&[]).is_empty());
+    }
+
+    /// Poll the embedded terminal until its command has exited
+    fn wait_for_transaction(app: &mut App) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.transaction.as_ref().is_some_and(|t| t.is_running()) && Instant::now() < deadline
+        {
+            app.poll_tasks();
+            thread::sleep(Duration::from_millis(10));
+        }
+        app.poll_tasks();
+    }
+
+    #[test]
+    fn test_snapshot_before_update() {
+        let snapshot = SnapshotConfig {
+            provider: SnapshotProvider::Command,
+            snapper_config: String::new(),
+            subvolume: String::new(),
+            directory: String::new(),
+            command: Some("echo taking {label}; echo snap-2".into()),
+            delete_command: Some("true {id}".into()),
+            keep: 1,
+        };
+        let config = Config {
+            snapshot: Some(snapshot.clone()),
+            ..fake_app().config
+        };
+        let mut app = App::with_backend(config, Vec::new(), Arc::new(fake_backend()));
+        // Taken before switching providers
+        let snapper = Snapshot::new("42".into(), SnapshotProvider::Snapper, "Remove Packages");
+        let older = Snapshot::new("snap-1".into(), SnapshotProvider::Command, "Remove Packages");
+        app.state.snapshots.extend([snapper, older]);
+
+        // The snapshot runs first, then hands over to the update
+        let update = Action::RunUpdate(vec![]);
+        app.start_transaction("Update All Packages", update, vec!["true".into()], (24, 80));
+        assert!(matches!(app.transaction.as_ref().unwrap().action, Action::TakeSnapshot));
+        wait_for_transaction(&mut app);
+        wait_for_transaction(&mut app);
+        let transaction = app.transaction.as_ref().unwrap();
+        assert_eq!(transaction.title, "Update All Packages");
+        assert_eq!(transaction.snapshot.as_deref(), Some("snap-2"));
+
+        app.handle_key(key(KeyCode::Enter));
+        let taken = app.state.snapshots.last().unwrap();
+        assert_eq!((taken.id.as_str(), taken.succeeded), ("snap-2", Some(true)));
+        assert_eq!(taken.transaction, "Update All Packages");
+        settle(&mut app);
+
+        // Newest first; pruning keeps one
+        app.tab = Tab::Snapshots;
+        app.load_tab_data();
+        assert_eq!(app.selected_snapshot().map(|s| s.id.as_str()), Some("snap-2"));
+        app.handle_key(key(KeyCode::Char('P')));
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation shown");
+        assert!(matches!(&confirmation.action, Action::DeleteSnapshots(ids) if ids == &["snap-1"]));
+        app.handle_key(key(KeyCode::Esc));
+
+        // The snapper snapshot is not handed to the custom command
+        let error = app.snapshot_delete_command(&["snap-1".into(), "42".into()]).unwrap_err();
+        assert_eq!(error, "42 was taken with snapper, which is no longer configured");
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.selected_snapshot().map(|s| s.id.as_str()), Some("42"));
+        app.handle_key(key(KeyCode::Char('d')));
+        assert!(app.pending_confirmation.is_none());
+
+        // A failed snapshot keeps the transaction from running
+        app.config.snapshot = Some(SnapshotConfig {
+            command: Some("exit 1".into()),
+            ..snapshot
+        });
+        let remove = Action::Uninstall(vec!["foo".into()]);
+        app.start_transaction("Remove Packages", remove, vec!["true".into()], (24, 80));
+        wait_for_transaction(&mut app);
+        assert!(matches!(app.transaction.as_ref().unwrap().action, Action::TakeSnapshot));
+        let message = app.last_message.as_deref();
+        assert_eq!(message, Some("Snapshot failed, Remove Packages was not run"));
+        assert_eq!(app.state.snapshots.len(), 3);
+    }
+
+    #[test]
+    fn test_transaction_hooks() {
+        let log = std::env::temp_dir().join(format!("upkeep-hooks-{}", std::process::id()));
+        let record = format!("echo \"$UPKEEP_PHASE $UPKEEP_PACKAGES\" > {}", log.display());
+        let hook = |phase, command: &str| Hook {
+            phase,
+            command: command.to_string(),
+            packages: Vec::new(),
+            timeout: None,
+        };
+        let config = Config {
+            hooks: vec![
+                hook(HookPhase::PreRemove, &record),
+                hook(HookPhase::PostRemove, "echo \"exit $UPKEEP_EXIT_CODE\" >&2; false"),
+                Hook {
+                    packages: vec!["linux*".into()],
+                    ..hook(HookPhase::PreUpdate, "echo 'VM running' >&2; false")
+                },
+            ],
+            ..fake_app().config
+        };
+        let mut app = App::with_backend(config, Vec::new(), Arc::new(fake_backend()));
+        app.refresh();
+        settle(&mut app);
+
+        // A failing pre hook stops the transaction; it only applies to the kernel
+        let update_all = Action::RunUpdate(vec![]);
+        app.start_transaction("Update All Packages", update_all, vec!["true".into()], (24, 80));
+        // Hooks run in the background, the transaction waits for them
+        assert!(app.hooked_transaction.is_some());
+        settle(&mut app);
+        assert!(app.transaction.is_none());
+        let message = app.last_message.as_deref().unwrap();
+        assert!(message.starts_with("Update All Packages not run: pre-update hook"), "{}", message);
+        assert!(message.ends_with("failed: VM running"), "{}", message);
+        let update_mesa = Action::RunUpdate(vec!["mesa".into()]);
+        app.start_transaction("Update Packages", update_mesa, vec!["true".into()], (24, 80));
+        settle(&mut app);
+        assert!(app.transaction.is_some());
+        wait_for_transaction(&mut app);
+        app.handle_key(key(KeyCode::Enter));
+        settle(&mut app);
+
+        // Pre hooks see the packages; post hook failures show once the pane is closed
+        let remove = Action::Uninstall(vec!["foo".into(), "bar".into()]);
+        app.start_transaction("Remove Packages", remove, vec!["true".into()], (24, 80));
+        settle(&mut app);
+        wait_for_transaction(&mut app);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.hook_errors.is_none() && Instant::now() < deadline {
+            app.poll_tasks();
+            thread::sleep(Duration::from_millis(10));
+        }
+        app.handle_key(key(KeyCode::Enter));
+        let message = app.last_message.as_deref().unwrap();
+        assert!(message.starts_with("post-remove hook `echo"), "{}", message);
+        assert!(message.ends_with("failed: exit 0"), "{}", message);
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "pre-remove foo bar\n");
+        let _ = std::fs::remove_file(&log);
+    }
+
+    #[test]
+    fn test_drift_reconcile() {
+        let mut app = fake_app();
+        let strings = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        app.manifest = Some(Manifest {
+            packages: strings(&["firefox", "git"]),
+            aur: strings(&["paru"]),
+            groups: vec![
+                ManifestGroup {
+                    name: "dev".into(),
+                    hosts: vec![hostname()],
+                    packages: strings(&["ripgrep"]),
+                    ..Default::default()
+                },
+                ManifestGroup {
+                    name: "servers".into(),
+                    roles: strings(&["server"]),
+                    packages: strings(&["nginx"]),
+                    ..Default::default()
+                },
+            ],
+        });
+        app.tab = Tab::Orphans;
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.tab, Tab::Drift);
+        settle(&mut app);
+
+        let drift: Vec<(&str, DriftKind)> =
+            app.drift.iter().map(|e| (e.name.as_str(), e.kind)).collect();
+        assert_eq!(
+            drift,
+            [
+                ("git", DriftKind::Missing),
+                ("ripgrep", DriftKind::Missing),
+                ("neovim", DriftKind::Extra),
+            ]
+        );
+        assert_eq!(app.drift[1].group.as_deref(), Some("dev"));
+
+        // Nothing selected: reconcile everything of that kind
+        app.handle_key(key(KeyCode::Char('i')));
+        let confirmation = app.pending_confirmation.as_ref().unwrap();
+        assert!(matches!(&confirmation.action, Action::Install(p) if *p == ["git", "ripgrep"]));
+        app.handle_key(key(KeyCode::Esc));
+
+        // Undeclared packages are only touched once selected
+        app.handle_key(key(KeyCode::Char('D')));
+        assert!(app.pending_confirmation.is_none());
+
+        // Marking a selected extra as a dependency
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char(' ')));
+        app.handle_key(key(KeyCode::Char('m')));
+        assert_eq!(app.pending_confirmation.as_ref().unwrap().title, "Mark as Dependencies");
+        let action = app.handle_key(key(KeyCode::Char('y')));
+        assert!(matches!(action, Action::MarkAsDeps(p) if p == ["neovim"]));
+    }
+
+    #[test]
+    fn test_import_from_backup() {
+        let dir = std::env::temp_dir().join(format!("upkeep-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let packages_path = dir.join("packages-2024-01-31.txt");
+        std::fs::write(&packages_path, "firefox\nripgrep\nmissing-pkg\n").unwrap();
+
+        let mut app = fake_app();
+        app.tab = Tab::Installed;
+        app.handle_key(key(KeyCode::Char('b')));
+        app.import.as_mut().unwrap().backups = vec![Backup {
+            date: "2024-01-31".into(),
+            packages_path: Some(packages_path),
+            aur_path: None,
+        }];
+
+        // Row 0 is the clipboard
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Enter));
+        settle(&mut app);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let state = app.import.as_ref().unwrap();
+        assert_eq!(state.source.as_deref(), Some("backup of 2024-01-31"));
+        assert_eq!(state.installed, 1);
+        let entries: Vec<(&str, Option<PackageSource>, bool)> = state
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.source.clone(), e.selected))
+            .collect();
+        assert_eq!(
+            entries,
+            [("ripgrep", Some(PackageSource::Pacman), true), ("missing-pkg", None, false)]
+        );
+
+        // Names that were not found cannot be chosen
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char(' ')));
+        assert!(!app.import.as_ref().unwrap().entries[1].selected);
+
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.import.is_none());
+        let confirmation = app.pending_confirmation.as_ref().unwrap();
+        assert!(matches!(&confirmation.action, Action::Install(p) if *p == ["ripgrep"]));
+    }
+}
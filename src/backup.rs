@@ -1,9 +1,94 @@
+use crate::manifest::hostname;
+use crate::updates::PackageSource;
 use chrono::Local;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+/// Files of one export: packages-DATE.txt, aur-DATE.txt and system-DATE.json
+const BACKUP_FILE: &str = r"^(packages|aur|system)-(\d{4}-\d{2}-\d{2})\.(?:txt|json)$";
+
+/// Everything needed to put a system's packages back the way they were, saved as
+/// system-DATE.json next to the plain package lists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemBackup {
+    /// RFC 3339 time of the export
+    pub created: String,
+    pub hostname: String,
+    /// Every installed package, explicit or not
+    pub packages: Vec<BackupPackage>,
+    /// Contents of /etc/pacman.conf
+    pub pacman_conf: Option<String>,
+    /// Contents of /etc/pacman.d/mirrorlist
+    pub mirrorlist: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupPackage {
+    pub name: String,
+    pub version: String,
+    /// Sync repository it is installed from, or "aur" for foreign packages
+    pub repo: String,
+    pub reason: InstallReason,
+    /// As printed by pacman, e.g. "Wed 31 Jan 2024 10:00:01 AM CET"
+    pub install_date: Option<String>,
+    /// Config files of the package (its pacman backup entries) changed since installation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_config: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallReason {
+    Explicit,
+    Dependency,
+}
+
+/// What `export_packages` wrote
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub dir: PathBuf,
+    pub official: usize,
+    pub aur: usize,
+    /// Files of older exports removed to stay within the retention limit
+    pub pruned: usize,
+}
+
+/// Package lists written by `export_packages` on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub packages_path: Option<PathBuf>,
+    pub aur_path: Option<PathBuf>,
+}
+
+/// Package names read back from an export or the clipboard
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageList {
+    pub official: Vec<String>,
+    pub aur: Vec<String>,
+}
+
+impl PackageList {
+    pub fn names(&self) -> Vec<String> {
+        self.official.iter().chain(&self.aur).cloned().collect()
+    }
+}
+
+/// A listed package that is not installed, and where it can be installed from now
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportEntry {
+    pub name: String,
+    /// None if it is in neither the repositories nor the AUR any more
+    pub source: Option<PackageSource>,
+    pub selected: bool,
+}
+
 /// Fetches installed packages split into official and AUR
 fn fetch_packages() -> Result<(Vec<String>, Vec<String>), String> {
     // Get all explicitly installed packages
@@ -42,11 +127,17 @@ fn fetch_packages() -> Result<(Vec<String>, Vec<String>), String> {
     Ok((official, aur))
 }
 
-pub fn export_packages() -> Result<(PathBuf, PathBuf, usize, usize), String> {
-    let dir = dirs::config_dir()
+pub fn backup_dir() -> PathBuf {
+    dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("upkeep")
-        .join("backups");
+        .join("backups")
+}
+
+/// Write the package lists and the system backup to the backup directory, then delete all
+/// but the newest `keep` exports (0 keeps everything)
+pub fn export_packages(keep: usize) -> Result<Export, String> {
+    let dir = backup_dir();
 
     fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
 
@@ -70,7 +161,143 @@ pub fn export_packages() -> Result<(PathBuf, PathBuf, usize, usize), String> {
         writeln!(aur_file, "{}", pkg).map_err(|e| format!("Failed to write: {}", e))?;
     }
 
-    Ok((pkg_path, aur_path, official.len(), aur.len()))
+    let system_path = dir.join(format!("system-{}.json", date));
+    let json = serde_json::to_string_pretty(&fetch_system_backup()?)
+        .map_err(|e| format!("Failed to serialize backup: {}", e))?;
+    fs::write(&system_path, json)
+        .map_err(|e| format!("Failed to write {}: {}", system_path.display(), e))?;
+
+    let pruned = match keep {
+        0 => 0,
+        keep => prune_backups(&dir, keep)?,
+    };
+
+    Ok(Export {
+        dir,
+        official: official.len(),
+        aur: aur.len(),
+        pruned,
+    })
+}
+
+/// Versions, install reasons and modified config files of every installed package, along
+/// with the pacman configuration
+fn fetch_system_backup() -> Result<SystemBackup, String> {
+    // The C locale keeps the field names parseable
+    let output = Command::new("pacman")
+        .arg("-Qii")
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| format!("Failed to run pacman: {}", e))?;
+    if !output.status.success() {
+        return Err("pacman -Qii failed".to_string());
+    }
+
+    let sync = Command::new("pacman")
+        .arg("-Sl")
+        .output()
+        .map_err(|e| format!("Failed to run pacman -Sl: {}", e))?;
+    let repos = parse_sync_list(&String::from_utf8_lossy(&sync.stdout));
+
+    Ok(SystemBackup {
+        created: Local::now().to_rfc3339(),
+        hostname: hostname(),
+        packages: parse_package_details(&String::from_utf8_lossy(&output.stdout), &repos),
+        pacman_conf: fs::read_to_string("/etc/pacman.conf").ok(),
+        mirrorlist: fs::read_to_string("/etc/pacman.d/mirrorlist").ok(),
+    })
+}
+
+/// Repository of every package in the sync databases, from `pacman -Sl`; the first
+/// repository listing a package wins, as it does for pacman
+fn parse_sync_list(output: &str) -> BTreeMap<String, String> {
+    let mut repos = BTreeMap::new();
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        if let (Some(repo), Some(name)) = (fields.next(), fields.next()) {
+            repos.entry(name.to_string()).or_insert_with(|| repo.to_string());
+        }
+    }
+    repos
+}
+
+/// Parse `pacman -Qii` output (C locale), one blank-line separated block per package
+fn parse_package_details(output: &str, repos: &BTreeMap<String, String>) -> Vec<BackupPackage> {
+    let mut packages = Vec::new();
+
+    for block in output.split("\n\n") {
+        let mut name = String::new();
+        let mut version = String::new();
+        let mut reason = InstallReason::Explicit;
+        let mut install_date = None;
+        let mut modified_config = Vec::new();
+        let mut key = "";
+
+        for line in block.lines() {
+            // Lists continue on the following lines: indented names, or backup entries
+            // like "MODIFIED\t/etc/pacman.conf"
+            let value = match line.split_once(':') {
+                Some((k, value)) if !line.starts_with(' ') && !k.contains('\t') => {
+                    key = k.trim();
+                    value.trim()
+                }
+                _ => line.trim(),
+            };
+            match key {
+                "Name" => name = value.to_string(),
+                "Version" => version = value.to_string(),
+                "Install Date" => install_date = Some(value.to_string()),
+                "Install Reason" if value.starts_with("Installed as a dependency") => {
+                    reason = InstallReason::Dependency
+                }
+                "Backup Files" => {
+                    if let Some(path) = value.strip_prefix("MODIFIED") {
+                        modified_config.push(path.trim().to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if name.is_empty() {
+            continue;
+        }
+        packages.push(BackupPackage {
+            repo: repos.get(&name).cloned().unwrap_or_else(|| "aur".to_string()),
+            name,
+            version,
+            reason,
+            install_date,
+            modified_config,
+        });
+    }
+    packages
+}
+
+/// Delete the files of all but the newest `keep` exports in `dir`; returns how many
+/// files were removed
+pub fn prune_backups(dir: &Path, keep: usize) -> Result<usize, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let file_name = Regex::new(BACKUP_FILE).unwrap();
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(captures) = file_name.captures(&name) {
+            files.push((captures[2].to_string(), entry.path()));
+        }
+    }
+
+    let dates: BTreeSet<&String> = files.iter().map(|(date, _)| date).collect();
+    let kept: BTreeSet<&String> = dates.into_iter().rev().take(keep).collect();
+    let mut pruned = 0;
+    for (date, path) in &files {
+        if !kept.contains(date) {
+            fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
 }
 
 pub fn get_package_list() -> Result<(String, usize, usize), String> {
@@ -91,6 +318,130 @@ pub fn get_package_list() -> Result<(String, usize, usize), String> {
     Ok((result, official.len(), aur.len()))
 }
 
+/// Exports in the backup directory, newest first
+pub fn list_backups() -> Vec<Backup> {
+    let Ok(entries) = fs::read_dir(backup_dir()) else {
+        return Vec::new();
+    };
+    let file_name = Regex::new(BACKUP_FILE).unwrap();
+
+    let mut backups: BTreeMap<String, Backup> = BTreeMap::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // The system backup is not a package list
+        let Some(captures) = file_name.captures(&name).filter(|c| &c[1] != "system") else {
+            continue;
+        };
+        let date = captures[2].to_string();
+        let backup = backups.entry(date.clone()).or_insert(Backup {
+            date,
+            packages_path: None,
+            aur_path: None,
+        });
+        if &captures[1] == "aur" {
+            backup.aur_path = Some(entry.path());
+        } else {
+            backup.packages_path = Some(entry.path());
+        }
+    }
+    backups.into_values().rev().collect()
+}
+
+impl Backup {
+    pub fn load(&self) -> Result<PackageList, String> {
+        let mut list = PackageList::default();
+        for (path, aur) in [(&self.packages_path, false), (&self.aur_path, true)] {
+            let Some(path) = path else {
+                continue;
+            };
+            let text = fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let parsed = parse_package_list(&text, aur);
+            list.official.extend(parsed.official);
+            list.aur.extend(parsed.aur);
+        }
+        Ok(list)
+    }
+}
+
+/// Parse an exported list: one package per line, optionally split by the "# Official" and
+/// "# AUR" headers `get_package_list` writes (lines before any header are AUR if `aur`)
+/// Anything after the name, like a version from `pacman -Q`, is ignored, as are lines that
+/// cannot be package names
+pub fn parse_package_list(text: &str, aur: bool) -> PackageList {
+    let valid_name = Regex::new(r"^[a-z0-9@_+][a-z0-9@._+-]*$").unwrap();
+    let mut list = PackageList::default();
+    let mut in_aur = aur;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('#') {
+            match header.trim().to_lowercase().as_str() {
+                "aur" => in_aur = true,
+                "official" => in_aur = false,
+                _ => {}
+            }
+            continue;
+        }
+        let Some(name) = line.split_whitespace().next().filter(|n| valid_name.is_match(n)) else {
+            continue;
+        };
+        let names = if in_aur { &mut list.aur } else { &mut list.official };
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    list
+}
+
+/// Listed packages that are not installed: repository packages, then AUR, then the ones
+/// found in neither; everything installable starts out selected
+pub fn import_entries(
+    list: &PackageList,
+    installed: &BTreeMap<String, String>,
+    located: &BTreeMap<String, PackageSource>,
+) -> Vec<ImportEntry> {
+    let mut entries: Vec<ImportEntry> = list
+        .names()
+        .into_iter()
+        .filter(|name| !installed.contains_key(name))
+        .map(|name| {
+            let source = located.get(&name).cloned();
+            ImportEntry {
+                name,
+                selected: source.is_some(),
+                source,
+            }
+        })
+        .collect();
+    let rank = |entry: &ImportEntry| match entry.source {
+        Some(PackageSource::Pacman) => 0,
+        Some(_) => 1,
+        None => 2,
+    };
+    entries.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.name.cmp(&b.name)));
+    entries.dedup_by(|a, b| a.name == b.name);
+    entries
+}
+
+pub fn read_clipboard() -> Result<String, String> {
+    // Try wl-paste first (Wayland)
+    if let Ok(output) = Command::new("wl-paste").arg("--no-newline").output() {
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+    }
+
+    // Fall back to xclip (X11)
+    let output = Command::new("xclip")
+        .args(["-selection", "clipboard", "-o"])
+        .output()
+        .map_err(|_| "Neither wl-paste nor xclip available".to_string())?;
+    if !output.status.success() {
+        return Err("Clipboard is empty".to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
 pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     // Try wl-copy first (Wayland)
     if let Ok(mut child) = Command::new("wl-copy").stdin(Stdio::piped()).spawn() {
@@ -114,3 +465,113 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     }
     child.wait().map(|_| ()).map_err(|e| format!("xclip failed: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_list() {
+        let clipboard = "# Official\nbase\nlinux 6.7.2-1\n\n# AUR\nparu\n-Syu\nbase\n";
+        let list = parse_package_list(clipboard, false);
+        assert_eq!(list.official, ["base", "linux"]);
+        assert_eq!(list.aur, ["paru", "base"]);
+
+        // aur-DATE.txt has no headers
+        assert_eq!(parse_package_list("yay\nparu\n", true).aur, ["yay", "paru"]);
+    }
+
+    #[test]
+    fn test_import_entries() {
+        let list = PackageList {
+            official: vec!["git".into(), "zsh".into(), "community-only".into()],
+            aur: vec!["paru".into(), "yay".into()],
+        };
+        let installed: BTreeMap<String, String> = [("git".into(), "2.43.0-1".into())].into();
+        let located: BTreeMap<String, PackageSource> = [
+            ("zsh".into(), PackageSource::Pacman),
+            ("paru".into(), PackageSource::Aur),
+            // Moved from the AUR to the repositories
+            ("yay".into(), PackageSource::Pacman),
+        ]
+        .into();
+
+        let entries = import_entries(&list, &installed, &located);
+        let summary: Vec<(&str, Option<PackageSource>, bool)> =
+            entries.iter().map(|e| (e.name.as_str(), e.source.clone(), e.selected)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("yay", Some(PackageSource::Pacman), true),
+                ("zsh", Some(PackageSource::Pacman), true),
+                ("paru", Some(PackageSource::Aur), true),
+                ("community-only", None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_package_details() {
+        let output = "Name            : pacman
+Version         : 6.0.2-9
+Description     : A library-based package manager
+Install Date    : Wed 31 Jan 2024 10:00:01 AM CET
+Install Reason  : Explicitly installed
+Backup Files    :
+MODIFIED\t/etc/pacman.conf
+UNMODIFIED\t/etc/makepkg.conf
+
+Name            : paru
+Version         : 2.0.1-1
+Required By     : None
+Install Date    : Thu 01 Feb 2024 09:00:00 AM CET
+Install Reason  : Installed as a dependency for another package
+Backup Files    : (none)
+
+";
+        let repos = parse_sync_list("core pacman 6.0.2-9 [installed]\nextra git 2.43.0-1\n");
+        let packages = parse_package_details(output, &repos);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            packages[0],
+            BackupPackage {
+                name: "pacman".into(),
+                version: "6.0.2-9".into(),
+                repo: "core".into(),
+                reason: InstallReason::Explicit,
+                install_date: Some("Wed 31 Jan 2024 10:00:01 AM CET".into()),
+                modified_config: vec!["/etc/pacman.conf".into()],
+            }
+        );
+        let paru = &packages[1];
+        assert_eq!((paru.repo.as_str(), paru.reason), ("aur", InstallReason::Dependency));
+        assert!(paru.modified_config.is_empty());
+    }
+
+    #[test]
+    fn test_prune_backups() {
+        let dir = std::env::temp_dir().join(format!("upkeep-prune-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files = [
+            "packages-2024-01-01.txt",
+            "aur-2024-01-01.txt",
+            "system-2024-01-01.json",
+            "packages-2024-02-01.txt",
+            "system-2024-03-01.json",
+            "notes.txt",
+        ];
+        for file in files {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(prune_backups(&dir, 2).unwrap(), 3);
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(left, ["notes.txt", "packages-2024-02-01.txt", "system-2024-03-01.json"]);
+    }
+}
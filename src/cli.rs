@@ -0,0 +1,250 @@
+use crate::config::Config;
+use crate::manifest::{check_drift, hostname, manifest_path, Manifest};
+use crate::rebuilds::{find_rebuild_issues, load_checks};
+use crate::updates::{load_news, PackageBackend, SystemBackend};
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+const USAGE: &str = r#"Usage: upkeep [COMMAND] [--json]
+
+Run without a command to start the TUI.
+
+Commands:
+  check       List pending pacman and AUR updates
+  installed   List explicitly installed packages
+  orphans     List orphan packages
+  drift       List packages missing from or not declared in packages.toml
+  rebuilds    Run rebuild checks and the built-in scans
+  news        Show recent Arch Linux news
+  help        Show this message
+
+Options:
+  --json      Print machine-readable JSON instead of a table
+"#;
+
+/// Parsed command line for headless mode
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    command: Command,
+    json: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Check,
+    Installed,
+    Orphans,
+    Drift,
+    Rebuilds,
+    News,
+    Help,
+}
+
+fn parse_args(args: &[String]) -> Result<Args> {
+    let mut command = None;
+    let mut json = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" | "help" => command = Some(Command::Help),
+            "check" | "updates" if command.is_none() => command = Some(Command::Check),
+            "installed" if command.is_none() => command = Some(Command::Installed),
+            "orphans" if command.is_none() => command = Some(Command::Orphans),
+            "drift" if command.is_none() => command = Some(Command::Drift),
+            "rebuilds" if command.is_none() => command = Some(Command::Rebuilds),
+            "news" if command.is_none() => command = Some(Command::News),
+            other => bail!("unexpected argument '{}'\n\n{}", other, USAGE),
+        }
+    }
+
+    match command {
+        Some(command) => Ok(Args { command, json }),
+        None => bail!("missing command\n\n{}", USAGE),
+    }
+}
+
+/// Entry point for `upkeep <command>` - prints results and exits without the TUI
+pub fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    if args.command == Command::Help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let config = Config::load().unwrap_or_default();
+    let backend = SystemBackend::new(&config.aur_helper);
+
+    match args.command {
+        Command::Help => {}
+        Command::Check => {
+            let mut packages = backend.pacman_updates();
+            packages.extend(backend.aur_updates());
+            if args.json {
+                print_json(&packages)?;
+            } else {
+                let rows = packages
+                    .iter()
+                    .map(|p| {
+                        vec![
+                            p.name.clone(),
+                            p.old_version.clone(),
+                            p.new_version.clone(),
+                            p.source.to_string(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["NAME", "CURRENT", "NEW", "SOURCE"], rows);
+            }
+        }
+        Command::Installed | Command::Orphans => {
+            let packages = if args.command == Command::Installed {
+                backend.installed()
+            } else {
+                backend.orphans()
+            };
+            if args.json {
+                print_json(&packages)?;
+            } else {
+                let rows = packages
+                    .iter()
+                    .map(|p| vec![p.name.clone(), p.version.clone(), p.source.to_string()])
+                    .collect();
+                print_table(&["NAME", "VERSION", "SOURCE"], rows);
+            }
+        }
+        Command::Drift => {
+            let path = manifest_path();
+            let Some(manifest) = Manifest::load(&path).map_err(anyhow::Error::msg)? else {
+                bail!("no package manifest at {}", path.display());
+            };
+            let declared = manifest.declared(&hostname(), &config.roles);
+            let drift = check_drift(&backend, &declared);
+            if args.json {
+                print_json(&drift)?;
+            } else {
+                let rows = drift
+                    .iter()
+                    .map(|e| {
+                        vec![
+                            e.name.clone(),
+                            e.kind.label().to_string(),
+                            if e.aur { "aur" } else { "pacman" }.to_string(),
+                            e.group.clone().or_else(|| e.version.clone()).unwrap_or_default(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["NAME", "STATUS", "SOURCE", "DETAIL"], rows);
+            }
+        }
+        Command::Rebuilds => {
+            let checks = load_checks()?;
+            let scan_with = config.scan_libraries.then_some(config.aur_helper.as_str());
+            let issues = find_rebuild_issues(&checks, scan_with);
+            if args.json {
+                print_json(&issues)?;
+            } else {
+                let rows = issues
+                    .iter()
+                    .map(|i| vec![i.name.clone(), i.detail.clone(), i.rebuild_command.clone()])
+                    .collect();
+                print_table(&["NAME", "DETAIL", "REBUILD COMMAND"], rows);
+            }
+        }
+        Command::News => {
+            let installed: Vec<String> = backend
+                .installed()
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+            let feed = load_news(&installed).map_err(anyhow::Error::msg)?;
+            if let Some(error) = &feed.offline {
+                eprintln!("warning: {} - showing cached news", error);
+            }
+            let items = feed.items;
+            if args.json {
+                print_json(&items)?;
+            } else {
+                let rows = items
+                    .iter()
+                    .map(|n| {
+                        vec![
+                            if n.requires_attention { "!" } else { "" }.to_string(),
+                            n.pub_date.clone(),
+                            n.title.clone(),
+                        ]
+                    })
+                    .collect();
+                print_table(&["", "DATE", "TITLE"], rows);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Print rows as left-aligned columns sized to their widest cell
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let last = cells.len() - 1;
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i == last {
+                    cell.to_string()
+                } else {
+                    format!("{:<width$}", cell, width = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(|s| s.as_str()).collect()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(&args(&["check", "--json"])).unwrap();
+        assert_eq!(parsed.command, Command::Check);
+        assert!(parsed.json);
+
+        let parsed = parse_args(&args(&["orphans"])).unwrap();
+        assert_eq!(parsed.command, Command::Orphans);
+        assert!(!parsed.json);
+
+        assert!(parse_args(&args(&["check", "news"])).is_err());
+        assert!(parse_args(&args(&["--json"])).is_err());
+        assert!(parse_args(&args(&["bogus"])).is_err());
+    }
+}
@@ -1,21 +1,142 @@
+use crate::hooks::Hook;
+use crate::snapshot::SnapshotConfig;
+use crate::updates::vercmp;
 use anyhow::Result;
-use serde::Deserialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     #[serde(default = "default_aur_helper")]
     pub aur_helper: String,
+    /// Arch Linux Archive mirror offered as a downgrade source (unset = cache only)
+    #[serde(default)]
+    pub archive_url: Option<String>,
+    /// Scan for binaries linking to missing libraries and modules stranded by interpreter upgrades
+    #[serde(default = "default_scan_libraries")]
+    pub scan_libraries: bool,
+    /// Show PKGBUILDs (diffed against the last reviewed revision) before AUR installs and updates
+    #[serde(default = "default_review_aur")]
+    pub review_aur: bool,
+    /// AUR to fetch PKGBUILDs from for review
+    #[serde(default = "default_aur_url")]
+    pub aur_url: String,
+    /// Check VCS packages (`-git`, `-svn`, `-hg`) for new upstream commits, like `yay --devel`
+    #[serde(default)]
+    pub devel: bool,
+    /// Roles of this machine, selecting `[[group]]`s of packages.toml
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Package exports kept in the backup directory, newest first (0 = keep all)
+    #[serde(default = "default_keep_backups")]
+    pub keep_backups: usize,
+    /// Snapshot taken before updates, reinstalls, removals and rebuilds (unset = none)
+    #[serde(default)]
+    pub snapshot: Option<SnapshotConfig>,
+    /// Packages held back from upgrades, one `[[hold]]` table each
+    #[serde(default, rename = "hold")]
+    pub holds: Vec<Hold>,
+    /// Commands run around transactions, one `[[hook]]` table each
+    #[serde(default, rename = "hook")]
+    pub hooks: Vec<Hook>,
+    /// Where to save; None keeps changes in memory only (used by tests)
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+/// A package upkeep keeps at its installed version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hold {
+    pub name: String,
+    /// Hold lapses once an update to this version (or newer) is available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until_version: Option<String>,
+    /// Hold lapses on this date, "YYYY-MM-DD"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl Hold {
+    /// Whether the hold still applies, given the version currently offered (if any)
+    pub fn is_active(&self, available: Option<&str>, today: NaiveDate) -> bool {
+        if let Some(until) = self.until.as_deref() {
+            // An unparseable date never expires rather than silently releasing the hold
+            if NaiveDate::parse_from_str(until, "%Y-%m-%d").is_ok_and(|date| today >= date) {
+                return false;
+            }
+        }
+        if let (Some(until_version), Some(available)) = (self.until_version.as_deref(), available) {
+            if vercmp(available, until_version) != Ordering::Less {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Short description of the expiry, e.g. "until 2024-01-22" or "until 6.8"
+    pub fn expiry_label(&self) -> Option<String> {
+        self.until
+            .as_ref()
+            .or(self.until_version.as_ref())
+            .map(|until| format!("until {}", until))
+    }
+}
+
+/// Wrapper so holds serialize as `[[hold]]` tables
+#[derive(Serialize)]
+struct HoldTables<'a> {
+    hold: &'a [Hold],
+}
+
+/// Wrapper so hooks serialize as `[[hook]]` tables
+#[derive(Serialize)]
+struct HookTables<'a> {
+    hook: &'a [Hook],
+}
+
+/// Wrapper so the snapshot settings serialize as a `[snapshot]` table
+#[derive(Serialize)]
+struct SnapshotTable<'a> {
+    snapshot: &'a SnapshotConfig,
 }
 
 fn default_aur_helper() -> String {
     "yay".to_string()
 }
 
+fn default_scan_libraries() -> bool {
+    true
+}
+
+fn default_review_aur() -> bool {
+    true
+}
+
+fn default_keep_backups() -> usize {
+    10
+}
+
+fn default_aur_url() -> String {
+    "https://aur.archlinux.org".to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             aur_helper: default_aur_helper(),
+            archive_url: None,
+            scan_libraries: default_scan_libraries(),
+            review_aur: default_review_aur(),
+            aur_url: default_aur_url(),
+            devel: false,
+            roles: Vec::new(),
+            keep_backups: default_keep_backups(),
+            snapshot: None,
+            holds: Vec::new(),
+            hooks: Vec::new(),
+            path: None,
         }
     }
 }
@@ -25,31 +146,193 @@ impl Config {
         let path = config_path();
         if path.exists() {
             let content = std::fs::read_to_string(&path)?;
-            Ok(toml::from_str(&content)?)
+            let mut config: Self = toml::from_str(&content)?;
+            config.path = Some(path);
+            Ok(config)
         } else {
             // Create default config
-            let config = Self::default();
+            let config = Self {
+                path: Some(path),
+                ..Self::default()
+            };
             config.save()?;
             Ok(config)
         }
     }
 
     pub fn save(&self) -> Result<()> {
-        let dir = config_dir();
-        std::fs::create_dir_all(&dir)?;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.render()?)?;
+        Ok(())
+    }
+
+    /// Write the hold list to config.toml, leaving the rest of the file as the user wrote it
+    pub fn save_holds(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return self.save(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut document: toml_edit::DocumentMut = content.parse()?;
+        let had_holds = document.contains_key("hold");
+
+        let mut tables = toml_edit::ArrayOfTables::new();
+        for hold in &self.holds {
+            let mut table = toml_edit::Table::new();
+            table.insert("name", toml_edit::value(&hold.name));
+            if let Some(until_version) = &hold.until_version {
+                table.insert("until_version", toml_edit::value(until_version));
+            }
+            if let Some(until) = &hold.until {
+                table.insert("until", toml_edit::value(until));
+            }
+            tables.push(table);
+        }
 
-        let content = format!(
+        if tables.is_empty() {
+            document.remove("hold");
+        } else {
+            // Holds added to a file that had none go at the end under a heading
+            if let Some(first) = tables.get_mut(0).filter(|_| !had_holds) {
+                first.decor_mut().set_prefix("\n# Held packages (managed by upkeep)\n");
+            }
+            document.insert("hold", toml_edit::Item::ArrayOfTables(tables));
+        }
+        std::fs::write(path, document.to_string())?;
+        Ok(())
+    }
+
+    /// config.toml with every setting commented
+    fn render(&self) -> Result<String> {
+        // Strings go through toml so quotes and backslashes are escaped
+        let string = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let archive_url = match &self.archive_url {
+            Some(url) => format!("archive_url = {}", string(url)),
+            None => "# archive_url = \"https://archive.archlinux.org\"".to_string(),
+        };
+
+        let mut content = format!(
             r#"# Upkeep configuration
 
 # AUR helper to use for updates (default: yay)
 # Alternatives: paru, pikaur, etc.
-aur_helper = "{}"
+aur_helper = {}
+
+# Arch Linux Archive mirror to list older versions from when downgrading
+# (the local pacman cache is always used)
+{}
+
+# Scan for AUR packages with binaries linking to libraries that no longer exist,
+# or with modules left in an old interpreter directory (e.g. /usr/lib/python3.11),
+# and for DKMS modules not built for every installed kernel, and list them on the
+# Rebuilds tab
+scan_libraries = {}
+
+# Review the PKGBUILD of AUR packages before installing or updating them, as a diff
+# against the revision reviewed last time, with warnings for risky changes
+review_aur = {}
+
+# AUR the PKGBUILDs are fetched from
+aur_url = {}
+
+# Check VCS packages (-git, -svn, -hg) for new upstream commits with git ls-remote and
+# list them as "devel" updates; queries every source of them on each refresh
+devel = {}
+
+# Roles of this machine; the [[group]]s of packages.toml for these roles (or for this
+# hostname) are added to the packages it should have, e.g. ["dev", "laptop"]
+roles = {}
+
+# Package exports (c on the Installed tab) kept in ~/.config/upkeep/backups; older
+# ones are deleted after each export (0 = keep all)
+keep_backups = {}
+
+# Filesystem snapshot taken before every update, reinstall, removal and rebuild, e.g.
+# [snapshot]
+# provider = "snapper"        # "snapper", "timeshift", "btrfs" or "command"
+# snapper_config = "root"
+# subvolume = "/"             # btrfs: subvolume to snapshot...
+# directory = "/.snapshots"   # ...and where to put it
+# command = "my-snap {{label}}" # command: prints the snapshot ID last
+# delete_command = "my-snap-rm {{id}}"
+# keep = 5                    # snapshots kept when pruning
+
+# Commands run before (pre-) or after (post-) updates, installs, removals and rebuilds,
+# with $UPKEEP_PACKAGES, $UPKEEP_TRANSACTION and (post) $UPKEEP_EXIT_CODE set; a
+# failing pre hook stops the transaction, e.g.
+# [[hook]]
+# phase = "pre-update"        # pre/post-update, -install, -remove or -rebuild
+# command = "virsh shutdown win10"
+# packages = ["linux*", "nvidia*"]   # only when one of these is affected
+# timeout = 60
 "#,
-            self.aur_helper
+            string(&self.aur_helper),
+            archive_url,
+            self.scan_libraries,
+            self.review_aur,
+            string(&self.aur_url),
+            self.devel,
+            toml::Value::try_from(&self.roles)?,
+            self.keep_backups
         );
 
-        std::fs::write(config_path(), content)?;
-        Ok(())
+        if let Some(snapshot) = &self.snapshot {
+            content.push('\n');
+            content.push_str(&toml::to_string(&SnapshotTable { snapshot })?);
+        }
+
+        if !self.holds.is_empty() {
+            content.push_str("\n# Held packages (managed by upkeep)\n");
+            content.push_str(&toml::to_string(&HoldTables { hold: &self.holds })?);
+        }
+
+        if !self.hooks.is_empty() {
+            content.push_str("\n# Commands run around transactions\n");
+            content.push_str(&toml::to_string(&HookTables { hook: &self.hooks })?);
+        }
+
+        Ok(content)
+    }
+
+    /// The hold on a package if one exists and has not expired
+    pub fn active_hold(&self, name: &str, available: Option<&str>) -> Option<&Hold> {
+        let today = chrono::Local::now().date_naive();
+        self.holds
+            .iter()
+            .find(|h| h.name == name && h.is_active(available, today))
+    }
+
+    /// Add a package to the hold list
+    /// A package that is already held keeps its hold, with only the given bounds changed
+    pub fn add_hold(&mut self, name: &str, until: Option<String>, until_version: Option<String>) {
+        match self.holds.iter_mut().find(|h| h.name == name) {
+            Some(hold) => {
+                if until.is_some() {
+                    hold.until = until;
+                }
+                if until_version.is_some() {
+                    hold.until_version = until_version;
+                }
+            }
+            None => self.holds.push(Hold {
+                name: name.to_string(),
+                until_version,
+                until,
+            }),
+        }
+    }
+
+    /// Remove a package from the hold list
+    pub fn remove_hold(&mut self, name: &str) {
+        self.holds.retain(|h| h.name != name);
     }
 }
 
@@ -62,3 +345,108 @@ pub fn config_dir() -> PathBuf {
 pub fn config_path() -> PathBuf {
     config_dir().join("config.toml")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookPhase;
+    use crate::snapshot::SnapshotProvider;
+
+    #[test]
+    fn test_hold_expiry() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let hold = |until_version: Option<&str>, until: Option<&str>| Hold {
+            name: "linux".into(),
+            until_version: until_version.map(String::from),
+            until: until.map(String::from),
+        };
+
+        assert!(hold(None, None).is_active(Some("6.8-1"), today));
+        assert!(hold(None, Some("2024-01-22")).is_active(None, today));
+        assert!(!hold(None, Some("2024-01-15")).is_active(None, today));
+        assert!(hold(Some("6.8"), None).is_active(Some("6.7.2-1"), today));
+        assert!(!hold(Some("6.8"), None).is_active(Some("6.8.1-1"), today));
+        assert!(hold(Some("6.8"), None).is_active(None, today));
+    }
+
+    #[test]
+    fn test_holds_round_trip() {
+        let mut config = Config::default();
+        config.add_hold("linux", Some("2024-01-22".into()), None);
+        config.add_hold("mesa", None, None);
+        let tables = toml::to_string(&HoldTables { hold: &config.holds }).unwrap();
+        assert!(tables.contains("[[hold]]"));
+
+        let parsed: Config = toml::from_str(&tables).unwrap();
+        assert_eq!(parsed.holds, config.holds);
+
+        // Holding again only changes the bounds given
+        config.add_hold("linux", None, Some("6.8".into()));
+        config.add_hold("linux", None, None);
+        assert_eq!(config.holds.len(), 2);
+        assert_eq!(config.holds[0].until.as_deref(), Some("2024-01-22"));
+        assert_eq!(config.holds[0].until_version.as_deref(), Some("6.8"));
+    }
+
+    #[test]
+    fn test_save_holds_keeps_the_rest() {
+        let path = std::env::temp_dir().join(format!("upkeep-config-{}.toml", std::process::id()));
+        let written = "# mine\naur_helper = \"paru\" # the fast one\nunknown = 1\n";
+        std::fs::write(&path, written).unwrap();
+        let mut config: Config = toml::from_str(written).unwrap();
+        config.path = Some(path.clone());
+
+        config.add_hold("linux", None, Some("6.8".into()));
+        config.save_holds().unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(written));
+        let parsed: Config = toml::from_str(&content).unwrap();
+        assert_eq!(parsed.holds, config.holds);
+
+        config.remove_hold("linux");
+        config.save_holds().unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(content.starts_with(written));
+        assert!(!content.contains("[[hold]]"));
+    }
+
+    #[test]
+    fn test_render_escapes_strings() {
+        let config = Config {
+            aur_helper: "paru".into(),
+            archive_url: Some(r#"https://example.org/a"rchive\"#.into()),
+            aur_url: r"https://aur.example.org\".into(),
+            ..Config::default()
+        };
+        let parsed: Config = toml::from_str(&config.render().unwrap()).unwrap();
+        assert_eq!(parsed.archive_url, config.archive_url);
+        assert_eq!(parsed.aur_url, config.aur_url);
+        assert_eq!(parsed.aur_helper, "paru");
+    }
+
+    #[test]
+    fn test_hook_tables() {
+        let config: Config = toml::from_str(
+            "[[hook]]\nphase = \"pre-update\"\ncommand = \"true\"\npackages = [\"linux*\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.hooks[0].phase, HookPhase::PreUpdate);
+
+        let tables = toml::to_string(&HookTables { hook: &config.hooks }).unwrap();
+        let parsed: Config = toml::from_str(&tables).unwrap();
+        assert_eq!(parsed.hooks, config.hooks);
+    }
+
+    #[test]
+    fn test_snapshot_table() {
+        let config: Config = toml::from_str("[snapshot]\nprovider = \"snapper\"\n").unwrap();
+        let snapshot = config.snapshot.unwrap();
+        assert_eq!(snapshot.provider, SnapshotProvider::Snapper);
+        assert_eq!((snapshot.snapper_config.as_str(), snapshot.keep), ("root", 5));
+
+        let table = toml::to_string(&SnapshotTable { snapshot: &snapshot }).unwrap();
+        let parsed: Config = toml::from_str(&table).unwrap();
+        assert_eq!(parsed.snapshot, Some(snapshot));
+    }
+}
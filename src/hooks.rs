@@ -0,0 +1,201 @@
+use crate::action::Action;
+use crate::process::run_with_timeout;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Duration;
+
+/// Seconds a hook may run before it is killed
+const DEFAULT_TIMEOUT: u64 = 60;
+
+/// When a hook runs: before or after a kind of transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookPhase {
+    PreUpdate,
+    PostUpdate,
+    PreInstall,
+    PostInstall,
+    PreRemove,
+    PostRemove,
+    PreRebuild,
+    PostRebuild,
+}
+
+impl HookPhase {
+    pub fn label(self) -> &'static str {
+        match self {
+            HookPhase::PreUpdate => "pre-update",
+            HookPhase::PostUpdate => "post-update",
+            HookPhase::PreInstall => "pre-install",
+            HookPhase::PostInstall => "post-install",
+            HookPhase::PreRemove => "pre-remove",
+            HookPhase::PostRemove => "post-remove",
+            HookPhase::PreRebuild => "pre-rebuild",
+            HookPhase::PostRebuild => "post-rebuild",
+        }
+    }
+
+    /// Phases around a transaction running `action` as (pre, post); None for config file
+    /// and service actions
+    pub fn around(action: &Action) -> Option<(HookPhase, HookPhase)> {
+        match action {
+            Action::RunUpdate(_) | Action::Downgrade(..) => {
+                Some((HookPhase::PreUpdate, HookPhase::PostUpdate))
+            }
+            Action::Install(_) | Action::Reinstall(_) => {
+                Some((HookPhase::PreInstall, HookPhase::PostInstall))
+            }
+            Action::Uninstall(_) | Action::UninstallWithDeps(_) => {
+                Some((HookPhase::PreRemove, HookPhase::PostRemove))
+            }
+            Action::ForceRebuild(_) | Action::RunRebuild(_) => {
+                Some((HookPhase::PreRebuild, HookPhase::PostRebuild))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A `[[hook]]` from config.toml: a shell command run around upkeep transactions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hook {
+    pub phase: HookPhase,
+    /// Run with `sh -c`
+    pub command: String,
+    /// Only run when an affected package matches one of these globs (e.g. "linux*")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<String>,
+    /// Seconds before the command is killed (default: 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl Hook {
+    fn applies_to(&self, packages: &[String]) -> bool {
+        if self.packages.is_empty() {
+            return true;
+        }
+        let globs: Vec<Regex> = self.packages.iter().filter_map(|glob| glob_regex(glob)).collect();
+        packages.iter().any(|name| globs.iter().any(|glob| glob.is_match(name)))
+    }
+}
+
+/// What a transaction is about, passed to its hooks as environment variables
+pub struct HookContext<'a> {
+    /// Transaction title, e.g. "Update All Packages" ($UPKEEP_TRANSACTION)
+    pub transaction: &'a str,
+    /// Affected packages ($UPKEEP_PACKAGES, space separated)
+    pub packages: &'a [String],
+    /// Exit code of the finished transaction, for post hooks ($UPKEEP_EXIT_CODE)
+    pub exit_code: Option<u32>,
+}
+
+/// Run the hooks for `phase` in config order
+/// Pre hooks stop at the first failure; post hooks all run. Returns one message per failure.
+pub fn run_hooks(hooks: &[Hook], phase: HookPhase, context: &HookContext) -> Vec<String> {
+    let is_pre = phase.label().starts_with("pre-");
+    let mut errors = Vec::new();
+    for hook in hooks.iter().filter(|h| h.phase == phase && h.applies_to(context.packages)) {
+        if let Err(e) = run_hook(hook, context) {
+            errors.push(format!("{} hook `{}` failed: {}", phase.label(), hook.command, e));
+            if is_pre {
+                break;
+            }
+        }
+    }
+    errors
+}
+
+fn run_hook(hook: &Hook, context: &HookContext) -> Result<(), String> {
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", &hook.command])
+        .env("UPKEEP_PHASE", hook.phase.label())
+        .env("UPKEEP_TRANSACTION", context.transaction)
+        .env("UPKEEP_PACKAGES", context.packages.join(" "));
+    if let Some(code) = context.exit_code {
+        command.env("UPKEEP_EXIT_CODE", code.to_string());
+    }
+
+    let timeout = hook.timeout.unwrap_or(DEFAULT_TIMEOUT);
+    let output =
+        run_with_timeout(&mut command, Duration::from_secs(timeout)).map_err(|e| e.to_string())?;
+    if output.timed_out {
+        return Err(format!("timed out after {}s", timeout));
+    }
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // The last thing it complained about usually says why
+    match output.stderr.lines().map(str::trim).rfind(|line| !line.is_empty()) {
+        Some(line) => Err(line.to_string()),
+        None => Err(match output.status.code() {
+            Some(code) => format!("exited with status {}", code),
+            None => "killed by a signal".to_string(),
+        }),
+    }
+}
+
+/// Anchored regex for a shell-style glob with `*` and `?`
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let pattern = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    Regex::new(&format!("^{}$", pattern)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(phase: HookPhase, command: &str, packages: &[&str]) -> Hook {
+        Hook {
+            phase,
+            command: command.to_string(),
+            packages: packages.iter().map(|p| p.to_string()).collect(),
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_hook_package_globs() {
+        let kernel = hook(HookPhase::PreUpdate, "true", &["linux*", "nvidia-?kms"]);
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(kernel.applies_to(&names(&["mesa", "linux-lts"])));
+        assert!(kernel.applies_to(&names(&["nvidia-dkms"])));
+        assert!(!kernel.applies_to(&names(&["mesa", "util-linux"])));
+        assert!(hook(HookPhase::PreUpdate, "true", &[]).applies_to(&[]));
+    }
+
+    #[test]
+    fn test_run_hooks() {
+        let packages = vec!["linux".to_string(), "mesa".to_string()];
+        let context = HookContext {
+            transaction: "Update Packages",
+            packages: &packages,
+            exit_code: None,
+        };
+        let hooks = vec![
+            hook(HookPhase::PreUpdate, r#"[ "$UPKEEP_PACKAGES" = "linux mesa" ]"#, &[]),
+            hook(HookPhase::PreRemove, "exit 1", &[]),
+            hook(HookPhase::PreUpdate, "echo 'VM still running' >&2; exit 3", &["linux"]),
+            hook(HookPhase::PreUpdate, "exit 4", &[]),
+        ];
+
+        // Stops at the first failing pre hook
+        assert_eq!(
+            run_hooks(&hooks, HookPhase::PreUpdate, &context),
+            vec!["pre-update hook `echo 'VM still running' >&2; exit 3` failed: VM still running"]
+        );
+        assert!(run_hooks(&hooks, HookPhase::PostUpdate, &context).is_empty());
+
+        let slow = Hook {
+            timeout: Some(1),
+            ..hook(HookPhase::PostRemove, "sleep 10", &[])
+        };
+        assert_eq!(
+            run_hooks(&[slow], HookPhase::PostRemove, &context),
+            vec!["post-remove hook `sleep 10` failed: timed out after 1s"]
+        );
+    }
+}
@@ -1,8 +1,15 @@
 mod action;
 mod app;
 mod backup;
+mod cli;
 mod config;
+mod hooks;
+mod manifest;
+mod process;
 mod rebuilds;
+mod snapshot;
+mod state;
+mod transaction;
 mod ui;
 mod updates;
 
@@ -14,6 +21,12 @@ use ratatui::DefaultTerminal;
 use std::time::Duration;
 
 fn main() -> Result<()> {
+    // Any arguments select headless mode (e.g. `upkeep check --json`)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
     let mut terminal = ratatui::init();
     let result = run(&mut terminal);
     ratatui::restore();
@@ -26,53 +39,78 @@ fn run(terminal: &mut DefaultTerminal) -> Result<()> {
     // Initial update check
     app.refresh();
 
+    // News and the last upgrade time feed the pre-upgrade news gate
+    app.refresh_news();
+    if app.state.last_upgrade.is_none() {
+        app.refresh_history();
+    }
+
+    // Packages may have been upgraded outside upkeep since the last boot
+    app.refresh_restart();
+
     loop {
         terminal.draw(|frame| ui::draw(frame, &mut app))?;
 
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    let size = ui::transaction_size(terminal.size()?);
                     match app.handle_key(key) {
                         Action::Quit => break,
-                        Action::RunUpdate(packages) => {
-                            run_update(terminal, &app, packages)?;
-                            app.refresh();
+                        Action::RunUpdate(packages) => run_update(&mut app, packages, size),
+                        Action::RunRebuild(command) => run_command(&mut app, command, size),
+                        Action::AddCheckTemplates(names) => {
+                            match rebuilds::add_templates(&names, &app.config.aur_helper) {
+                                Ok(count) => {
+                                    app.reload_checks();
+                                    app.last_message = Some(format!("Added {} check(s) to checks.toml", count));
+                                }
+                                Err(e) => app.last_message = Some(format!("Failed to add checks: {}", e)),
+                            }
                         }
-                        Action::RunRebuild(command) => {
-                            run_command(terminal, &command)?;
-                            app.refresh_rebuilds();
+                        Action::Uninstall(packages) => run_uninstall(&mut app, packages, false, size),
+                        Action::UninstallWithDeps(packages) => {
+                            run_uninstall(&mut app, packages, true, size)
                         }
-                        Action::Uninstall(packages) => {
-                            run_uninstall(terminal, &app, packages, false)?;
-                            app.refresh_installed();
-                            app.refresh_orphans();
+                        Action::Reinstall(packages) => run_reinstall(&mut app, packages, false, size),
+                        Action::ForceRebuild(packages) => {
+                            run_reinstall(&mut app, packages, true, size)
                         }
-                        Action::UninstallWithDeps(packages) => {
-                            run_uninstall(terminal, &app, packages, true)?;
-                            app.refresh_installed();
-                            app.refresh_orphans();
+                        Action::Install(packages) => run_install(&mut app, packages, size),
+                        Action::MarkAsDeps(packages) => {
+                            let argv = command_line("sudo", &["pacman", "-D", "--asdeps"], &packages);
+                            app.start_transaction("Mark as Dependencies", Action::MarkAsDeps(packages), argv, size);
                         }
-                        Action::Reinstall(packages) => {
-                            run_reinstall(terminal, &app, packages, false)?;
-                            app.refresh_installed();
+                        Action::Downgrade(candidate, hold) => {
+                            run_downgrade(&mut app, candidate, hold, size)
                         }
-                        Action::ForceRebuild(packages) => {
-                            run_reinstall(terminal, &app, packages, true)?;
-                            app.refresh_installed();
+                        Action::CleanCache => run_cache_cleanup(&mut app, size),
+                        Action::KeepCurrentConfig(file) => {
+                            let argv = command_line("sudo", &["rm", "--", &file.path], &[]);
+                            app.start_transaction("Keep Current Config", Action::KeepCurrentConfig(file), argv, size);
                         }
-                        Action::Install(packages) => {
-                            run_install(terminal, &app, packages)?;
-                            app.refresh_installed();
-                            // Re-run search to update installed status
-                            app.do_search();
+                        Action::TakeNewConfig(file) => {
+                            let argv = command_line("sudo", &["mv", "-f", "--", &file.path, &file.target], &[]);
+                            app.start_transaction("Take New Config", Action::TakeNewConfig(file), argv, size);
                         }
-                        Action::CleanCache => {
-                            run_cache_cleanup(terminal)?;
+                        Action::MergeConfig(file) => run_merge(&mut app, file, size),
+                        Action::DeletePacnew(paths) => {
+                            let argv = command_line("sudo", &["rm", "--"], &paths);
+                            app.start_transaction("Delete Files", Action::DeletePacnew(paths), argv, size);
                         }
+                        Action::RestartServices(units) => {
+                            let argv = command_line("sudo", &["systemctl", "restart"], &units);
+                            app.start_transaction("Restart Services", Action::RestartServices(units), argv, size);
+                        }
+                        Action::DeleteSnapshots(ids) => run_delete_snapshots(&mut app, ids, size),
                         Action::ExportPackages => {
-                            app.last_message = match backup::export_packages() {
-                                Ok((pkg_path, _, pkg_count, aur_count)) => {
-                                    Some(format!("Exported {} + {} AUR to {}", pkg_count, aur_count, pkg_path.parent().unwrap().display()))
+                            app.last_message = match backup::export_packages(app.config.keep_backups) {
+                                Ok(export) => {
+                                    let pruned = match export.pruned {
+                                        0 => String::new(),
+                                        n => format!(", removed {} old file(s)", n),
+                                    };
+                                    Some(format!("Exported {} + {} AUR to {}{}", export.official, export.aur, export.dir.display(), pruned))
                                 }
                                 Err(e) => Some(format!("Export failed: {}", e)),
                             };
@@ -88,7 +126,8 @@ fn run(terminal: &mut DefaultTerminal) -> Result<()> {
                                 Err(e) => Some(format!("Failed: {}", e)),
                             };
                         }
-                        Action::None => {}
+                        // Snapshots before transactions are started by the app itself
+                        Action::TakeSnapshot | Action::None => {}
                     }
                 }
             }
@@ -105,160 +144,102 @@ fn run(terminal: &mut DefaultTerminal) -> Result<()> {
     Ok(())
 }
 
-fn run_update(terminal: &mut DefaultTerminal, app: &App, packages: Vec<String>) -> Result<()> {
-    // Restore terminal to normal mode
-    ratatui::restore();
+/// Build an argv from a program, fixed arguments and package names
+fn command_line(program: &str, args: &[&str], packages: &[String]) -> Vec<String> {
+    std::iter::once(program.to_string())
+        .chain(args.iter().map(|a| a.to_string()))
+        .chain(packages.iter().cloned())
+        .collect()
+}
 
-    // Build and run the update command
-    let helper = &app.config.aur_helper;
-    let status = if packages.is_empty() {
-        // Update all
-        std::process::Command::new(helper).arg("-Syu").status()?
+fn run_update(app: &mut App, packages: Vec<String>, size: (u16, u16)) {
+    let helper = app.config.aur_helper.clone();
+    let (title, argv) = if packages.is_empty() {
+        // Update all, skipping held packages
+        // VCS packages only rebuild when named, as their version has not changed
+        let mut argv = command_line(&helper, &["-Syu"], &app.devel_updates(&[]));
+        let ignored = app.ignored_packages();
+        if !ignored.is_empty() {
+            argv.push("--ignore".to_string());
+            argv.push(ignored.join(","));
+        }
+        ("Update All Packages", argv)
     } else {
-        // Update selected packages
-        std::process::Command::new(helper)
-            .arg("-S")
-            .arg("--needed")
-            .args(&packages)
-            .status()?
+        // Update selected packages; --needed would skip devel updates
+        let args: &[&str] =
+            if app.devel_updates(&packages).is_empty() { &["-S", "--needed"] } else { &["-S"] };
+        ("Update Packages", command_line(&helper, args, &packages))
     };
-
-    if !status.success() {
-        eprintln!("\nUpdate command exited with status: {}", status);
-    }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-
-    // Re-initialize terminal
-    *terminal = ratatui::init();
-    Ok(())
+    app.start_transaction(title, Action::RunUpdate(packages), argv, size);
 }
 
-fn run_command(terminal: &mut DefaultTerminal, command: &str) -> Result<()> {
-    ratatui::restore();
-
-    let status = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .status()?;
-
-    if !status.success() {
-        eprintln!("\nCommand exited with status: {}", status);
-    }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-
-    *terminal = ratatui::init();
-    Ok(())
+fn run_command(app: &mut App, command: String, size: (u16, u16)) {
+    let argv = command_line("sh", &["-c", &command], &[]);
+    app.start_transaction("Rebuild", Action::RunRebuild(command), argv, size);
 }
 
-fn run_uninstall(
-    terminal: &mut DefaultTerminal,
-    app: &App,
-    packages: Vec<String>,
-    with_deps: bool,
-) -> Result<()> {
-    ratatui::restore();
-
-    let helper = &app.config.aur_helper;
-    let status = if with_deps {
+fn run_uninstall(app: &mut App, packages: Vec<String>, with_deps: bool, size: (u16, u16)) {
+    let helper = app.config.aur_helper.clone();
+    if with_deps {
         // Remove with dependencies and config files
-        std::process::Command::new(helper)
-            .arg("-Rns")
-            .args(&packages)
-            .status()?
+        let argv = command_line(&helper, &["-Rns"], &packages);
+        app.start_transaction("Remove Packages", Action::UninstallWithDeps(packages), argv, size);
     } else {
         // Simple remove
-        std::process::Command::new(helper)
-            .arg("-R")
-            .args(&packages)
-            .status()?
-    };
-
-    if !status.success() {
-        eprintln!("\nUninstall command exited with status: {}", status);
+        let argv = command_line(&helper, &["-R"], &packages);
+        app.start_transaction("Remove Packages", Action::Uninstall(packages), argv, size);
     }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-
-    *terminal = ratatui::init();
-    Ok(())
 }
 
-fn run_reinstall(
-    terminal: &mut DefaultTerminal,
-    app: &App,
-    packages: Vec<String>,
-    force_rebuild: bool,
-) -> Result<()> {
-    ratatui::restore();
-
-    let helper = &app.config.aur_helper;
-    let status = if force_rebuild {
+fn run_reinstall(app: &mut App, packages: Vec<String>, force_rebuild: bool, size: (u16, u16)) {
+    let helper = app.config.aur_helper.clone();
+    if force_rebuild {
         // Force rebuild from source
-        std::process::Command::new(helper)
-            .arg("-S")
-            .arg("--rebuild")
-            .args(&packages)
-            .status()?
+        let argv = command_line(&helper, &["-S", "--rebuild"], &packages);
+        app.start_transaction("Rebuild Packages", Action::ForceRebuild(packages), argv, size);
     } else {
         // Reinstall (redownload)
-        std::process::Command::new(helper)
-            .arg("-S")
-            .args(&packages)
-            .status()?
-    };
-
-    if !status.success() {
-        eprintln!("\nReinstall command exited with status: {}", status);
+        let argv = command_line(&helper, &["-S"], &packages);
+        app.start_transaction("Reinstall Packages", Action::Reinstall(packages), argv, size);
     }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-
-    *terminal = ratatui::init();
-    Ok(())
 }
 
-fn run_install(terminal: &mut DefaultTerminal, app: &App, packages: Vec<String>) -> Result<()> {
-    ratatui::restore();
-
-    let helper = &app.config.aur_helper;
-    let status = std::process::Command::new(helper)
-        .arg("-S")
-        .args(&packages)
-        .status()?;
+fn run_install(app: &mut App, packages: Vec<String>, size: (u16, u16)) {
+    let helper = app.config.aur_helper.clone();
+    let argv = command_line(&helper, &["-S"], &packages);
+    app.start_transaction("Install Packages", Action::Install(packages), argv, size);
+}
 
-    if !status.success() {
-        eprintln!("\nInstall command exited with status: {}", status);
+fn run_delete_snapshots(app: &mut App, ids: Vec<String>, size: (u16, u16)) {
+    match app.snapshot_delete_command(&ids) {
+        Ok(argv) => {
+            app.start_transaction("Delete Snapshots", Action::DeleteSnapshots(ids), argv, size)
+        }
+        Err(e) => app.last_message = Some(format!("Cannot delete snapshots: {}", e)),
     }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-
-    *terminal = ratatui::init();
-    Ok(())
 }
 
-fn run_cache_cleanup(terminal: &mut DefaultTerminal) -> Result<()> {
-    ratatui::restore();
-
-    println!("Cleaning package cache...\n");
-    let status = std::process::Command::new("paccache")
-        .arg("-r")
-        .status()?;
-
-    if !status.success() {
-        eprintln!("\nCache cleanup exited with status: {}", status);
-    }
-    eprintln!("\nPress Enter to continue...");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
+fn run_downgrade(app: &mut App, candidate: updates::DowngradeCandidate, hold: bool, size: (u16, u16)) {
+    // pacman -U accepts both cached package files and archive URLs
+    let argv = command_line("sudo", &["pacman", "-U", &candidate.location], &[]);
+    app.start_transaction("Downgrade Package", Action::Downgrade(candidate, hold), argv, size);
+}
 
-    *terminal = ratatui::init();
-    Ok(())
+/// Open the live config and its pacnew side by side, like pacdiff does
+fn run_merge(app: &mut App, file: updates::PacnewFile, size: (u16, u16)) {
+    let argv = match std::env::var("DIFFPROG") {
+        // DIFFPROG may carry its own arguments (e.g. "vim -d"), so let the shell split it
+        Ok(diffprog) if !diffprog.is_empty() => {
+            let script = format!("{} \"$1\" \"$2\"", diffprog);
+            command_line("sudo", &["sh", "-c", &script, "sh", &file.target, &file.path], &[])
+        }
+        // sudoedit honours $SUDO_EDITOR, $VISUAL and $EDITOR
+        _ => command_line("sudoedit", &[&file.target, &file.path], &[]),
+    };
+    app.start_transaction("Merge Config", Action::MergeConfig(file), argv, size);
 }
 
+fn run_cache_cleanup(app: &mut App, size: (u16, u16)) {
+    let argv = command_line("paccache", &["-r"], &[]);
+    app.start_transaction("Clean Package Cache", Action::CleanCache, argv, size);
+}
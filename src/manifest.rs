@@ -0,0 +1,309 @@
+use crate::config::config_dir;
+use crate::updates::{InstalledPackage, PackageBackend, PackageSource};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// packages.toml: the packages every machine (or some of them) should have installed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Official packages for every machine
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// AUR packages for every machine
+    #[serde(default)]
+    pub aur: Vec<String>,
+    /// Packages for some machines only, one `[[group]]` table each
+    #[serde(default, rename = "group", skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ManifestGroup>,
+}
+
+/// Packages declared for the machines with one of the given hostnames or roles
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManifestGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// Matched against `roles` in config.toml
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub packages: Vec<String>,
+    #[serde(default)]
+    pub aur: Vec<String>,
+}
+
+impl ManifestGroup {
+    fn applies_to(&self, host: &str, roles: &[String]) -> bool {
+        self.hosts.iter().any(|h| h == host) || self.roles.iter().any(|r| roles.contains(r))
+    }
+}
+
+/// A package the manifest asks for on this machine
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredPackage {
+    pub name: String,
+    pub aur: bool,
+    /// Group it was declared in; None for the top-level lists
+    pub group: Option<String>,
+}
+
+/// Which way an installed package differs from the manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftKind {
+    /// Declared but not installed
+    Missing,
+    /// Explicitly installed but not declared
+    Extra,
+}
+
+impl DriftKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DriftKind::Missing => "missing",
+            DriftKind::Extra => "not declared",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftEntry {
+    pub name: String,
+    pub kind: DriftKind,
+    pub aur: bool,
+    /// Manifest group of a missing package
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Installed version of an extra package
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip)]
+    pub selected: bool,
+}
+
+impl Manifest {
+    /// Read a manifest; Ok(None) if the file does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map(Some)
+                .map_err(|e| format!("Invalid {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Manifest declaring exactly what is explicitly installed, as a starting point
+    pub fn from_installed(installed: &[InstalledPackage]) -> Self {
+        let names = |aur: bool| -> Vec<String> {
+            installed
+                .iter()
+                .filter(|p| (p.source != PackageSource::Pacman) == aur)
+                .map(|p| p.name.clone())
+                .collect()
+        };
+        Self {
+            packages: names(false),
+            aur: names(true),
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = format!(
+            r#"# Packages upkeep keeps this machine in line with (see the Drift tab)
+#
+# Packages for some machines only go in groups, applied by hostname or by the
+# roles listed in config.toml, e.g.
+# [[group]]
+# name = "laptops"
+# hosts = ["thinkpad"]
+# roles = ["dev"]
+# packages = ["tlp"]
+# aur = ["visual-studio-code-bin"]
+
+{}"#,
+            toml::to_string_pretty(self)?
+        );
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Packages declared for a machine, in manifest order; the first declaration of a name wins
+    pub fn declared(&self, host: &str, roles: &[String]) -> Vec<DeclaredPackage> {
+        let mut seen = BTreeSet::new();
+        let mut declared = Vec::new();
+        let mut add = |names: &[String], aur: bool, group: Option<&str>| {
+            for name in names {
+                if seen.insert(name.clone()) {
+                    declared.push(DeclaredPackage {
+                        name: name.clone(),
+                        aur,
+                        group: group.map(String::from),
+                    });
+                }
+            }
+        };
+
+        add(&self.packages, false, None);
+        add(&self.aur, true, None);
+        for group in self.groups.iter().filter(|g| g.applies_to(host, roles)) {
+            add(&group.packages, false, Some(&group.name));
+            add(&group.aur, true, Some(&group.name));
+        }
+        declared
+    }
+}
+
+/// Compare the declared packages with what is installed: missing ones first, then extras
+///
+/// `present` holds the installed versions of declared packages, explicit or not, so a
+/// declared package pulled in as a dependency does not count as missing.
+pub fn find_drift(
+    declared: &[DeclaredPackage],
+    installed: &[InstalledPackage],
+    present: &BTreeMap<String, String>,
+) -> Vec<DriftEntry> {
+    let mut missing: Vec<DriftEntry> = declared
+        .iter()
+        .filter(|p| !present.contains_key(&p.name))
+        .map(|p| DriftEntry {
+            name: p.name.clone(),
+            kind: DriftKind::Missing,
+            aur: p.aur,
+            group: p.group.clone(),
+            version: None,
+            selected: false,
+        })
+        .collect();
+    missing.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let names: BTreeSet<&str> = declared.iter().map(|p| p.name.as_str()).collect();
+    let mut extra: Vec<DriftEntry> = installed
+        .iter()
+        .filter(|p| !names.contains(p.name.as_str()))
+        .map(|p| DriftEntry {
+            name: p.name.clone(),
+            kind: DriftKind::Extra,
+            aur: p.source != PackageSource::Pacman,
+            group: None,
+            version: Some(p.version.clone()),
+            selected: false,
+        })
+        .collect();
+    extra.sort_by(|a, b| a.name.cmp(&b.name));
+
+    missing.extend(extra);
+    missing
+}
+
+/// Drift of this machine from the packages declared for it
+pub fn check_drift(backend: &dyn PackageBackend, declared: &[DeclaredPackage]) -> Vec<DriftEntry> {
+    let names: Vec<String> = declared.iter().map(|p| p.name.clone()).collect();
+    let present = backend.package_versions(&names);
+    find_drift(declared, &backend.installed(), &present)
+}
+
+/// Hostname groups are matched against
+pub fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
+
+pub fn manifest_path() -> PathBuf {
+    config_dir().join("packages.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_declared_packages() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+packages = ["base", "git"]
+aur = ["paru"]
+
+[[group]]
+name = "laptops"
+hosts = ["thinkpad"]
+packages = ["tlp", "git"]
+
+[[group]]
+name = "dev"
+roles = ["dev"]
+aur = ["visual-studio-code-bin"]
+"#,
+        )
+        .unwrap();
+
+        let names = |declared: Vec<DeclaredPackage>| -> Vec<String> {
+            declared.into_iter().map(|p| p.name).collect()
+        };
+        assert_eq!(names(manifest.declared("desktop", &[])), ["base", "git", "paru"]);
+        assert_eq!(
+            names(manifest.declared("thinkpad", &strings(&["dev"]))),
+            ["base", "git", "paru", "tlp", "visual-studio-code-bin"]
+        );
+
+        let declared = manifest.declared("thinkpad", &[]);
+        assert_eq!(declared[3].group.as_deref(), Some("laptops"));
+        assert!(declared[2].aur);
+    }
+
+    #[test]
+    fn test_find_drift() {
+        let declare = |name: &str, aur, group: Option<&str>| DeclaredPackage {
+            name: name.to_string(),
+            aur,
+            group: group.map(String::from),
+        };
+        let declared = vec![
+            declare("git", false, None),
+            declare("zlib", false, None),
+            declare("paru", true, Some("dev")),
+        ];
+        let installed = vec![
+            InstalledPackage::new("git".into(), "2.43.0-1".into(), PackageSource::Pacman),
+            InstalledPackage::new("yay".into(), "12.3.0-1".into(), PackageSource::Aur),
+            InstalledPackage::new("cowsay".into(), "3.04-3".into(), PackageSource::Pacman),
+        ];
+        // zlib is only installed as a dependency
+        let present: BTreeMap<String, String> =
+            [("git", "2.43.0-1"), ("zlib", "1:1.3-2")].map(|(n, v)| (n.into(), v.into())).into();
+
+        let drift = find_drift(&declared, &installed, &present);
+        let summary: Vec<(&str, DriftKind, bool)> =
+            drift.iter().map(|e| (e.name.as_str(), e.kind, e.aur)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("paru", DriftKind::Missing, true),
+                ("cowsay", DriftKind::Extra, false),
+                ("yay", DriftKind::Extra, true),
+            ]
+        );
+        assert_eq!(drift[0].group.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_manifest_from_installed() {
+        let installed = vec![
+            InstalledPackage::new("git".into(), "2.43.0-1".into(), PackageSource::Pacman),
+            InstalledPackage::new("yay".into(), "12.3.0-1".into(), PackageSource::Aur),
+        ];
+        let manifest = Manifest::from_installed(&installed);
+        assert_eq!((manifest.packages, manifest.aur), (strings(&["git"]), strings(&["yay"])));
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What a command run by `run_with_timeout` printed and how it ended
+pub struct TimedOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: ExitStatus,
+    pub timed_out: bool,
+}
+
+/// Run a command with its output captured, killing it once `timeout` has passed
+/// It gets its own process group, so a timeout also stops anything the command started
+pub fn run_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<TimedOutput> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0);
+
+    let mut child = command.spawn()?;
+    let stdout = capture(child.stdout.take());
+    let stderr = capture(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            timed_out = true;
+            let _ = Command::new("kill")
+                .args(["-KILL", "--", &format!("-{}", child.id())])
+                .status();
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    Ok(TimedOutput {
+        stdout: stdout.finish(),
+        stderr: stderr.finish(),
+        status,
+        timed_out,
+    })
+}
+
+/// Output collected by a background reader thread
+struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: Option<thread::JoinHandle<()>>,
+}
+
+impl Capture {
+    /// Everything read so far, giving the reader a moment to drain the pipe
+    /// (a leftover grandchild may hold it open indefinitely)
+    fn finish(self) -> String {
+        if let Some(reader) = self.reader {
+            let deadline = Instant::now() + Duration::from_millis(500);
+            while !reader.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        let buffer = self.buffer.lock().map(|b| b.clone()).unwrap_or_default();
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+fn capture(pipe: Option<impl Read + Send + 'static>) -> Capture {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let reader = pipe.map(|mut pipe| {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            while let Ok(n) = pipe.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut buffer) = buffer.lock() {
+                    buffer.extend_from_slice(&chunk[..n]);
+                }
+            }
+        })
+    });
+    Capture { buffer, reader }
+}
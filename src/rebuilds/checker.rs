@@ -1,51 +1,357 @@
-use super::config::RebuildCheck;
+use super::config::{MatchOn, RebuildCheck, DEFAULT_TIMEOUT};
+use super::dkms::scan_dkms;
+use super::interpreters::scan_interpreters;
+use super::scanner::scan_libraries;
+use crate::process::run_with_timeout;
+use regex::Regex;
+use serde::Serialize;
+use std::io::ErrorKind;
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RebuildIssue {
     pub name: String,
     pub rebuild_command: String,
+    /// Why a rebuild is needed: the matched output line, exit status or missing library
+    pub detail: String,
+    /// Package to rebuild with the AUR helper, for issues found by the built-in scans
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    #[serde(skip)]
     pub selected: bool,
 }
 
-pub fn check_rebuilds(checks: &[RebuildCheck]) -> Vec<RebuildIssue> {
-    checks
-        .iter()
-        .filter_map(|check| {
-            if has_rebuild_issue(check) {
-                Some(RebuildIssue {
-                    name: check.name.clone(),
-                    rebuild_command: check.rebuild.clone(),
-                    selected: false,
-                })
-            } else {
-                None
+/// Most checks wait on a subprocess, so a few more workers than cores is fine
+const MAX_WORKERS: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CheckStatus {
+    #[default]
+    Pending,
+    Running,
+    Ok,
+    Failed,
+    TimedOut,
+    /// Not run: its trigger packages are unchanged since it last passed
+    Skipped,
+}
+
+impl CheckStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pending => "pending",
+            CheckStatus::Running => "running",
+            CheckStatus::Ok => "ok",
+            CheckStatus::Failed => "failed",
+            CheckStatus::TimedOut => "timed out",
+            CheckStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Progress of one check in a run, reported as it happens
+#[derive(Debug, Clone)]
+pub enum CheckEvent {
+    Started(usize), // index into the checks
+    Finished(usize, CheckResult),
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    pub duration: Duration,
+    pub issue: Option<RebuildIssue>,
+}
+
+/// Run the configured checks, plus the built-in library, interpreter and DKMS scans when
+/// `scan_with` names the AUR helper to rebuild with
+pub fn find_rebuild_issues(checks: &[RebuildCheck], scan_with: Option<&str>) -> Vec<RebuildIssue> {
+    let found = Mutex::new(Vec::new());
+    run_checks(checks, |event| {
+        if let CheckEvent::Finished(index, CheckResult { issue: Some(issue), .. }) = event {
+            if let Ok(mut found) = found.lock() {
+                found.push((index, issue));
             }
-        })
-        .collect()
+        }
+    });
+
+    // Report in config order regardless of which check finished first
+    let mut found = found.into_inner().unwrap_or_default();
+    found.sort_by_key(|(index, _)| *index);
+    let mut issues: Vec<RebuildIssue> = found.into_iter().map(|(_, issue)| issue).collect();
+
+    if let Some(aur_helper) = scan_with {
+        merge_scanned(&mut issues, scan_packages(aur_helper));
+    }
+    issues
 }
 
-fn has_rebuild_issue(check: &RebuildCheck) -> bool {
-    if check.command.is_empty() {
-        return false;
+/// Run checks on a bounded pool of worker threads, reporting each start and result
+pub fn run_checks(checks: &[RebuildCheck], on_event: impl Fn(CheckEvent) + Sync) {
+    let workers = thread::available_parallelism()
+        .map_or(4, |n| n.get() * 2)
+        .min(MAX_WORKERS)
+        .min(checks.len());
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(check) = checks.get(index) else {
+                    break;
+                };
+                on_event(CheckEvent::Started(index));
+                on_event(CheckEvent::Finished(index, run_one(check)));
+            });
+        }
+    });
+}
+
+fn run_one(check: &RebuildCheck) -> CheckResult {
+    let started = Instant::now();
+    let (status, detail) = find_rebuild_issue(check);
+    CheckResult {
+        status,
+        duration: started.elapsed(),
+        issue: detail.map(|detail| RebuildIssue {
+            name: check.name.clone(),
+            rebuild_command: check.rebuild.clone(),
+            detail,
+            package: None,
+            selected: false,
+        }),
+    }
+}
+
+/// Issues found by the built-in library, interpreter and DKMS scans
+pub fn scan_packages(aur_helper: &str) -> Vec<RebuildIssue> {
+    let mut scanned = scan_libraries(aur_helper);
+    scanned.extend(scan_interpreters(aur_helper));
+    scanned.extend(scan_dkms());
+    scanned
+}
+
+/// Add scan results to the check issues
+pub fn merge_scanned(issues: &mut Vec<RebuildIssue>, scanned: Vec<RebuildIssue>) {
+    for issue in scanned {
+        match issues.iter_mut().find(|i| i.name == issue.name) {
+            // One entry per package, listing every reason
+            Some(existing) if existing.package.is_some() => {
+                existing.detail = format!("{}; {}", existing.detail, issue.detail);
+            }
+            // A hand-written check for the same package takes precedence
+            Some(_) => {}
+            None => issues.push(issue),
+        }
+    }
+}
+
+/// What a check command did
+#[derive(Debug, Default)]
+struct CheckOutput {
+    stdout: String,
+    stderr: String,
+    /// Exit code, None when killed by a signal
+    code: Option<i32>,
+    /// The check's own timeout killed the command
+    timed_out: bool,
+}
+
+/// Run a check, returning its status and why it fired (the matched line, exit status, ...)
+fn find_rebuild_issue(check: &RebuildCheck) -> (CheckStatus, Option<String>) {
+    let Some(program) = check.command.first() else {
+        return (CheckStatus::Ok, None);
+    };
+
+    let output = match run_check(check) {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound && check.missing_is_failure => {
+            return (CheckStatus::Failed, Some(format!("{} not found", program)));
+        }
+        Err(_) => return (CheckStatus::Ok, None),
+    };
+
+    match evaluate(check, &output) {
+        Some(detail) => (CheckStatus::Failed, Some(detail)),
+        None if output.timed_out => (CheckStatus::TimedOut, None),
+        None => (CheckStatus::Ok, None),
     }
+}
 
-    let result = Command::new(&check.command[0])
-        .args(&check.command[1..])
-        .output();
+fn evaluate(check: &RebuildCheck, output: &CheckOutput) -> Option<String> {
+    let regexes: Vec<Regex> = match check.regex_patterns.iter().map(|p| Regex::new(p)).collect() {
+        Ok(regexes) => regexes,
+        // Surface config mistakes on the Rebuilds tab rather than silently skipping the check
+        Err(e) => return Some(format!("invalid regex_patterns: {}", e)),
+    };
 
-    let output = match result {
-        Ok(o) => o,
-        Err(_) => return false,
+    let text = match check.match_on {
+        MatchOn::Stderr => output.stderr.clone(),
+        MatchOn::Stdout => output.stdout.clone(),
+        MatchOn::Both => format!("{}\n{}", output.stdout, output.stderr),
     };
+    let matched = text.lines().find(|line| {
+        check.error_patterns.iter().any(|pattern| line.contains(pattern.as_str()))
+            || regexes.iter().any(|regex| regex.is_match(line))
+    });
+    if let Some(line) = matched {
+        return Some(line.trim().to_string());
+    }
+
+    // A timeout is how long-running apps are expected to end, not a failure
+    if output.timed_out {
+        return None;
+    }
+    match output.code {
+        Some(code) if check.failure_exit_codes.contains(&code) => {
+            Some(format!("exited with status {}", code))
+        }
+        Some(code) if !check.expected_exit_codes.is_empty() && !check.expected_exit_codes.contains(&code) => {
+            Some(format!("exited with status {}", code))
+        }
+        None if !check.expected_exit_codes.is_empty() => Some("killed by a signal".to_string()),
+        _ => None,
+    }
+}
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
+/// Run the check command with its env, cwd and timeout, capturing both outputs
+fn run_check(check: &RebuildCheck) -> std::io::Result<CheckOutput> {
+    let mut command = Command::new(&check.command[0]);
+    command.args(&check.command[1..]).envs(&check.env);
+    if let Some(cwd) = &check.cwd {
+        command.current_dir(cwd);
+    }
 
-    for pattern in &check.error_patterns {
-        if stderr.contains(pattern) {
-            return true;
+    let timeout = Duration::from_secs(check.timeout.unwrap_or(DEFAULT_TIMEOUT));
+    let output = run_with_timeout(&mut command, timeout)?;
+    Ok(CheckOutput {
+        stdout: output.stdout,
+        stderr: output.stderr,
+        code: output.status.code(),
+        timed_out: output.timed_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(toml_extra: &str) -> RebuildCheck {
+        let base = r#"
+name = "app"
+command = ["app"]
+rebuild = "yay -S --rebuild app"
+"#;
+        toml::from_str(&format!("{}{}", base, toml_extra)).unwrap()
+    }
+
+    fn output(stdout: &str, stderr: &str, code: Option<i32>) -> CheckOutput {
+        CheckOutput {
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            code,
+            timed_out: false,
         }
     }
 
-    false
+    #[test]
+    fn test_evaluate_patterns() {
+        let stderr = "starting\n  app: symbol lookup error: undefined symbol: _ZN3foo  \n";
+
+        let substring = check(r#"error_patterns = ["symbol lookup error"]"#);
+        assert_eq!(
+            evaluate(&substring, &output("", stderr, Some(127))),
+            Some("app: symbol lookup error: undefined symbol: _ZN3foo".to_string())
+        );
+        // Only stderr is matched by default
+        assert_eq!(evaluate(&substring, &output(stderr, "", Some(0))), None);
+
+        let regex = check(
+            r#"regex_patterns = ['undefined symbol: _ZN\w+']
+match_on = "both""#,
+        );
+        assert!(evaluate(&regex, &output(stderr, "", Some(0))).is_some());
+
+        let invalid = check(r#"regex_patterns = ["(unclosed"]"#);
+        assert!(evaluate(&invalid, &output("", "", Some(0))).unwrap().starts_with("invalid regex_patterns"));
+    }
+
+    #[test]
+    fn test_evaluate_exit_codes() {
+        let expected = check("expected_exit_codes = [0]");
+        assert_eq!(evaluate(&expected, &output("", "", Some(0))), None);
+        assert_eq!(
+            evaluate(&expected, &output("", "", Some(134))),
+            Some("exited with status 134".to_string())
+        );
+        assert_eq!(evaluate(&expected, &output("", "", None)), Some("killed by a signal".to_string()));
+
+        let timed_out = CheckOutput {
+            timed_out: true,
+            ..output("", "", None)
+        };
+        assert_eq!(evaluate(&expected, &timed_out), None);
+
+        let failure = check("failure_exit_codes = [2]");
+        assert!(evaluate(&failure, &output("", "", Some(2))).is_some());
+        assert_eq!(evaluate(&failure, &output("", "", Some(1))), None);
+    }
+
+    #[test]
+    fn test_run_check_timeout_and_env() {
+        let mut sleepy = check(
+            r#"timeout = 1
+error_patterns = ["broken"]
+match_on = "stdout""#,
+        );
+        sleepy.command = vec!["sh".into(), "-c".into(), "echo $STATE; sleep 30".into()];
+        sleepy.env.insert("STATE".into(), "broken".into());
+
+        let started = Instant::now();
+        let output = run_check(&sleepy).unwrap();
+        assert!(output.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(evaluate(&sleepy, &output), Some("broken".to_string()));
+
+        let mut missing = check("missing_is_failure = true");
+        missing.command = vec!["upkeep-no-such-command".into()];
+        assert_eq!(
+            find_rebuild_issue(&missing),
+            (CheckStatus::Failed, Some("upkeep-no-such-command not found".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run_checks_in_parallel() {
+        let checks: Vec<RebuildCheck> = (0..4)
+            .map(|i| {
+                let mut c = check(r#"error_patterns = ["broken"]"#);
+                c.name = format!("check-{}", i);
+                let script = if i == 2 { "sleep 0.3; echo broken >&2" } else { "sleep 0.3" };
+                c.command = vec!["sh".into(), "-c".into(), script.into()];
+                c
+            })
+            .collect();
+
+        let events = Mutex::new(Vec::new());
+        let started = Instant::now();
+        run_checks(&checks, |event| events.lock().unwrap().push(event));
+        assert!(started.elapsed() < Duration::from_millis(1100));
+
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.iter().filter(|e| matches!(e, CheckEvent::Started(_))).count(), 4);
+        let failed: Vec<usize> = events
+            .iter()
+            .filter_map(|e| match e {
+                CheckEvent::Finished(index, result) if result.status == CheckStatus::Failed => Some(*index),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failed, vec![2]);
+    }
 }
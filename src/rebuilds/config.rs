@@ -1,16 +1,91 @@
 use crate::config::config_dir;
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Deserialize)]
+/// Seconds a check command may run before it is killed
+pub const DEFAULT_TIMEOUT: u64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RebuildCheck {
     pub name: String,
     pub command: Vec<String>,
+    /// Substrings that indicate a rebuild is needed
+    #[serde(default)]
     pub error_patterns: Vec<String>,
+    /// Regular expressions matched against each output line
+    #[serde(default)]
+    pub regex_patterns: Vec<String>,
+    /// Which output the patterns are matched against
+    #[serde(default)]
+    pub match_on: MatchOn,
+    /// Exit codes of a healthy run; any other exit status fires the check
+    #[serde(default)]
+    pub expected_exit_codes: Vec<i32>,
+    /// Exit codes that fire the check
+    #[serde(default)]
+    pub failure_exit_codes: Vec<i32>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Seconds before the command is killed (default 10); output so far is still matched
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Fire the check when the command cannot be found instead of skipping it
+    #[serde(default)]
+    pub missing_is_failure: bool,
+    /// Packages whose updates can break the app; once the check passes it is only rerun
+    /// after one of them changes version
+    #[serde(default)]
+    pub triggers: Vec<String>,
     pub rebuild: String,
 }
 
+impl RebuildCheck {
+    /// Key of the trigger versions it passed with in state.json: the name plus a hash of the
+    /// whole definition, so an edited check runs again
+    /// The hash is 64-bit FNV-1a over the check as JSON, which stays the same across builds
+    pub fn state_key(&self) -> String {
+        let definition = serde_json::to_vec(self).unwrap_or_default();
+        let hash = definition.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{}#{:016x}", self.name, hash)
+    }
+
+    /// Installed versions of this check's trigger packages
+    pub fn trigger_versions(&self, installed: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        self.triggers
+            .iter()
+            .filter_map(|name| installed.get(name).map(|version| (name.clone(), version.clone())))
+            .collect()
+    }
+
+    /// Whether the check has to run, given the trigger versions it last passed with
+    /// Checks without triggers always run
+    pub fn is_due(
+        &self,
+        passed_with: Option<&BTreeMap<String, String>>,
+        installed: &BTreeMap<String, String>,
+    ) -> bool {
+        if self.triggers.is_empty() {
+            return true;
+        }
+        passed_with != Some(&self.trigger_versions(installed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchOn {
+    #[default]
+    Stderr,
+    Stdout,
+    Both,
+}
+
 #[derive(Debug, Deserialize)]
 struct ChecksConfig {
     #[serde(default)]
@@ -40,25 +115,41 @@ fn create_default_checks() -> Result<()> {
     let content = r#"# Upkeep rebuild checks configuration
 #
 # Each [[check]] block defines an application to monitor for version mismatch issues.
-# When the command outputs any of the error_patterns to stderr, the app needs rebuilding.
+# When the command's output matches a pattern, or it exits with an unexpected status,
+# the app needs rebuilding.
 #
 # Fields:
-#   name           - Display name for the check
-#   command        - Command to run (as array of arguments)
-#   error_patterns - Strings to look for in stderr that indicate a rebuild is needed
-#   rebuild        - Shell command to run to fix the issue
+#   name                - Display name for the check
+#   command             - Command to run (as array of arguments)
+#   error_patterns      - Strings to look for in the output that indicate a rebuild is needed
+#   regex_patterns      - Regular expressions matched against each output line
+#   match_on            - Output to match: "stderr" (default), "stdout" or "both"
+#   expected_exit_codes - Exit codes of a healthy run; any other status fires the check
+#   failure_exit_codes  - Exit codes that fire the check
+#   env                 - Extra environment variables, e.g. { QT_QPA_PLATFORM = "offscreen" }
+#   cwd                 - Working directory for the command
+#   timeout             - Seconds before the command is killed (default: 10)
+#   missing_is_failure  - Fire the check when the command is not installed (default: false)
+#   triggers            - Packages whose updates can break the app; once the check passes it
+#                         only runs again after one of them changes version (`F` forces a run)
+#   rebuild             - Shell command to run to fix the issue
 
 # Example check (uncomment and modify as needed):
 # [[check]]
 # name = "elephant"
-# command = ["timeout", "3", "elephant"]
+# command = ["elephant"]
+# timeout = 3
 # error_patterns = ["plugin was built with a different version"]
 # rebuild = "yay -S --rebuild $(pacman -Qqm | grep elephant)"
 
 # [[check]]
 # name = "obs-studio"
-# command = ["timeout", "3", "obs", "--help"]
-# error_patterns = ["ABI mismatch", "symbol lookup error"]
+# command = ["obs", "--help"]
+# match_on = "both"
+# regex_patterns = ["symbol lookup error: .*undefined symbol", "ABI mismatch"]
+# expected_exit_codes = [0]
+# env = { QT_QPA_PLATFORM = "offscreen" }
+# triggers = ["qt6-base", "ffmpeg"]
 # rebuild = "yay -S --rebuild obs-studio"
 "#;
 
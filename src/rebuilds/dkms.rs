@@ -0,0 +1,182 @@
+use super::checker::RebuildIssue;
+use crate::updates::installed_kernels;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+/// One line of `dkms status`
+#[derive(Debug, PartialEq)]
+struct DkmsEntry {
+    module: String,
+    version: String,
+    /// None for modules that are only added, not built for any kernel
+    kernel: Option<String>,
+    /// added, built or installed
+    state: String,
+}
+
+/// An installed kernel and whether its headers are there to build modules against
+#[derive(Debug)]
+struct Kernel {
+    package: String,
+    release: String,
+    has_headers: bool,
+}
+
+/// Find DKMS modules that are not installed for every kernel with a modules directory
+pub fn scan_dkms() -> Vec<RebuildIssue> {
+    let Ok(output) = Command::new("dkms").arg("status").output() else {
+        return Vec::new();
+    };
+    let entries = parse_status(&String::from_utf8_lossy(&output.stdout));
+
+    let kernels: Vec<Kernel> = installed_kernels()
+        .into_iter()
+        .map(|(package, release)| Kernel {
+            // Headers packages link the build directory into the modules directory
+            has_headers: Path::new("/usr/lib/modules").join(&release).join("build").exists(),
+            package,
+            release,
+        })
+        .collect();
+
+    unbuilt_modules(&entries, &kernels)
+}
+
+/// Parse `dkms status` in both the current and the pre-3.0 format
+///
+/// nvidia/550.54.14, 6.7.9-arch1-1, x86_64: installed
+/// zfs/2.2.3: added
+/// nvidia, 550.54.14, 6.7.9-arch1-1, x86_64: installed
+fn parse_status(output: &str) -> Vec<DkmsEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (left, state) = line.rsplit_once(": ")?;
+            let state = state.split_whitespace().next()?.trim_end_matches(',').to_string();
+            let mut parts = left.split(", ");
+            let first = parts.next()?;
+            let (module, version) = match first.split_once('/') {
+                Some((module, version)) => (module, version),
+                None => (first, parts.next()?),
+            };
+            Some(DkmsEntry {
+                module: module.to_string(),
+                version: version.to_string(),
+                kernel: parts.next().map(String::from),
+                state,
+            })
+        })
+        .collect()
+}
+
+fn unbuilt_modules(entries: &[DkmsEntry], kernels: &[Kernel]) -> Vec<RebuildIssue> {
+    // (module, version) -> kernels it is installed for
+    let mut modules: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+    for entry in entries {
+        let kernels = modules.entry((&entry.module, &entry.version)).or_default();
+        if let Some(kernel) = entry.kernel.as_deref().filter(|_| entry.state == "installed") {
+            kernels.push(kernel);
+        }
+    }
+
+    modules
+        .into_iter()
+        .filter_map(|((module, version), installed)| {
+            let missing: Vec<&Kernel> = kernels
+                .iter()
+                .filter(|k| !installed.contains(&k.release.as_str()))
+                .collect();
+            if missing.is_empty() {
+                return None;
+            }
+
+            let mut commands = Vec::new();
+            let headers: Vec<String> = missing
+                .iter()
+                .filter(|k| !k.has_headers)
+                .map(|k| format!("{}-headers", k.package))
+                .collect();
+            if !headers.is_empty() {
+                commands.push(format!("sudo pacman -S --needed {}", headers.join(" ")));
+            }
+            for kernel in &missing {
+                commands.push(format!("sudo dkms install {}/{} -k {}", module, version, kernel.release));
+            }
+
+            let kernel_list: Vec<String> = missing
+                .iter()
+                .map(|k| format!("{} ({})", k.release, k.package))
+                .collect();
+            let mut detail = format!("DKMS module not built for {}", kernel_list.join(", "));
+            if !headers.is_empty() {
+                detail.push_str(&format!("; {} not installed", headers.join(", ")));
+            }
+
+            Some(RebuildIssue {
+                name: format!("{}/{}", module, version),
+                rebuild_command: commands.join(" && "),
+                detail,
+                package: None,
+                selected: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = "\
+nvidia/550.54.14, 6.7.9-arch1-1, x86_64: installed
+v4l2loopback/0.12.7, 6.7.9-arch1-1, x86_64: installed (WARNING! Diff between built and installed module!)
+zfs/2.2.3: added
+vboxhost, 7.0.14_OSE, 6.6.21-1-lts, x86_64: built
+";
+        let entries = parse_status(output);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(
+            entries[0],
+            DkmsEntry {
+                module: "nvidia".to_string(),
+                version: "550.54.14".to_string(),
+                kernel: Some("6.7.9-arch1-1".to_string()),
+                state: "installed".to_string(),
+            }
+        );
+        assert_eq!(entries[1].state, "installed");
+        assert_eq!(entries[2].kernel, None);
+        assert_eq!(entries[3].module, "vboxhost");
+        assert_eq!(entries[3].version, "7.0.14_OSE");
+        assert_eq!(entries[3].kernel.as_deref(), Some("6.6.21-1-lts"));
+    }
+
+    #[test]
+    fn test_unbuilt_modules() {
+        let entries = parse_status(
+            "nvidia/550.54.14, 6.8.1-arch1-1, x86_64: installed\n\
+             nvidia/550.54.14, 6.6.21-1-lts, x86_64: built\n\
+             zfs/2.2.3, 6.8.1-arch1-1, x86_64: installed\n\
+             zfs/2.2.3, 6.6.21-1-lts, x86_64: installed\n",
+        );
+        let kernels = vec![
+            Kernel { package: "linux".into(), release: "6.8.1-arch1-1".into(), has_headers: true },
+            Kernel { package: "linux-lts".into(), release: "6.6.21-1-lts".into(), has_headers: false },
+        ];
+
+        let issues = unbuilt_modules(&entries, &kernels);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].name, "nvidia/550.54.14");
+        assert_eq!(
+            issues[0].detail,
+            "DKMS module not built for 6.6.21-1-lts (linux-lts); linux-lts-headers not installed"
+        );
+        assert_eq!(
+            issues[0].rebuild_command,
+            "sudo pacman -S --needed linux-lts-headers && sudo dkms install nvidia/550.54.14 -k 6.6.21-1-lts"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::checker::RebuildIssue;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// A family of versioned module directories and the package owning the current one
+struct Interpreter {
+    name: &'static str,
+    /// Package that owns the module directory of the installed version
+    package: &'static str,
+    /// Directory holding the versioned directories
+    parent: &'static str,
+    /// Prefix of the versioned directory names
+    prefix: &'static str,
+}
+
+const INTERPRETERS: &[Interpreter] = &[
+    Interpreter { name: "Python", package: "python", parent: "/usr/lib", prefix: "python3." },
+    Interpreter { name: "Perl", package: "perl", parent: "/usr/lib/perl5", prefix: "5." },
+    Interpreter { name: "Ruby", package: "ruby", parent: "/usr/lib/ruby/gems", prefix: "" },
+    Interpreter { name: "Haskell", package: "ghc-libs", parent: "/usr/lib", prefix: "ghc-" },
+];
+
+/// Find packages with modules installed for an interpreter version that is no longer current
+///
+/// Every versioned module directory (e.g. /usr/lib/python3.11) is checked with `pacman -Qo`:
+/// the current one is owned by the interpreter package itself, any other is left over from
+/// before an upgrade and its owners need rebuilding.
+pub fn scan_interpreters(aur_helper: &str) -> Vec<RebuildIssue> {
+    // package -> stranded directories
+    let mut stranded: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for interpreter in INTERPRETERS {
+        for dir in versioned_dirs(interpreter) {
+            for package in stranded_owners(&owners(&dir), interpreter.package) {
+                stranded.entry(package).or_default().push(format!("{} ({})", dir, interpreter.name));
+            }
+        }
+    }
+
+    stranded
+        .into_iter()
+        .map(|(package, dirs)| RebuildIssue {
+            name: package.clone(),
+            rebuild_command: format!("{} -S --rebuild {}", aur_helper, package),
+            detail: format!("modules in {}", dirs.join(", ")),
+            package: Some(package),
+            selected: false,
+        })
+        .collect()
+}
+
+fn versioned_dirs(interpreter: &Interpreter) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(interpreter.parent) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| {
+            name.strip_prefix(interpreter.prefix)
+                .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map(|name| format!("{}/{}", interpreter.parent, name))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Packages owning a directory (`pacman -Qqo`)
+fn owners(dir: &str) -> Vec<String> {
+    let Ok(output) = Command::new("pacman").args(["-Qqo", dir]).output() else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Owners of a versioned directory that need rebuilding - none if the interpreter owns it
+fn stranded_owners(owners: &[String], interpreter_package: &str) -> Vec<String> {
+    if owners.iter().any(|owner| owner == interpreter_package) {
+        return Vec::new();
+    }
+    owners.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stranded_owners() {
+        let owners = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        // Current directory: shared with the interpreter
+        assert!(stranded_owners(&owners(&["python", "python-requests"]), "python").is_empty());
+
+        // Left over from the previous minor version
+        assert_eq!(
+            stranded_owners(&owners(&["python-foo-git", "python-bar"]), "python"),
+            owners(&["python-foo-git", "python-bar"])
+        );
+    }
+}
@@ -1,5 +1,13 @@
 mod checker;
 mod config;
+mod dkms;
+mod interpreters;
+mod scanner;
+mod templates;
 
-pub use checker::{check_rebuilds, RebuildIssue};
+pub use checker::{
+    find_rebuild_issues, merge_scanned, run_checks, scan_packages, CheckEvent, CheckResult,
+    CheckStatus, RebuildIssue,
+};
 pub use config::{load_checks, RebuildCheck};
+pub use templates::{add_templates, detect_packages, suggest_templates, CheckTemplate};
@@ -0,0 +1,493 @@
+use super::checker::RebuildIssue;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Program headers, dynamic section and string table are read up to this size; real
+/// binaries are far smaller
+const MAX_TABLE_SIZE: u64 = 1 << 20;
+
+/// Shared libraries a foreign package's binaries link against, cached per package version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScannedPackage {
+    version: String,
+    binaries: Vec<Binary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Binary {
+    path: String,
+    /// 32-bit binaries resolve against /usr/lib32
+    class32: bool,
+    needed: Vec<String>,
+    /// DT_RUNPATH/DT_RPATH entries, $ORIGIN still unexpanded
+    runpath: Vec<String>,
+}
+
+/// Find foreign (AUR) packages with binaries linking to libraries that no longer exist
+///
+/// Reading ELF headers is the slow part, so the linked sonames are cached per package
+/// version and only packages that changed since the last scan are read again.
+/// Resolution against the installed libraries always runs, since that is what changes.
+pub fn scan_libraries(aur_helper: &str) -> Vec<RebuildIssue> {
+    let foreign = foreign_packages();
+    if foreign.is_empty() {
+        return Vec::new();
+    }
+
+    let mut cache = load_cache();
+    cache.retain(|name, scanned| foreign.get(name) == Some(&scanned.version));
+
+    let stale: Vec<&String> = foreign.keys().filter(|name| !cache.contains_key(*name)).collect();
+    if !stale.is_empty() {
+        let mut files = package_files(&stale);
+        for name in stale {
+            let binaries = files
+                .remove(name)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|path| read_binary(path))
+                .collect();
+            let version = foreign[name].clone();
+            cache.insert(name.clone(), ScannedPackage { version, binaries });
+        }
+        save_cache(&cache);
+    }
+
+    let mut resolver = Resolver::new();
+    cache
+        .iter()
+        .filter_map(|(name, scanned)| {
+            let missing = resolver.missing(&scanned.binaries);
+            if missing.is_empty() {
+                return None;
+            }
+            Some(RebuildIssue {
+                name: name.clone(),
+                rebuild_command: format!("{} -S --rebuild {}", aur_helper, name),
+                detail: format!("missing {}", missing.into_iter().collect::<Vec<_>>().join(", ")),
+                package: Some(name.clone()),
+                selected: false,
+            })
+        })
+        .collect()
+}
+
+/// Foreign packages and their versions (`pacman -Qm`)
+fn foreign_packages() -> BTreeMap<String, String> {
+    let Ok(output) = Command::new("pacman").arg("-Qm").output() else {
+        return BTreeMap::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (name, version) = line.split_once(' ')?;
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// Files owned by each package (`pacman -Ql`), directories excluded
+fn package_files(names: &[&String]) -> HashMap<String, Vec<String>> {
+    let mut files: HashMap<String, Vec<String>> = HashMap::new();
+    let Ok(output) = Command::new("pacman").arg("-Ql").args(names).output() else {
+        return files;
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some((name, path)) = line.split_once(' ') {
+            if !path.ends_with('/') && !path.starts_with("/usr/lib/debug/") {
+                files.entry(name.to_string()).or_default().push(path.to_string());
+            }
+        }
+    }
+    files
+}
+
+/// Read the dynamic linking info of an ELF executable or shared library
+fn read_binary(path: &str) -> Option<Binary> {
+    let file = File::open(path).ok()?;
+    let read_at = |offset: u64, len: usize| {
+        let mut buf = vec![0; len];
+        file.read_exact_at(&mut buf, offset).ok().map(|_| buf)
+    };
+    let info = parse_elf(&read_at)?;
+    Some(Binary {
+        path: path.to_string(),
+        class32: info.class32,
+        needed: info.needed,
+        runpath: info.runpath,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ElfInfo {
+    class32: bool,
+    needed: Vec<String>,
+    runpath: Vec<String>,
+}
+
+/// Minimal ELF reader: follows PT_DYNAMIC to the DT_NEEDED and DT_RUNPATH strings
+/// Returns None for non-ELF files and for objects that are not executables or libraries
+fn parse_elf(read_at: &dyn Fn(u64, usize) -> Option<Vec<u8>>) -> Option<ElfInfo> {
+    const PT_LOAD: u32 = 1;
+    const PT_DYNAMIC: u32 = 2;
+    const DT_NULL: u64 = 0;
+    const DT_NEEDED: u64 = 1;
+    const DT_STRTAB: u64 = 5;
+    const DT_STRSZ: u64 = 10;
+    const DT_RPATH: u64 = 15;
+    const DT_RUNPATH: u64 = 29;
+
+    let header = read_at(0, 64).or_else(|| read_at(0, 52))?;
+    if header.get(..4)? != b"\x7fELF" {
+        return None;
+    }
+    let class32 = match header.get(4)? {
+        1 => true,
+        2 => false,
+        _ => return None,
+    };
+    // A truncated file can have the magic without the rest of the header
+    if header.len() < if class32 { 52 } else { 64 } {
+        return None;
+    }
+    let elf = Elf {
+        big_endian: header[5] == 2,
+    };
+
+    // ET_EXEC or ET_DYN; relocatable objects and core dumps are not linked
+    if !matches!(elf.u16(&header, 16)?, 2 | 3) {
+        return None;
+    }
+
+    let (phoff, phentsize, phnum) = if class32 {
+        let phoff = elf.u32(&header, 28)? as u64;
+        (phoff, elf.u16(&header, 42)? as usize, elf.u16(&header, 44)? as usize)
+    } else {
+        (elf.u64(&header, 32)?, elf.u16(&header, 54)? as usize, elf.u16(&header, 56)? as usize)
+    };
+    if phentsize < if class32 { 32 } else { 56 } {
+        return None;
+    }
+    let headers_size = phentsize.checked_mul(phnum)?;
+    if headers_size as u64 > MAX_TABLE_SIZE {
+        return None;
+    }
+    let headers = read_at(phoff, headers_size)?;
+
+    // (vaddr, offset, filesz) of loaded segments, to map DT_STRTAB to a file offset
+    let mut loads = Vec::new();
+    let mut dynamic = None;
+    for header in headers.chunks_exact(phentsize) {
+        let p_type = elf.u32(header, 0)?;
+        let (offset, vaddr, filesz) = if class32 {
+            (elf.u32(header, 4)? as u64, elf.u32(header, 8)? as u64, elf.u32(header, 16)? as u64)
+        } else {
+            (elf.u64(header, 8)?, elf.u64(header, 16)?, elf.u64(header, 32)?)
+        };
+        match p_type {
+            PT_LOAD => loads.push((vaddr, offset, filesz)),
+            PT_DYNAMIC => dynamic = Some((offset, filesz)),
+            _ => {}
+        }
+    }
+
+    // Statically linked
+    let Some((dyn_offset, dyn_size)) = dynamic else {
+        return Some(ElfInfo { class32, needed: Vec::new(), runpath: Vec::new() });
+    };
+
+    let entries = read_at(dyn_offset, dyn_size.min(MAX_TABLE_SIZE) as usize)?;
+    let entry_size = if class32 { 8 } else { 16 };
+    let mut needed = Vec::new();
+    let mut runpath = Vec::new();
+    let mut strtab = None;
+    let mut strsz = 0;
+    for entry in entries.chunks_exact(entry_size) {
+        let (tag, value) = if class32 {
+            (elf.u32(entry, 0)? as u64, elf.u32(entry, 4)? as u64)
+        } else {
+            (elf.u64(entry, 0)?, elf.u64(entry, 8)?)
+        };
+        match tag {
+            DT_NULL => break,
+            DT_NEEDED => needed.push(value),
+            DT_RPATH | DT_RUNPATH => runpath.push(value),
+            DT_STRTAB => strtab = Some(value),
+            DT_STRSZ => strsz = value,
+            _ => {}
+        }
+    }
+
+    let strtab = strtab?;
+    let (vaddr, offset, _) = loads
+        .iter()
+        .find(|(vaddr, _, filesz)| strtab >= *vaddr && strtab < vaddr + filesz)?;
+    let strings = read_at(strtab - vaddr + offset, strsz.min(MAX_TABLE_SIZE) as usize)?;
+    let string_at = |index: u64| {
+        let rest = strings.get(index as usize..)?;
+        let end = rest.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+    };
+
+    Some(ElfInfo {
+        class32,
+        needed: needed.into_iter().filter_map(string_at).collect(),
+        runpath: runpath
+            .into_iter()
+            .filter_map(string_at)
+            .flat_map(|paths| paths.split(':').map(String::from).collect::<Vec<_>>())
+            .filter(|path| !path.is_empty())
+            .collect(),
+    })
+}
+
+/// Integer decoding for the file's byte order; None past the end of the buffer
+struct Elf {
+    big_endian: bool,
+}
+
+impl Elf {
+    fn u16(&self, buf: &[u8], at: usize) -> Option<u16> {
+        let bytes: [u8; 2] = buf.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, buf: &[u8], at: usize) -> Option<u32> {
+        let bytes: [u8; 4] = buf.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn u64(&self, buf: &[u8], at: usize) -> Option<u64> {
+        let bytes: [u8; 8] = buf.get(at..at + 8)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// Looks up sonames the way ld.so would, remembering system directory hits
+struct Resolver {
+    dirs64: Vec<PathBuf>,
+    dirs32: Vec<PathBuf>,
+    found: HashMap<(String, bool), bool>,
+}
+
+impl Resolver {
+    fn new() -> Self {
+        let extra = ld_so_conf_dirs();
+        let with = |base: &str| {
+            let mut dirs = vec![PathBuf::from(base)];
+            dirs.extend(extra.iter().cloned());
+            dirs
+        };
+        Self {
+            dirs64: with("/usr/lib"),
+            dirs32: with("/usr/lib32"),
+            found: HashMap::new(),
+        }
+    }
+
+    /// Sonames needed by the binaries that resolve nowhere
+    /// Libraries shipped by the package itself count as found wherever they are installed,
+    /// since many /opt applications set LD_LIBRARY_PATH from a wrapper script
+    fn missing(&mut self, binaries: &[Binary]) -> BTreeSet<String> {
+        let bundled: HashSet<&str> = binaries
+            .iter()
+            .filter_map(|b| b.path.rsplit('/').next())
+            .collect();
+
+        let mut missing = BTreeSet::new();
+        for binary in binaries {
+            let origin = Path::new(&binary.path).parent().unwrap_or(Path::new("/"));
+            for soname in &binary.needed {
+                if bundled.contains(soname.as_str())
+                    || binary.runpath.iter().any(|dir| {
+                        let dir = dir.replace("${ORIGIN}", "$ORIGIN");
+                        let dir = dir.replace("$ORIGIN", &origin.to_string_lossy());
+                        Path::new(&dir).join(soname).exists()
+                    })
+                    || self.in_system_dirs(soname, binary.class32)
+                {
+                    continue;
+                }
+                missing.insert(soname.clone());
+            }
+        }
+        missing
+    }
+
+    fn in_system_dirs(&mut self, soname: &str, class32: bool) -> bool {
+        let dirs = if class32 { &self.dirs32 } else { &self.dirs64 };
+        *self
+            .found
+            .entry((soname.to_string(), class32))
+            .or_insert_with(|| dirs.iter().any(|dir| dir.join(soname).exists()))
+    }
+}
+
+/// Extra library directories from /etc/ld.so.conf.d
+fn ld_so_conf_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/etc/ld.so.conf.d") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('/'))
+                .map(PathBuf::from)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("upkeep")
+        .join("libraries.json")
+}
+
+fn load_cache() -> BTreeMap<String, ScannedPackage> {
+    std::fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Best effort - a missing cache only makes the next scan slower
+fn save_cache(cache: &BTreeMap<String, ScannedPackage>) {
+    let path = cache_path();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(content) = serde_json::to_string(cache) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little-endian 64-bit shared object with one PT_LOAD and a PT_DYNAMIC segment
+    fn elf64(needed: &[&str], runpath: Option<&str>) -> Vec<u8> {
+        let mut strtab = vec![0u8];
+        let mut add = |s: &str| {
+            let index = strtab.len() as u64;
+            strtab.extend_from_slice(s.as_bytes());
+            strtab.push(0);
+            index
+        };
+        let mut dynamic: Vec<(u64, u64)> = needed.iter().map(|s| (1, add(s))).collect();
+        if let Some(path) = runpath {
+            dynamic.push((29, add(path)));
+        }
+
+        let strtab_offset = 64 + 2 * 56;
+        let dyn_offset = strtab_offset + strtab.len() as u64;
+        dynamic.push((5, strtab_offset));
+        dynamic.push((10, strtab.len() as u64));
+        dynamic.push((0, 0));
+        let dyn_size = dynamic.len() as u64 * 16;
+
+        let mut out = vec![0u8; 64];
+        out[..4].copy_from_slice(b"\x7fELF");
+        out[4] = 2; // 64-bit
+        out[5] = 1; // little endian
+        out[16..18].copy_from_slice(&3u16.to_le_bytes()); // ET_DYN
+        out[32..40].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        out[54..56].copy_from_slice(&56u16.to_le_bytes()); // e_phentsize
+        out[56..58].copy_from_slice(&2u16.to_le_bytes()); // e_phnum
+
+        // PT_LOAD mapping the whole file at vaddr 0, then PT_DYNAMIC
+        for (p_type, offset, size) in [(1u32, 0, dyn_offset + dyn_size), (2, dyn_offset, dyn_size)] {
+            let mut header = vec![0u8; 56];
+            header[..4].copy_from_slice(&p_type.to_le_bytes());
+            header[8..16].copy_from_slice(&offset.to_le_bytes());
+            header[16..24].copy_from_slice(&offset.to_le_bytes());
+            header[32..40].copy_from_slice(&size.to_le_bytes());
+            out.extend(header);
+        }
+        out.extend(strtab);
+        for (tag, value) in dynamic {
+            out.extend(tag.to_le_bytes());
+            out.extend(value.to_le_bytes());
+        }
+        out
+    }
+
+    fn parse(data: &[u8]) -> Option<ElfInfo> {
+        parse_elf(&|offset, len| data.get(offset as usize..offset as usize + len).map(<[u8]>::to_vec))
+    }
+
+    #[test]
+    fn test_parse_elf() {
+        let data = elf64(&["libfoo.so.1", "libc.so.6"], Some("$ORIGIN/../lib:/opt/foo"));
+        assert_eq!(
+            parse(&data),
+            Some(ElfInfo {
+                class32: false,
+                needed: vec!["libfoo.so.1".to_string(), "libc.so.6".to_string()],
+                runpath: vec!["$ORIGIN/../lib".to_string(), "/opt/foo".to_string()],
+            })
+        );
+        assert_eq!(parse(b"#!/bin/sh\necho not an elf file\n"), None);
+    }
+
+    #[test]
+    fn test_parse_truncated_elf() {
+        // 64-bit magic with only a 32-bit header's worth of bytes
+        let data = elf64(&["libc.so.6"], None);
+        for len in [5, 52, 60, 63] {
+            assert_eq!(parse(&data[..len]), None, "{} bytes", len);
+        }
+
+        // Program headers claiming gigabytes
+        let mut data = data;
+        data[54..56].copy_from_slice(&u16::MAX.to_le_bytes()); // e_phentsize
+        data[56..58].copy_from_slice(&u16::MAX.to_le_bytes()); // e_phnum
+        assert_eq!(parse(&data), None);
+    }
+
+    #[test]
+    fn test_missing_libraries() {
+        let mut resolver = Resolver {
+            dirs64: Vec::new(),
+            dirs32: Vec::new(),
+            found: HashMap::new(),
+        };
+        let binary = |path: &str, needed: &[&str]| Binary {
+            path: path.to_string(),
+            class32: false,
+            needed: needed.iter().map(|s| s.to_string()).collect(),
+            runpath: Vec::new(),
+        };
+        let binaries = vec![
+            binary("/opt/app/app", &["libbundled.so", "libgone.so.3"]),
+            binary("/opt/app/lib/libbundled.so", &[]),
+        ];
+        let missing: Vec<String> = resolver.missing(&binaries).into_iter().collect();
+        assert_eq!(missing, vec!["libgone.so.3".to_string()]);
+    }
+}
@@ -0,0 +1,217 @@
+use super::config::{checks_path, load_checks, RebuildCheck};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// A ready-made `[[check]]` for software that commonly breaks after system updates
+#[derive(Debug)]
+pub struct CheckTemplate {
+    /// Same as the `name` of the check it adds
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Suggested when any of these packages is installed
+    pub detect: &'static [&'static str],
+    /// The `[[check]]` block; `{aur_helper}` is replaced with the configured helper
+    pub check: &'static str,
+}
+
+pub const TEMPLATES: &[CheckTemplate] = &[
+    CheckTemplate {
+        name: "obs-plugins",
+        description: "AUR OBS Studio plugins built against an older libobs",
+        detect: &["obs-studio", "obs-studio-git"],
+        check: r#"[[check]]
+name = "obs-plugins"
+command = ["sh", "-c", '''
+for f in $(pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/lib/obs-plugins/.*\.so$'); do
+  ldd -r "$f" 2>&1 | sed "s|^|$f: |"
+done''']
+match_on = "both"
+error_patterns = ["undefined symbol", "not found"]
+timeout = 30
+triggers = ["obs-studio", "obs-studio-git"]
+rebuild = "{aur_helper} -S --rebuild $(pacman -Qqm | grep -Fx \"$(pacman -Qqo /usr/lib/obs-plugins)\")"
+"#,
+    },
+    CheckTemplate {
+        name: "qt6-plugins",
+        description: "AUR Qt 6 plugins (styles, platform themes) using Qt private API",
+        detect: &["qt6-base"],
+        check: r#"[[check]]
+name = "qt6-plugins"
+command = ["sh", "-c", '''
+pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/lib/qt6/plugins/.*\.so$' | xargs -r ldd -r 2>&1''']
+match_on = "both"
+regex_patterns = ['undefined symbol: \S*Private', 'Qt_[0-9.]+_PRIVATE_API', '=> not found']
+timeout = 30
+triggers = ["qt6-base"]
+rebuild = "{aur_helper} -S --rebuild $(pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/lib/qt6/plugins/.*\\.so$' | xargs -r pacman -Qqo | sort -u)"
+"#,
+    },
+    CheckTemplate {
+        name: "qt5-plugins",
+        description: "AUR Qt 5 plugins (styles, platform themes) using Qt private API",
+        detect: &["qt5-base"],
+        check: r#"[[check]]
+name = "qt5-plugins"
+command = ["sh", "-c", '''
+pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/lib/qt/plugins/.*\.so$' | xargs -r ldd -r 2>&1''']
+match_on = "both"
+regex_patterns = ['undefined symbol: \S*Private', 'Qt_5_PRIVATE_API', '=> not found']
+timeout = 30
+triggers = ["qt5-base"]
+rebuild = "{aur_helper} -S --rebuild $(pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/lib/qt/plugins/.*\\.so$' | xargs -r pacman -Qqo | sort -u)"
+"#,
+    },
+    CheckTemplate {
+        name: "dkms",
+        description: "DKMS modules not built for the installed kernels",
+        detect: &["dkms"],
+        check: r#"[[check]]
+name = "dkms"
+command = ["dkms", "status"]
+match_on = "both"
+regex_patterns = [": added$", "(?i)diff between built and installed module"]
+triggers = ["dkms", "linux", "linux-lts", "linux-zen", "linux-hardened"]
+rebuild = "sudo dkms autoinstall"
+"#,
+    },
+    CheckTemplate {
+        name: "virtualbox-extpack",
+        description: "VirtualBox Extension Pack that no longer matches VirtualBox",
+        detect: &["virtualbox-ext-oracle"],
+        check: r#"[[check]]
+name = "virtualbox-extpack"
+command = ["VBoxManage", "list", "extpacks"]
+match_on = "stdout"
+regex_patterns = ['Usable:\s+false']
+triggers = ["virtualbox", "virtualbox-ext-oracle"]
+rebuild = "{aur_helper} -S --rebuild virtualbox-ext-oracle"
+"#,
+    },
+    CheckTemplate {
+        name: "gnome-shell-extensions",
+        description: "GNOME Shell extensions disabled as out of date after a GNOME update",
+        detect: &["gnome-shell"],
+        check: r#"[[check]]
+name = "gnome-shell-extensions"
+command = ["gnome-extensions", "list", "--details"]
+match_on = "stdout"
+regex_patterns = ['State: (OUT OF DATE|ERROR)']
+triggers = ["gnome-shell"]
+rebuild = "{aur_helper} -S --rebuild $(pacman -Qqm | grep '^gnome-shell-extension-')"
+"#,
+    },
+    CheckTemplate {
+        name: "electron-apps",
+        description: "AUR Electron apps whose launcher points at an Electron that is gone",
+        detect: &[
+            "electron", "electron28", "electron29", "electron30", "electron31", "electron32",
+            "electron33", "electron34",
+        ],
+        check: r#"[[check]]
+name = "electron-apps"
+command = ["sh", "-c", '''
+for f in $(pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/bin/.'); do
+  e=$(grep -Isoh -m1 '/usr/lib/electron[0-9]*/electron\|/usr/bin/electron[0-9]*' "$f" | head -n1)
+  if [ -n "$e" ] && [ ! -e "$e" ]; then echo "$(pacman -Qqo "$f") needs missing $e"; fi
+done''']
+match_on = "stdout"
+error_patterns = ["needs missing"]
+timeout = 30
+rebuild = '''{aur_helper} -S --rebuild $(for f in $(pacman -Qqm | xargs -r pacman -Qlq | grep '^/usr/bin/.'); do e=$(grep -Isoh -m1 '/usr/lib/electron[0-9]*/electron\|/usr/bin/electron[0-9]*' "$f" | head -n1); if [ -n "$e" ] && [ ! -e "$e" ]; then pacman -Qqo "$f"; fi; done | sort -u)'''
+"#,
+    },
+];
+
+/// Every package some template is detected by
+pub fn detect_packages() -> Vec<String> {
+    let mut names: Vec<String> = TEMPLATES
+        .iter()
+        .flat_map(|t| t.detect.iter().map(|name| name.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Templates for installed software that checks.toml does not cover yet
+pub fn suggest_templates(
+    installed: &BTreeMap<String, String>,
+    existing: &[RebuildCheck],
+) -> Vec<&'static CheckTemplate> {
+    TEMPLATES
+        .iter()
+        .filter(|t| t.detect.iter().any(|name| installed.contains_key(*name)))
+        .filter(|t| !existing.iter().any(|c| c.name == t.name))
+        .collect()
+}
+
+fn render(template: &CheckTemplate, aur_helper: &str) -> String {
+    template.check.replace("{aur_helper}", aur_helper)
+}
+
+/// Append the named templates to checks.toml, returning how many were added
+pub fn add_templates(names: &[String], aur_helper: &str) -> Result<usize> {
+    // Creates the default file on first use
+    load_checks()?;
+
+    let path = checks_path();
+    let mut content = std::fs::read_to_string(&path)?;
+    let mut added = 0;
+    for name in names {
+        let Some(template) = TEMPLATES.iter().find(|t| t.name == name) else {
+            bail!("unknown check template: {}", name);
+        };
+        if !content.ends_with("\n\n") {
+            content.push('\n');
+        }
+        content.push_str(&format!("# {}\n", template.description));
+        content.push_str(&render(template, aur_helper));
+        added += 1;
+    }
+
+    std::fs::write(&path, content)?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Checks {
+        check: Vec<RebuildCheck>,
+    }
+
+    #[test]
+    fn test_templates_parse() {
+        for template in TEMPLATES {
+            let checks: Checks = toml::from_str(&render(template, "yay"))
+                .unwrap_or_else(|e| panic!("{} does not parse: {}", template.name, e));
+            assert_eq!(checks.check.len(), 1);
+            let check = &checks.check[0];
+            assert_eq!(check.name, template.name);
+            assert!(!check.rebuild.contains("{aur_helper}"));
+            for pattern in &check.regex_patterns {
+                assert!(regex::Regex::new(pattern).is_ok(), "{}: {}", template.name, pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_suggest_templates() {
+        let installed: BTreeMap<String, String> = [("qt6-base", "6.7.2-1"), ("dkms", "3.0.12-1")]
+            .into_iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect();
+        let names = |suggested: Vec<&CheckTemplate>| suggested.iter().map(|t| t.name).collect::<Vec<_>>();
+
+        assert_eq!(names(suggest_templates(&installed, &[])), vec!["qt6-plugins", "dkms"]);
+
+        // Already in checks.toml
+        let existing: RebuildCheck =
+            toml::from_str("name = 'dkms'\ncommand = ['dkms', 'status']\nrebuild = 'true'").unwrap();
+        assert_eq!(names(suggest_templates(&installed, &[existing])), vec!["qt6-plugins"]);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::updates::{load_history_since, pacman_log_size};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Tool that takes and deletes the pre-transaction snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotProvider {
+    Snapper,
+    Timeshift,
+    /// Read-only snapshot of a btrfs subvolume, without any snapshot manager
+    Btrfs,
+    /// User-defined shell commands
+    Command,
+}
+
+/// `[snapshot]` table of config.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    pub provider: SnapshotProvider,
+    /// snapper: configuration to snapshot
+    #[serde(default = "default_snapper_config")]
+    pub snapper_config: String,
+    /// btrfs: subvolume to snapshot
+    #[serde(default = "default_subvolume")]
+    pub subvolume: String,
+    /// btrfs: directory the snapshots are created in
+    #[serde(default = "default_directory")]
+    pub directory: String,
+    /// command: shell command taking a snapshot; `{label}` is replaced with the quoted label
+    /// and the last line it prints is taken as the snapshot ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// command: shell command deleting a snapshot; `{id}` is replaced with the quoted ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_command: Option<String>,
+    /// Snapshots kept when pruning, newest first
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+impl SnapshotProvider {
+    pub fn label(self) -> &'static str {
+        match self {
+            SnapshotProvider::Snapper => "snapper",
+            SnapshotProvider::Timeshift => "timeshift",
+            SnapshotProvider::Btrfs => "btrfs",
+            SnapshotProvider::Command => "command",
+        }
+    }
+}
+
+fn default_snapper_config() -> String {
+    "root".to_string()
+}
+
+fn default_subvolume() -> String {
+    "/".to_string()
+}
+
+fn default_directory() -> String {
+    "/.snapshots".to_string()
+}
+
+fn default_keep() -> usize {
+    5
+}
+
+/// A snapshot upkeep took before running a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// snapper number, timeshift name, btrfs path or whatever the custom command printed
+    pub id: String,
+    pub provider: SnapshotProvider,
+    /// Unix time it was taken
+    pub created: i64,
+    /// Title of the transaction it was taken before, e.g. "Update All Packages"
+    pub transaction: String,
+    /// Outcome of that transaction; None if upkeep exited before it finished
+    #[serde(default)]
+    pub succeeded: Option<bool>,
+    /// Package changes the transaction made, e.g. "upgraded linux 6.7.1-1 -> 6.7.2-1"
+    #[serde(default)]
+    pub changes: Vec<String>,
+    /// Size of pacman.log when the snapshot was taken; later entries belong to the transaction
+    #[serde(default)]
+    pub log_offset: u64,
+}
+
+impl Snapshot {
+    pub fn new(id: String, provider: SnapshotProvider, transaction: &str) -> Self {
+        Self {
+            id,
+            provider,
+            created: Local::now().timestamp(),
+            transaction: transaction.to_string(),
+            succeeded: None,
+            changes: Vec::new(),
+            log_offset: pacman_log_size(),
+        }
+    }
+
+    /// Record how the transaction went and what it changed
+    pub fn finish(&mut self, succeeded: bool) {
+        self.succeeded = Some(succeeded);
+        self.changes = load_history_since(self.log_offset)
+            .iter()
+            .map(|e| format!("{} {} {}", e.action.label(), e.name, e.version_display()))
+            .collect();
+    }
+}
+
+impl SnapshotConfig {
+    /// Command taking a snapshot labeled `label`
+    pub fn create_command(&self, label: &str, now: DateTime<Local>) -> Result<Vec<String>, String> {
+        let argv = match self.provider {
+            // No cleanup algorithm: upkeep prunes its own snapshots, and snapper deleting
+            // them behind its back would leave IDs that can no longer be deleted
+            SnapshotProvider::Snapper => strings(&[
+                "sudo", "snapper", "-c", &self.snapper_config, "create", "--print-number",
+                "--description", label,
+            ]),
+            SnapshotProvider::Timeshift => {
+                strings(&["sudo", "timeshift", "--create", "--scripted", "--comments", label])
+            }
+            SnapshotProvider::Btrfs => {
+                let path = format!(
+                    "{}/upkeep-{}",
+                    self.directory.trim_end_matches('/'),
+                    now.format("%Y%m%d-%H%M%S")
+                );
+                strings(&["sudo", "btrfs", "subvolume", "snapshot", "-r", &self.subvolume, &path])
+            }
+            SnapshotProvider::Command => {
+                let command = self
+                    .command
+                    .as_ref()
+                    .ok_or("snapshot provider \"command\" needs a `command`")?;
+                strings(&["sh", "-c", &command.replace("{label}", &shell_quote(label))])
+            }
+        };
+        Ok(argv)
+    }
+
+    /// ID of the snapshot taken by `argv`, found in the output it printed
+    pub fn parse_id(&self, argv: &[String], output: &str) -> Option<String> {
+        let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty()).rev();
+        match self.provider {
+            SnapshotProvider::Snapper => lines
+                .find(|line| line.chars().all(|c| c.is_ascii_digit()))
+                .map(String::from),
+            SnapshotProvider::Timeshift => {
+                let tagged = Regex::new(r"Tagged snapshot '([^']+)'").unwrap();
+                tagged.captures(output).map(|c| c[1].to_string())
+            }
+            SnapshotProvider::Btrfs => argv.last().cloned(),
+            SnapshotProvider::Command => lines.next().map(String::from),
+        }
+    }
+
+    /// Command deleting the given snapshots
+    pub fn delete_command(&self, ids: &[String]) -> Result<Vec<String>, String> {
+        let mut argv = match self.provider {
+            SnapshotProvider::Snapper => {
+                strings(&["sudo", "snapper", "-c", &self.snapper_config, "delete"])
+            }
+            SnapshotProvider::Btrfs => strings(&["sudo", "btrfs", "subvolume", "delete"]),
+            // One snapshot per invocation
+            SnapshotProvider::Timeshift => {
+                let commands: Vec<String> = ids
+                    .iter()
+                    .map(|id| {
+                        format!("sudo timeshift --delete --scripted --snapshot {}", shell_quote(id))
+                    })
+                    .collect();
+                return Ok(strings(&["sh", "-c", &commands.join(" && ")]));
+            }
+            SnapshotProvider::Command => {
+                let command = self
+                    .delete_command
+                    .as_ref()
+                    .ok_or("snapshot provider \"command\" needs a `delete_command` to prune")?;
+                let commands: Vec<String> =
+                    ids.iter().map(|id| command.replace("{id}", &shell_quote(id))).collect();
+                return Ok(strings(&["sh", "-c", &commands.join(" && ")]));
+            }
+        };
+        argv.extend(ids.iter().cloned());
+        Ok(argv)
+    }
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// Quote a value for `sh -c`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: SnapshotProvider) -> SnapshotConfig {
+        SnapshotConfig {
+            provider,
+            snapper_config: default_snapper_config(),
+            subvolume: default_subvolume(),
+            directory: default_directory(),
+            command: Some("mksnap {label}".to_string()),
+            delete_command: Some("rmsnap {id}".to_string()),
+            keep: default_keep(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_ids() {
+        let snapper = config(SnapshotProvider::Snapper);
+        let argv = snapper.create_command("upkeep: Update All Packages", Local::now()).unwrap();
+        assert_eq!(argv[..6], ["sudo", "snapper", "-c", "root", "create", "--print-number"]);
+        let output = "[sudo] password for me:\r\n42\r\n";
+        assert_eq!(snapper.parse_id(&argv, output), Some("42".into()));
+
+        let timeshift = config(SnapshotProvider::Timeshift);
+        let output = "Creating new snapshot...\nTagged snapshot '2024-01-31_10-00-01': ondemand\n";
+        assert_eq!(timeshift.parse_id(&[], output), Some("2024-01-31_10-00-01".into()));
+
+        let btrfs = config(SnapshotProvider::Btrfs);
+        let now = DateTime::parse_from_rfc3339("2024-01-31T10:00:01+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        let argv = btrfs.create_command("label", now).unwrap();
+        let id = btrfs.parse_id(&argv, "Create a readonly snapshot").unwrap();
+        assert!(id.starts_with("/.snapshots/upkeep-2024"), "{}", id);
+        let argv = btrfs.delete_command(std::slice::from_ref(&id)).unwrap();
+        assert_eq!(argv, ["sudo", "btrfs", "subvolume", "delete", &id]);
+    }
+
+    #[test]
+    fn test_custom_commands() {
+        let custom = config(SnapshotProvider::Command);
+        let argv = custom.create_command("upkeep: it's", Local::now()).unwrap();
+        assert_eq!(argv, ["sh", "-c", r"mksnap 'upkeep: it'\''s'"]);
+        assert_eq!(custom.parse_id(&argv, "working...\nsnap-7\n\n"), Some("snap-7".into()));
+        assert_eq!(
+            custom.delete_command(&["a".into(), "b".into()]).unwrap(),
+            ["sh", "-c", "rmsnap 'a' && rmsnap 'b'"]
+        );
+
+        let missing = SnapshotConfig {
+            command: None,
+            ..custom
+        };
+        assert!(missing.create_command("label", Local::now()).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::config::config_dir;
+use crate::snapshot::Snapshot;
+use crate::updates::{AurSource, VcsHeads};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Things upkeep remembers between runs (as opposed to user configuration)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Links of news items that have been read
+    #[serde(default)]
+    pub read_news: BTreeSet<String>,
+    /// Links of manual-intervention news acknowledged in the upgrade confirmation
+    #[serde(default)]
+    pub acknowledged_news: BTreeSet<String>,
+    /// Unix time of the last successful full upgrade run from upkeep
+    #[serde(default)]
+    pub last_upgrade: Option<i64>,
+    /// Trigger package versions each rebuild check last passed with, by
+    /// `RebuildCheck::state_key` (its name and a hash of its definition)
+    #[serde(default)]
+    pub check_triggers: BTreeMap<String, BTreeMap<String, String>>,
+    /// Last reviewed PKGBUILD and .SRCINFO of each AUR package, by package name
+    #[serde(default)]
+    pub reviewed_aur: BTreeMap<String, AurSource>,
+    /// Maintainer of each installed AUR package at the last refresh (None = orphaned)
+    #[serde(default)]
+    pub aur_maintainers: BTreeMap<String, Option<String>>,
+    /// AUR packages whose maintainer changed, with the previous one, until they are reviewed
+    #[serde(default)]
+    pub aur_maintainer_changes: BTreeMap<String, Option<String>>,
+    /// Upstream revisions each installed VCS package was built from, by package and source
+    #[serde(default)]
+    pub devel_revisions: BTreeMap<String, VcsHeads>,
+    /// Snapshots taken before transactions, oldest first
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+    /// Where to save; None keeps the state in memory only (used by tests)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl State {
+    /// Load state from disk, starting fresh if it is missing or unreadable
+    pub fn load() -> Self {
+        let path = state_path();
+        let mut state: State = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        state.path = Some(path);
+        state
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn is_news_read(&self, link: &str) -> bool {
+        self.read_news.contains(link)
+    }
+
+    pub fn mark_news_read(&mut self, link: &str) {
+        self.read_news.insert(link.to_string());
+    }
+
+    pub fn is_news_acknowledged(&self, link: &str) -> bool {
+        self.acknowledged_news.contains(link)
+    }
+
+    /// Only the upgrade confirmation acknowledges news; reading it does not
+    pub fn acknowledge_news(&mut self, link: &str) {
+        self.acknowledged_news.insert(link.to_string());
+        self.read_news.insert(link.to_string());
+    }
+}
+
+pub fn state_path() -> PathBuf {
+    config_dir().join("state.json")
+}
@@ -0,0 +1,228 @@
+use crate::action::Action;
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{native_pty_system, Child, CommandBuilder, ExitStatus, MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+/// Lines of output kept above the visible screen for scrolling
+const SCROLLBACK_LINES: usize = 1000;
+
+/// A package manager command running in a pseudo-terminal inside the TUI
+pub struct Transaction {
+    pub title: String,
+    /// Action that started the transaction, used to refresh the right tabs afterwards
+    pub action: Action,
+    /// ID of the snapshot taken before the transaction, if any
+    pub snapshot: Option<String>,
+    parser: Arc<Mutex<vt100::Parser>>,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    exit_status: Option<ExitStatus>,
+    size: (u16, u16),
+    scroll: usize,
+}
+
+impl Transaction {
+    /// Spawn `argv` in a new PTY of the given size (rows, cols)
+    pub fn spawn(title: String, action: Action, argv: &[String], size: (u16, u16)) -> Result<Self> {
+        let program = argv.first().ok_or_else(|| anyhow!("empty command"))?;
+        let (rows, cols) = (size.0.max(1), size.1.max(1));
+
+        let pair = native_pty_system().openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+
+        let mut cmd = CommandBuilder::new(program);
+        cmd.args(&argv[1..]);
+        cmd.env("TERM", "xterm-256color");
+        if let Ok(cwd) = std::env::current_dir() {
+            cmd.cwd(cwd);
+        }
+
+        let child = pair.slave.spawn_command(cmd)?;
+        // Close our copy of the slave so reads hit EOF once the child exits
+        drop(pair.slave);
+
+        let mut reader = pair.master.try_clone_reader()?;
+        let writer = pair.master.take_writer()?;
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(rows, cols, SCROLLBACK_LINES)));
+
+        let output = Arc::clone(&parser);
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if let Ok(mut parser) = output.lock() {
+                            parser.process(&buf[..n]);
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            title,
+            action,
+            snapshot: None,
+            parser,
+            master: pair.master,
+            writer,
+            child,
+            exit_status: None,
+            size: (rows, cols),
+            scroll: 0,
+        })
+    }
+
+    /// Check whether the child has exited. Returns true on the poll where it finishes.
+    pub fn poll(&mut self) -> bool {
+        if self.exit_status.is_some() {
+            return false;
+        }
+        match self.child.try_wait() {
+            Ok(Some(status)) => {
+                self.exit_status = Some(status);
+                true
+            }
+            Ok(None) => false,
+            Err(_) => {
+                self.exit_status = Some(ExitStatus::with_exit_code(1));
+                true
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.exit_status.is_none()
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.exit_status.as_ref().is_some_and(|s| s.success())
+    }
+
+    pub fn exit_code(&self) -> Option<u32> {
+        self.exit_status.as_ref().map(|s| s.exit_code())
+    }
+
+    /// Resize the PTY and terminal emulator to match the pane
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        let (rows, cols) = (rows.max(1), cols.max(1));
+        if (rows, cols) == self.size {
+            return;
+        }
+        self.size = (rows, cols);
+        let _ = self.master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.set_size(rows, cols);
+        }
+    }
+
+    /// Terminal emulator state for rendering
+    pub fn screen(&self) -> MutexGuard<'_, vt100::Parser> {
+        self.parser.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Text currently on the screen, for commands whose output upkeep needs
+    pub fn output(&self) -> String {
+        self.screen().screen().contents()
+    }
+
+    /// Scroll back through output (positive = older lines)
+    pub fn scroll(&mut self, delta: i32) {
+        let scroll = (self.scroll as i64 + delta as i64).clamp(0, SCROLLBACK_LINES as i64);
+        self.scroll = scroll as usize;
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.set_scrollback(self.scroll);
+            // vt100 clamps to the available scrollback
+            self.scroll = parser.screen().scrollback();
+        }
+    }
+
+    /// Forward a key press to the child process
+    pub fn send_key(&mut self, key: KeyEvent) {
+        let application_cursor = self.screen().screen().application_cursor();
+        if let Some(bytes) = encode_key(key, application_cursor) {
+            if self.scroll > 0 {
+                self.scroll(-(self.scroll as i32));
+            }
+            let _ = self.writer.write_all(&bytes);
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+/// Translate a key event into the bytes a terminal would send
+fn encode_key(key: KeyEvent, application_cursor: bool) -> Option<Vec<u8>> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    let cursor = |c: char| -> Vec<u8> {
+        if application_cursor {
+            format!("\x1bO{}", c).into_bytes()
+        } else {
+            format!("\x1b[{}", c).into_bytes()
+        }
+    };
+
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if ctrl => {
+            let lower = c.to_ascii_lowercase();
+            if lower.is_ascii_lowercase() {
+                vec![lower as u8 - b'a' + 1]
+            } else {
+                return None;
+            }
+        }
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => cursor('A'),
+        KeyCode::Down => cursor('B'),
+        KeyCode::Right => cursor('C'),
+        KeyCode::Left => cursor('D'),
+        KeyCode::Home => cursor('H'),
+        KeyCode::End => cursor('F'),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        _ => return None,
+    };
+
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_key() {
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(encode_key(key(KeyCode::Char('y'), KeyModifiers::NONE), false), Some(b"y".to_vec()));
+        assert_eq!(encode_key(key(KeyCode::Enter, KeyModifiers::NONE), false), Some(b"\r".to_vec()));
+        assert_eq!(encode_key(key(KeyCode::Char('c'), KeyModifiers::CONTROL), false), Some(vec![3]));
+        assert_eq!(encode_key(key(KeyCode::Up, KeyModifiers::NONE), false), Some(b"\x1b[A".to_vec()));
+        assert_eq!(encode_key(key(KeyCode::Up, KeyModifiers::NONE), true), Some(b"\x1bOA".to_vec()));
+        assert_eq!(encode_key(key(KeyCode::F(1), KeyModifiers::NONE), false), None);
+    }
+}
@@ -17,11 +17,16 @@ pub fn draw_confirmation(frame: &mut Frame, state: &ConfirmationState, area: Rec
         .unwrap_or(0)
         .max(state.title.len())
         .max(state.message.len())
+        .max(state.news.iter().map(|n| n.title.len() + 16).max().unwrap_or(0))
+        .max(state.news_warning.as_ref().map_or(0, |w| w.len()))
         .max(30);
 
     let dialog_width = (max_item_width as u16 + 8).min(area.width.saturating_sub(4));
     let item_lines = state.items.len().min(15) as u16; // Cap at 15 visible items
-    let dialog_height = (item_lines + 8).min(area.height.saturating_sub(2));
+    // News gate: heading, one line per item, blank line; the feed warning takes two more
+    let news_lines = if state.news.is_empty() { 0 } else { state.news.len() as u16 + 2 };
+    let news_lines = news_lines + if state.news_warning.is_some() { 2 } else { 0 };
+    let dialog_height = (item_lines + news_lines + 8).min(area.height.saturating_sub(2));
 
     let dialog_area = centered_rect(dialog_width, dialog_height, area);
 
@@ -35,7 +40,7 @@ pub fn draw_confirmation(frame: &mut Frame, state: &ConfirmationState, area: Rec
     ];
 
     // Show items (with scroll indicator if too many)
-    let max_visible = (dialog_height.saturating_sub(8)) as usize;
+    let max_visible = (dialog_height.saturating_sub(8 + news_lines)) as usize;
     let items_to_show = if state.items.len() > max_visible {
         &state.items[..max_visible]
     } else {
@@ -53,18 +58,47 @@ pub fn draw_confirmation(frame: &mut Frame, state: &ConfirmationState, area: Rec
         )));
     }
 
+    if !state.news.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Unread news requiring manual intervention:",
+            styles::error(),
+        )));
+        for item in &state.news {
+            lines.push(Line::from(vec![
+                Span::styled("  ! ", styles::news_attention()),
+                Span::styled(format!("{:<13}", item.pub_date), styles::disabled()),
+                Span::raw(item.title.as_str()),
+            ]));
+        }
+    }
+
+    if let Some(warning) = &state.news_warning {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(warning.as_str(), styles::error())));
+    }
+
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         state.message.clone(),
         styles::warning(),
     )));
     lines.push(Line::from(""));
-    lines.push(Line::from(vec![
-        Span::styled("[Enter/y]", styles::help_key()),
-        Span::styled(" Confirm  ", styles::help()),
-        Span::styled("[Esc/n]", styles::help_key()),
-        Span::styled(" Cancel", styles::help()),
-    ]));
+    if state.needs_acknowledgement() {
+        lines.push(Line::from(vec![
+            Span::styled("[a]", styles::help_key()),
+            Span::styled(" Acknowledge news  ", styles::help()),
+            Span::styled("[Esc/n]", styles::help_key()),
+            Span::styled(" Cancel", styles::help()),
+        ]));
+    } else {
+        lines.push(Line::from(vec![
+            Span::styled("[Enter/y]", styles::help_key()),
+            Span::styled(" Confirm  ", styles::help()),
+            Span::styled("[Esc/n]", styles::help_key()),
+            Span::styled(" Cancel", styles::help()),
+        ]));
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -75,7 +109,7 @@ pub fn draw_confirmation(frame: &mut Frame, state: &ConfirmationState, area: Rec
     frame.render_widget(paragraph, dialog_area);
 }
 
-fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+pub(super) fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
     let x = area.x + (area.width.saturating_sub(width)) / 2;
     let y = area.y + (area.height.saturating_sub(height)) / 2;
     Rect::new(x, y, width, height)
@@ -0,0 +1,79 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+};
+
+use crate::app::DowngradeState;
+use crate::updates::VersionSource;
+
+use super::{confirm::centered_rect, styles};
+
+pub fn draw_downgrade(frame: &mut Frame, state: &mut DowngradeState, area: Rect) {
+    let list_height = state.candidates.len().clamp(1, 15) as u16;
+    let dialog_width = 60.min(area.width.saturating_sub(4));
+    let dialog_height = (list_height + 7).min(area.height.saturating_sub(2));
+    let dialog_area = centered_rect(dialog_width, dialog_height, area);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(styles::border_active())
+        .title(format!(" Downgrade {} ", state.name))
+        .title_style(styles::title_active());
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let [header_area, list_area, help_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(2),
+    ])
+    .areas(inner.inner(Margin::new(1, 0)));
+
+    let header = Line::from(vec![
+        Span::styled("Installed: ", styles::help()),
+        Span::raw(state.installed_version.as_str()),
+    ]);
+    frame.render_widget(Paragraph::new(header), header_area);
+
+    if state.loading {
+        let loading = Paragraph::new(Span::styled("Loading versions...", styles::warning()));
+        frame.render_widget(loading, list_area);
+    } else if let Some(error) = &state.error {
+        let error = Paragraph::new(Span::styled(error.as_str(), styles::error()));
+        frame.render_widget(error, list_area);
+    } else if state.candidates.is_empty() {
+        let empty = Paragraph::new(Span::styled("No other versions available", styles::disabled()));
+        frame.render_widget(empty, list_area);
+    } else {
+        let items: Vec<ListItem> = state
+            .candidates
+            .iter()
+            .map(|candidate| {
+                let source_style = match candidate.source {
+                    VersionSource::Cache => styles::status_active(),
+                    VersionSource::Archive => styles::disabled(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:<40}", candidate.version)),
+                    Span::styled(candidate.source.label(), source_style),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(styles::row_highlight())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut state.list_state);
+    }
+
+    let help = Line::from(vec![
+        Span::styled("[Enter]", styles::help_key()),
+        Span::styled(" Downgrade  ", styles::help()),
+        Span::styled("[h]", styles::help_key()),
+        Span::styled(" Downgrade + hold  ", styles::help()),
+        Span::styled("[Esc]", styles::help_key()),
+        Span::styled(" Cancel", styles::help()),
+    ]);
+    frame.render_widget(Paragraph::new(vec![Line::from(""), help]), help_area);
+}
@@ -0,0 +1,138 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+};
+
+use crate::app::ImportState;
+use crate::updates::PackageSource;
+
+use super::{confirm::centered_rect, styles};
+
+pub fn draw_import(frame: &mut Frame, state: &mut ImportState, area: Rect) {
+    let rows = match state.source {
+        Some(_) => state.entries.len(),
+        None => state.backups.len() + 1,
+    };
+    let list_height = rows.clamp(1, 15) as u16;
+    let dialog_width = 70.min(area.width.saturating_sub(4));
+    let dialog_height = (list_height + 7).min(area.height.saturating_sub(2));
+    let dialog_area = centered_rect(dialog_width, dialog_height, area);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(styles::border_active())
+        .title(" Restore Packages ")
+        .title_style(styles::title_active());
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let [header_area, list_area, help_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(2),
+    ])
+    .areas(inner.inner(Margin::new(1, 0)));
+
+    let Some(source) = &state.source else {
+        draw_picker(frame, state, header_area, list_area, help_area);
+        return;
+    };
+
+    let chosen = state.entries.iter().filter(|e| e.selected).count();
+    let header = Span::styled(
+        format!(
+            "From the {}: {} of {} missing package(s) chosen, {} already installed",
+            source,
+            chosen,
+            state.entries.len(),
+            state.installed
+        ),
+        styles::help(),
+    );
+    frame.render_widget(Paragraph::new(header), header_area);
+
+    if state.loading {
+        let loading = Paragraph::new(Span::styled("Looking up packages...", styles::warning()));
+        frame.render_widget(loading, list_area);
+    } else if let Some(error) = &state.error {
+        let error = Paragraph::new(Span::styled(error.as_str(), styles::error()));
+        frame.render_widget(error, list_area);
+    } else if state.entries.is_empty() {
+        let empty = Span::styled("Everything listed is installed", styles::disabled());
+        frame.render_widget(Paragraph::new(empty), list_area);
+    } else {
+        let items: Vec<ListItem> = state
+            .entries
+            .iter()
+            .map(|entry| {
+                let checkbox = if entry.selected { "[x] " } else { "[ ] " };
+                let (source, source_style) = match entry.source {
+                    Some(PackageSource::Pacman) => ("repo", styles::status_active()),
+                    Some(_) => ("AUR", styles::warning()),
+                    None => ("no longer exists", styles::error()),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(checkbox, styles::help_key()),
+                    Span::raw(format!("{:<40}", entry.name)),
+                    Span::styled(source, source_style),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(styles::row_highlight())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut state.list_state);
+    }
+
+    let help = Line::from(vec![
+        Span::styled("[Space]", styles::help_key()),
+        Span::styled(" Select  ", styles::help()),
+        Span::styled("[a/n]", styles::help_key()),
+        Span::styled(" All/None  ", styles::help()),
+        Span::styled("[Enter]", styles::help_key()),
+        Span::styled(" Install  ", styles::help()),
+        Span::styled("[Esc]", styles::help_key()),
+        Span::styled(" Back", styles::help()),
+    ]);
+    frame.render_widget(Paragraph::new(vec![Line::from(""), help]), help_area);
+}
+
+/// Choice of the list to restore: the clipboard, then the exports, newest first
+fn draw_picker(frame: &mut Frame, state: &mut ImportState, header: Rect, list: Rect, help: Rect) {
+    let text = Span::styled(
+        "Restore from a list copied with C, or one exported with c to ~/.config/upkeep/backups",
+        styles::help(),
+    );
+    frame.render_widget(Paragraph::new(text), header);
+
+    let clipboard = ListItem::new(Line::from(vec![
+        Span::raw(format!("{:<16}", "Clipboard")),
+        Span::styled("pasted package list", styles::disabled()),
+    ]));
+    let backups = state.backups.iter().map(|backup| {
+        let files = match (&backup.packages_path, &backup.aur_path) {
+            (Some(_), Some(_)) => "official + AUR",
+            (Some(_), None) => "official",
+            _ => "AUR",
+        };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{:<16}", backup.date)),
+            Span::styled(files, styles::disabled()),
+        ]))
+    });
+    let items: Vec<ListItem> = std::iter::once(clipboard).chain(backups).collect();
+    let items = List::new(items)
+        .highlight_style(styles::row_highlight())
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(items, list, &mut state.list_state);
+
+    let keys = Line::from(vec![
+        Span::styled("[Enter]", styles::help_key()),
+        Span::styled(" Preview  ", styles::help()),
+        Span::styled("[Esc]", styles::help_key()),
+        Span::styled(" Cancel", styles::help()),
+    ]);
+    frame.render_widget(Paragraph::new(vec![Line::from(""), keys]), help);
+}
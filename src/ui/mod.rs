@@ -1,10 +1,18 @@
 mod confirm;
+mod downgrade;
+mod import;
+mod restart;
+mod review;
 mod styles;
+mod templates;
+mod terminal;
 
 use crate::app::{App, LoadingState, Tab};
-use crate::updates::{format_short_date, NewsInfo, PackageInfo};
+use crate::manifest::DriftKind;
+use crate::rebuilds::CheckStatus;
+use crate::updates::{format_short_date, HistoryAction, NewsInfo, PackageInfo, PacnewKind};
 use ratatui::{
-    layout::{Alignment, Constraint, Layout, Rect},
+    layout::{Alignment, Constraint, Layout, Rect, Size},
     style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph, Tabs},
@@ -182,14 +190,24 @@ fn format_package_name(name: &str, source_label: &str, total_width: usize) -> St
     }
 }
 
-pub fn draw(frame: &mut Frame, app: &mut App) {
-    let chunks = Layout::vertical([
+fn split_screen(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::vertical([
         Constraint::Length(3), // Header + tabs
         Constraint::Length(1), // Status bar
         Constraint::Min(0),    // Content
         Constraint::Length(2), // Help bar
     ])
-    .split(frame.area());
+    .split(area)
+}
+
+/// PTY size (rows, cols) for a transaction pane on a terminal of this size
+pub fn transaction_size(size: Size) -> (u16, u16) {
+    let content = split_screen(Rect::new(0, 0, size.width, size.height))[2];
+    terminal::inner_size(terminal::pane_area(content))
+}
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let chunks = split_screen(frame.area());
 
     draw_header(frame, app, chunks[0]);
     draw_status(frame, app, chunks[1]);
@@ -198,15 +216,15 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
 }
 
 fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
-    let titles = vec!["Updates", "Installed", "Orphans", "Rebuilds", "Search", "News"];
-    let selected = match app.tab {
-        Tab::Updates => 0,
-        Tab::Installed => 1,
-        Tab::Orphans => 2,
-        Tab::Rebuilds => 3,
-        Tab::Search => 4,
-        Tab::News => 5,
-    };
+    let unread = app.unread_news_count();
+    let titles: Vec<String> = Tab::ALL
+        .iter()
+        .map(|t| match t {
+            Tab::News if unread > 0 => format!("{} ({})", t.title(), unread),
+            _ => t.title().to_string(),
+        })
+        .collect();
+    let selected = app.tab.index();
 
     let tabs = Tabs::new(titles)
         .block(
@@ -224,6 +242,23 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    // Pre hooks hold the transaction back; say so rather than look frozen
+    if let Some(hooked) = &app.hooked_transaction {
+        let message = format!(" Running {} hooks before {}...", hooked.phase.label(), hooked.title);
+        frame.render_widget(Paragraph::new(Span::styled(message, styles::warning())), area);
+        return;
+    }
+
+    if let Some(prompt) = &app.hold_prompt {
+        let line = Line::from(vec![
+            Span::styled(format!(" Hold {} until version: ", prompt.name), styles::warning()),
+            Span::raw(format!("{}█", prompt.version)),
+            Span::styled("  (Enter to hold, Esc to cancel)", styles::disabled()),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+        return;
+    }
+
     // Show flash message if present
     if let Some(msg) = &app.last_message {
         let paragraph = Paragraph::new(Line::from(vec![
@@ -248,10 +283,15 @@ fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
     let orph_style = if orph > 0 { styles::warning() } else { styles::status_active() };
     let rebuild_style = if rebuild > 0 { styles::error() } else { styles::status_active() };
 
+    // Only shown when something needs restarting
+    let reboot = app.restart_status.as_ref().is_some_and(|s| s.reboot_needed());
+    let services = app.restart_status.as_ref().map_or(0, |s| s.units.len());
+    let restart_needed = app.restart_status.as_ref().is_some_and(|s| s.needs_attention());
+
     let status = if width >= 100 {
         // Wide: full labels
         let loading_indicator = if loading { " [loading...]" } else { "" };
-        Line::from(vec![
+        let mut line = Line::from(vec![
             Span::raw(" Pacman: "),
             Span::styled(format!("{} updates", pac), pac_style),
             Span::styled(" | ", styles::disabled()),
@@ -267,12 +307,20 @@ fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled(" | ", styles::disabled()),
             Span::raw("Rebuilds: "),
             Span::styled(format!("{} issues", rebuild), rebuild_style),
-            Span::styled(loading_indicator, styles::warning()),
-        ])
+        ]);
+        if reboot {
+            line.push_span(Span::styled(" | ", styles::disabled()));
+            line.push_span(Span::styled("Reboot needed (R)", styles::error()));
+        } else if services > 0 {
+            line.push_span(Span::styled(" | ", styles::disabled()));
+            line.push_span(Span::styled(format!("{} to restart (R)", services), styles::warning()));
+        }
+        line.push_span(Span::styled(loading_indicator, styles::warning()));
+        line
     } else if width >= 60 {
         // Medium: abbreviated labels
         let loading_indicator = if loading { " [...]" } else { "" };
-        Line::from(vec![
+        let mut line = Line::from(vec![
             Span::raw(" Pac: "),
             Span::styled(format!("{}", pac), pac_style),
             Span::styled(" | ", styles::disabled()),
@@ -288,12 +336,20 @@ fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled(" | ", styles::disabled()),
             Span::raw("Reb: "),
             Span::styled(format!("{}", rebuild), rebuild_style),
-            Span::styled(loading_indicator, styles::warning()),
-        ])
+        ]);
+        if reboot {
+            line.push_span(Span::styled(" | ", styles::disabled()));
+            line.push_span(Span::styled("Reboot", styles::error()));
+        } else if services > 0 {
+            line.push_span(Span::styled(" | ", styles::disabled()));
+            line.push_span(Span::styled(format!("Restart: {}", services), styles::warning()));
+        }
+        line.push_span(Span::styled(loading_indicator, styles::warning()));
+        line
     } else {
         // Narrow: minimal
         let loading_indicator = if loading { " *" } else { "" };
-        Line::from(vec![
+        let mut line = Line::from(vec![
             Span::raw(" P:"),
             Span::styled(format!("{}", pac), pac_style),
             Span::raw(" A:"),
@@ -304,8 +360,12 @@ fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled(format!("{}", orph), orph_style),
             Span::raw(" R:"),
             Span::styled(format!("{}", rebuild), rebuild_style),
-            Span::styled(loading_indicator, styles::warning()),
-        ])
+        ]);
+        if restart_needed {
+            line.push_span(Span::styled(" !", if reboot { styles::error() } else { styles::warning() }));
+        }
+        line.push_span(Span::styled(loading_indicator, styles::warning()));
+        line
     };
 
     let paragraph = Paragraph::new(status);
@@ -318,15 +378,48 @@ fn draw_content(frame: &mut Frame, app: &mut App, area: Rect) {
         Tab::Updates => draw_updates(frame, app, area),
         Tab::Installed => draw_installed(frame, app, area),
         Tab::Orphans => draw_orphans(frame, app, area),
+        Tab::Drift => draw_drift(frame, app, area),
         Tab::Rebuilds => draw_rebuilds(frame, app, area),
         Tab::Search => draw_search(frame, app, area),
         Tab::News => draw_news(frame, app, area),
+        Tab::History => draw_history(frame, app, area),
+        Tab::Pacnew => draw_pacnew(frame, app, area),
+        Tab::Snapshots => draw_snapshots(frame, app, area),
     }
 
     // Draw confirmation overlay if active
     if let Some(state) = &app.pending_confirmation {
         confirm::draw_confirmation(frame, state, area);
     }
+
+    // Version picker for a downgrade
+    if let Some(state) = &mut app.downgrade {
+        downgrade::draw_downgrade(frame, state, area);
+    }
+
+    // PKGBUILD review before building AUR packages
+    if let Some(state) = &app.review {
+        review::draw_review(frame, state, area);
+    }
+
+    // Restoring packages from an export or the clipboard
+    if let Some(state) = &mut app.import {
+        import::draw_import(frame, state, area);
+    }
+
+    // Check template picker
+    if let Some(state) = &mut app.template_picker {
+        templates::draw_templates(frame, state, area);
+    }
+
+    if app.show_restart {
+        restart::draw_restart(frame, app.restart_status.as_ref(), app.restart_scroll, area);
+    }
+
+    // Running or finished transaction sits on top of everything
+    if let Some(transaction) = &mut app.transaction {
+        terminal::draw_transaction(frame, transaction, area);
+    }
 }
 
 fn draw_updates(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -349,10 +442,16 @@ fn draw_updates(frame: &mut Frame, app: &mut App, area: Rect) {
     };
 
     // Collect filtered packages into owned data
-    let filtered: Vec<(usize, bool, String, String, String, &'static str)> = app
+    // (index, selected, name, old, new, source, hold label)
+    type UpdateRow = (usize, bool, String, String, String, &'static str, Option<String>);
+    let filtered: Vec<UpdateRow> = app
         .filtered_updates()
         .into_iter()
         .map(|(idx, pkg)| {
+            let hold = app.package_hold(pkg).map(|hold| match hold.expiry_label() {
+                Some(expiry) => format!("held {}", expiry),
+                None => "held".to_string(),
+            });
             (
                 idx,
                 pkg.selected,
@@ -360,6 +459,7 @@ fn draw_updates(frame: &mut Frame, app: &mut App, area: Rect) {
                 pkg.old_version.clone(),
                 pkg.new_version.clone(),
                 pkg.source_label(),
+                hold,
             )
         })
         .collect();
@@ -388,10 +488,28 @@ fn draw_updates(frame: &mut Frame, app: &mut App, area: Rect) {
     let items: Vec<ListItem> = filtered
         .iter()
         .enumerate()
-        .map(|(filter_idx, (_, selected, name, old_version, new_version, source))| {
+        .map(|(filter_idx, (_, selected, name, old_version, new_version, source, hold))| {
             let is_cursor = app.list_state.selected() == Some(filter_idx);
             let checkbox = if *selected { "[x]" } else { "[ ]" };
 
+            // Held packages are dimmed entirely
+            if let Some(hold) = hold {
+                let name_style = if is_cursor && is_active {
+                    styles::row_highlight()
+                } else {
+                    styles::disabled()
+                };
+                return ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", checkbox), styles::disabled()),
+                    Span::styled(format_package_name(name, source, 30), name_style),
+                    Span::raw(" "),
+                    Span::styled(truncate_with_ellipsis(old_version, 14), styles::disabled()),
+                    Span::styled(" -> ", styles::disabled()),
+                    Span::styled(new_version.as_str(), styles::disabled()),
+                    Span::styled(format!("  [{}]", hold), styles::disabled()),
+                ]));
+            }
+
             let line = Line::from(vec![
                 Span::styled(
                     format!("{} ", checkbox),
@@ -419,11 +537,18 @@ fn draw_updates(frame: &mut Frame, app: &mut App, area: Rect) {
         })
         .collect();
 
+    let held = app.held_count();
+    let title = if held > 0 {
+        format!(" Packages ({} held) ", held)
+    } else {
+        " Packages ".to_string()
+    };
+
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Packages ")
+                .title(title)
                 .title_style(if is_active {
                     styles::title_active()
                 } else {
@@ -472,6 +597,11 @@ fn draw_installed(frame: &mut Frame, app: &mut App, area: Rect) {
         .map(|(idx, pkg)| (idx, pkg.selected, pkg.name.clone(), pkg.version.clone(), pkg.source_label()))
         .collect();
     let filtered_count = filtered.len();
+    let title = if app.aur_problems_only {
+        " Installed Packages (AUR problems) "
+    } else {
+        " Installed Packages "
+    };
 
     // Draw filter bar
     if let Some(filter_area) = filter_area {
@@ -488,8 +618,13 @@ fn draw_installed(frame: &mut Frame, app: &mut App, area: Rect) {
         return;
     }
 
+    if filtered_count == 0 && app.aur_problems_only {
+        draw_empty_state(frame, title, "No AUR packages need attention (p to show all)", is_active, list_area);
+        return;
+    }
+
     if filtered_count == 0 && !app.filter_text.is_empty() {
-        draw_empty_state(frame, " Installed Packages ", "No packages match filter", is_active, list_area);
+        draw_empty_state(frame, title, "No packages match filter", is_active, list_area);
         return;
     }
 
@@ -500,7 +635,7 @@ fn draw_installed(frame: &mut Frame, app: &mut App, area: Rect) {
             let is_cursor = app.installed_list_state.selected() == Some(filter_idx);
             let checkbox = if *selected { "[x]" } else { "[ ]" };
 
-            let line = Line::from(vec![
+            let mut line = Line::from(vec![
                 Span::styled(
                     format!("{} ", checkbox),
                     if *selected {
@@ -520,6 +655,20 @@ fn draw_installed(frame: &mut Frame, app: &mut App, area: Rect) {
                 Span::raw(" "),
                 Span::styled(version, styles::disabled()),
             ]);
+            for badge in app.aur_badges(name) {
+                let style = match badge {
+                    "new maintainer" | "not in AUR" => styles::error(),
+                    _ => styles::warning(),
+                };
+                line.push_span(Span::styled(format!(" [{}]", badge), style));
+            }
+            // Votes and popularity help judge an orphan or a new maintainer
+            if let Some(aur) = app.aur_status.get(name).filter(|_| app.aur_problems_only) {
+                line.push_span(Span::styled(
+                    format!("  {} votes, popularity {:.2}", aur.votes, aur.popularity),
+                    styles::disabled(),
+                ));
+            }
 
             ListItem::new(line)
         })
@@ -529,7 +678,7 @@ fn draw_installed(frame: &mut Frame, app: &mut App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Installed Packages ")
+                .title(title)
                 .title_style(if is_active {
                     styles::title_active()
                 } else {
@@ -636,21 +785,136 @@ fn draw_orphans(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+fn draw_drift(frame: &mut Frame, app: &mut App, area: Rect) {
+    let is_active = app.tab == Tab::Drift;
+
+    let (list_area, info_area) = if app.show_info_pane {
+        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(10)]).split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    if app.drift.is_empty() {
+        let message = if let Some(error) = &app.manifest_error {
+            error.clone()
+        } else if app.manifest.is_none() {
+            "No packages.toml - press w to write one declaring the explicitly installed packages"
+                .to_string()
+        } else if app.drift_loading {
+            "Comparing packages.toml with installed packages...".to_string()
+        } else {
+            "Installed packages match packages.toml".to_string()
+        };
+        draw_empty_state(frame, " Drift ", &message, is_active, list_area);
+        if let Some(info_area) = info_area {
+            draw_info_pane(frame, app.cached_pkg_info.as_ref(), info_area);
+        }
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .drift
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let is_selected = app.drift_list_state.selected() == Some(idx);
+            let checkbox = if entry.selected { "[x]" } else { "[ ]" };
+            let kind_style = match entry.kind {
+                DriftKind::Missing => styles::error(),
+                DriftKind::Extra => styles::warning(),
+            };
+            let source_label = if entry.aur { " (AUR)" } else { "" };
+            // Where a missing package was declared, or what version the extra one is at
+            let detail = match (&entry.group, &entry.version) {
+                (Some(group), _) => format!("group {}", group),
+                (None, Some(version)) => version.clone(),
+                (None, None) => String::new(),
+            };
+
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{} ", checkbox),
+                    if entry.selected {
+                        styles::status_active()
+                    } else {
+                        styles::disabled()
+                    },
+                ),
+                Span::styled(format!("{:<13}", entry.kind.label()), kind_style),
+                Span::styled(
+                    format_package_name(&entry.name, source_label, 36),
+                    if is_selected && is_active {
+                        styles::row_highlight()
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::raw(" "),
+                Span::styled(detail, styles::disabled()),
+            ]);
+
+            ListItem::new(line)
+        })
+        .collect();
+
+    let missing = app.drift.iter().filter(|e| e.kind == DriftKind::Missing).count();
+    let title = format!(
+        " Drift from packages.toml ({} missing, {} not declared) ",
+        missing,
+        app.drift.len() - missing
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(if is_active {
+                    styles::title_active()
+                } else {
+                    styles::title_inactive()
+                })
+                .border_style(if is_active {
+                    styles::border_active()
+                } else {
+                    styles::border_inactive()
+                }),
+        )
+        .highlight_style(styles::row_highlight())
+        .highlight_symbol(if is_active { ">> " } else { "   " });
+
+    frame.render_stateful_widget(list, list_area, &mut app.drift_list_state);
+
+    if let Some(info_area) = info_area {
+        draw_info_pane(frame, app.cached_pkg_info.as_ref(), info_area);
+    }
+}
+
 fn draw_rebuilds(frame: &mut Frame, app: &mut App, area: Rect) {
     let is_active = app.tab == Tab::Rebuilds;
 
     // Split area for info pane if visible
-    let (list_area, info_area) = if app.show_info_pane {
+    let (area, info_area) = if app.show_info_pane {
         let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(10)]).split(area);
         (chunks[0], Some(chunks[1]))
     } else {
         (area, None)
     };
 
+    // Per-check progress below the issues
+    let list_area = if app.check_progress.is_empty() {
+        area
+    } else {
+        let height = (app.check_progress.len() as u16 + 2).min(area.height / 2);
+        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).split(area);
+        draw_check_progress(frame, app, chunks[1]);
+        chunks[0]
+    };
+
     if app.rebuild_issues.is_empty() {
         let message = if app.loading == LoadingState::Loading {
             "Checking for rebuild issues..."
-        } else if app.rebuild_checks.is_empty() {
+        } else if app.rebuild_checks.is_empty() && !app.config.scan_libraries {
             "No rebuild checks configured\nAdd checks to ~/.config/upkeep/checks.toml"
         } else {
             "No rebuild issues detected"
@@ -687,7 +951,7 @@ fn draw_rebuilds(frame: &mut Frame, app: &mut App, area: Rect) {
                         styles::error()
                     },
                 ),
-                Span::styled(" - needs rebuild", styles::disabled()),
+                Span::styled(format!(" - {}", issue.detail), styles::disabled()),
             ]);
 
             ListItem::new(line)
@@ -721,6 +985,48 @@ fn draw_rebuilds(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+fn draw_check_progress(frame: &mut Frame, app: &App, area: Rect) {
+    let done = app
+        .check_progress
+        .iter()
+        .filter(|p| !matches!(p.status, CheckStatus::Pending | CheckStatus::Running))
+        .count();
+
+    let lines: Vec<Line> = app
+        .rebuild_checks
+        .iter()
+        .zip(&app.check_progress)
+        .map(|(check, progress)| {
+            let status_style = match progress.status {
+                CheckStatus::Pending | CheckStatus::Skipped => styles::disabled(),
+                CheckStatus::Running | CheckStatus::TimedOut => styles::warning(),
+                CheckStatus::Ok => styles::status_active(),
+                CheckStatus::Failed => styles::error(),
+            };
+            let elapsed = progress
+                .elapsed()
+                .filter(|_| progress.status != CheckStatus::Skipped)
+                .map(|d| format!("{:.1}s", d.as_secs_f64()))
+                .unwrap_or_default();
+            Line::from(vec![
+                Span::styled(format!("{:<10}", progress.status.label()), status_style),
+                Span::raw(truncate_with_ellipsis(&check.name, 40)),
+                Span::styled(format!(" {:>6}", elapsed), styles::disabled()),
+            ])
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Checks ({}/{}) ", done, app.check_progress.len()))
+            .title_style(styles::title_inactive())
+            .border_style(styles::border_inactive()),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
 fn draw_search(frame: &mut Frame, app: &mut App, area: Rect) {
     let is_active = app.tab == Tab::Search;
 
@@ -855,6 +1161,7 @@ fn draw_news(frame: &mut Frame, app: &mut App, area: Rect) {
         .enumerate()
         .map(|(idx, item)| {
             let is_selected = app.news_list_state.selected() == Some(idx);
+            let unread = !app.state.is_news_read(&item.link);
 
             // Build indicator: * for related, ! for attention
             let indicator = match (item.requires_attention, !item.related_packages.is_empty()) {
@@ -867,7 +1174,17 @@ fn draw_news(frame: &mut Frame, app: &mut App, area: Rect) {
             // Date in short format
             let date_short = format_short_date(&item.pub_date);
 
+            let title_style = if is_selected && is_active {
+                styles::row_highlight()
+            } else if item.requires_attention {
+                styles::news_attention()
+            } else {
+                Style::default()
+            };
+
             let line = Line::from(vec![
+                // Unread marker
+                Span::styled(if unread { "● " } else { "  " }, styles::news_related()),
                 // * indicator (blue)
                 Span::styled(
                     &indicator[0..1],
@@ -892,12 +1209,10 @@ fn draw_news(frame: &mut Frame, app: &mut App, area: Rect) {
                 // Title
                 Span::styled(
                     truncate_with_ellipsis(&item.title, 60),
-                    if is_selected && is_active {
-                        styles::row_highlight()
-                    } else if item.requires_attention {
-                        styles::news_attention()
+                    if unread {
+                        title_style.patch(styles::news_unread())
                     } else {
-                        Style::default()
+                        title_style
                     },
                 ),
                 // Author
@@ -910,7 +1225,7 @@ fn draw_news(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let attention_count = app.news_attention_count();
     let related_count = app.news_related_count();
-    let title = if attention_count > 0 || related_count > 0 {
+    let mut title = if attention_count > 0 || related_count > 0 {
         format!(
             " Arch News ({} attention, {} related) ",
             attention_count, related_count
@@ -918,6 +1233,9 @@ fn draw_news(frame: &mut Frame, app: &mut App, area: Rect) {
     } else {
         format!(" Arch News ({}) ", app.news_items.len())
     };
+    if app.news_offline.is_some() {
+        title.push_str("- offline, showing cached copy ");
+    }
 
     let list = List::new(items)
         .block(
@@ -1003,25 +1321,460 @@ fn draw_news_info_pane(frame: &mut Frame, info: Option<&NewsInfo>, scroll: u16,
     frame.render_widget(paragraph, area);
 }
 
-fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
-    let (line1, line2) = match app.tab {
-        Tab::Updates => (
+fn draw_history(frame: &mut Frame, app: &mut App, area: Rect) {
+    let is_active = app.tab == Tab::History;
+
+    // Split area for info pane if visible
+    let (main_area, info_area) = if app.show_info_pane {
+        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(10)]).split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    // Split main area for filter bar if filtering
+    let (filter_area, list_area) = if app.filter_mode || !app.filter_text.is_empty() {
+        let chunks = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(main_area);
+        (Some(chunks[0]), chunks[1])
+    } else {
+        (None, main_area)
+    };
+
+    let filtered = app.filtered_history();
+    let filtered_count = filtered.len();
+
+    if let Some(filter_area) = filter_area {
+        draw_filter_bar(frame, &app.filter_text, app.filter_mode, filtered_count, filter_area);
+    }
+
+    if app.history.is_empty() {
+        let message = if app.history_loading {
+            "Reading pacman.log...".to_string()
+        } else if let Some(error) = &app.history_error {
+            format!("{} (press r to retry)", error)
+        } else {
+            "No package history found".to_string()
+        };
+        draw_empty_state(frame, " History ", &message, is_active, list_area);
+        if let Some(info_area) = info_area {
+            draw_history_info_pane(frame, app, info_area);
+        }
+        return;
+    }
+
+    if filtered_count == 0 && !app.filter_text.is_empty() {
+        draw_empty_state(frame, " History ", "No packages match filter", is_active, list_area);
+        if let Some(info_area) = info_area {
+            draw_history_info_pane(frame, app, info_area);
+        }
+        return;
+    }
+
+    let mut previous_transaction = None;
+    let items: Vec<ListItem> = filtered
+        .iter()
+        .enumerate()
+        .map(|(filter_idx, (_, entry))| {
+            let is_cursor = app.history_list_state.selected() == Some(filter_idx);
+
+            // Only label the first row of each transaction so groups stand out
+            let first_in_group = previous_transaction != Some(entry.transaction);
+            previous_transaction = Some(entry.transaction);
+            let timestamp = if first_in_group {
+                format!("{:<17}", entry.timestamp)
+            } else {
+                " ".repeat(17)
+            };
+
+            let action_style = match entry.action {
+                HistoryAction::Removed | HistoryAction::Downgraded => styles::error(),
+                HistoryAction::Installed => styles::status_active(),
+                HistoryAction::Upgraded | HistoryAction::Reinstalled => styles::warning(),
+            };
+
+            let line = Line::from(vec![
+                Span::styled(timestamp, styles::disabled()),
+                Span::styled(format!("{:<12}", entry.action.label()), action_style),
+                Span::styled(
+                    truncate_with_ellipsis(&entry.name, 30),
+                    if is_cursor && is_active {
+                        styles::row_highlight()
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::raw(" "),
+                Span::styled(entry.version_display(), styles::disabled()),
+            ]);
+
+            ListItem::new(line)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" History ({}) ", filtered_count))
+                .title_style(if is_active {
+                    styles::title_active()
+                } else {
+                    styles::title_inactive()
+                })
+                .border_style(if is_active {
+                    styles::border_active()
+                } else {
+                    styles::border_inactive()
+                }),
+        )
+        .highlight_style(styles::row_highlight())
+        .highlight_symbol(if is_active { ">> " } else { "   " });
+
+    frame.render_stateful_widget(list, list_area, &mut app.history_list_state);
+
+    if let Some(info_area) = info_area {
+        draw_history_info_pane(frame, app, info_area);
+    }
+}
+
+fn draw_history_info_pane(frame: &mut Frame, app: &App, area: Rect) {
+    let content = if let Some(entry) = app.selected_history_entry() {
+        let mut lines = vec![
+            // Line 1: package name
+            Line::from(Span::styled(&entry.name, styles::title_active())),
+            // Line 2: transaction that changed it
             Line::from(vec![
-                Span::styled("f/F", styles::help_key()),
-                Span::styled(" Filter", styles::help()),
-                Span::styled(" | ", styles::help()),
-                Span::styled("u", styles::help_key()),
-                Span::styled(" Update", styles::help()),
-                Span::styled(" | ", styles::help()),
-                Span::styled("Enter", styles::help_key()),
-                Span::styled(" Update All", styles::help()),
-                Span::styled(" | ", styles::help()),
-                Span::styled("a/n", styles::help_key()),
-                Span::styled(" All/None", styles::help()),
+                Span::styled("Transaction: ", styles::disabled()),
+                Span::styled(&entry.timestamp, styles::status_active()),
+                Span::styled(" | ", styles::disabled()),
+                Span::styled(
+                    if entry.command.is_empty() { "unknown command" } else { entry.command.as_str() },
+                    styles::status_active(),
+                ),
             ]),
-            Line::from(vec![
-                Span::styled("Space", styles::help_key()),
-                Span::styled(" Select", styles::help()),
+            Line::from(Span::styled("Version history:", styles::disabled())),
+        ];
+
+        // Remaining lines: every recorded change for this package, newest first
+        for change in app.package_history(&entry.name) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {}  ", change.timestamp), styles::disabled()),
+                Span::styled(format!("{:<12}", change.action.label()), styles::status_active()),
+                Span::raw(change.version_display()),
+            ]));
+        }
+
+        lines
+    } else {
+        vec![Line::from(Span::styled(
+            "No history entry selected",
+            styles::disabled(),
+        ))]
+    };
+
+    let paragraph = Paragraph::new(content).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Info ")
+            .title_style(styles::title_inactive())
+            .border_style(styles::border_inactive()),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_pacnew(frame: &mut Frame, app: &mut App, area: Rect) {
+    let is_active = app.tab == Tab::Pacnew;
+
+    if app.pacnew_files.is_empty() {
+        let message = if app.pacnew_loading {
+            "Scanning /etc for .pacnew and .pacsave files..."
+        } else {
+            "No .pacnew or .pacsave files found"
+        };
+        draw_empty_state(frame, " Config Files ", message, is_active, area);
+        return;
+    }
+
+    // File list on top, diff of the file under the cursor below
+    let chunks =
+        Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)]).split(area);
+
+    let items: Vec<ListItem> = app
+        .pacnew_files
+        .iter()
+        .enumerate()
+        .map(|(idx, file)| {
+            let is_selected = app.pacnew_list_state.selected() == Some(idx);
+            let checkbox = if file.selected { "[x]" } else { "[ ]" };
+
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{} ", checkbox),
+                    if file.selected {
+                        styles::status_active()
+                    } else {
+                        styles::disabled()
+                    },
+                ),
+                Span::styled(
+                    format!("{:<8}", file.kind.label()),
+                    match file.kind {
+                        PacnewKind::Pacnew => styles::status_active(),
+                        PacnewKind::Pacsave => styles::warning(),
+                    },
+                ),
+                Span::styled(
+                    &file.path,
+                    if is_selected && is_active {
+                        styles::row_highlight()
+                    } else {
+                        Style::default()
+                    },
+                ),
+            ]);
+
+            ListItem::new(line)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Config Files ({}) ", app.pacnew_files.len()))
+                .title_style(if is_active {
+                    styles::title_active()
+                } else {
+                    styles::title_inactive()
+                })
+                .border_style(if is_active {
+                    styles::border_active()
+                } else {
+                    styles::border_inactive()
+                }),
+        )
+        .highlight_style(styles::row_highlight())
+        .highlight_symbol(if is_active { ">> " } else { "   " });
+
+    frame.render_stateful_widget(list, chunks[0], &mut app.pacnew_list_state);
+
+    draw_pacnew_diff(frame, app, chunks[1]);
+}
+
+/// Colors for a line of unified diff output
+fn diff_style(line: &str) -> Style {
+    if line.starts_with("+++") || line.starts_with("---") {
+        styles::disabled()
+    } else if line.starts_with('+') {
+        styles::status_active()
+    } else if line.starts_with('-') {
+        styles::error()
+    } else if line.starts_with("@@") {
+        styles::help_key()
+    } else {
+        Style::default()
+    }
+}
+
+fn draw_pacnew_diff(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = if app.pacnew_diff.is_empty() {
+        vec![Line::from(Span::styled("Loading diff...", styles::disabled()))]
+    } else {
+        app.pacnew_diff
+            .iter()
+            .map(|line| Line::from(Span::styled(line.as_str(), diff_style(line))))
+            .collect()
+    };
+
+    let title = match app.selected_pacnew() {
+        Some(file) => format!(" {} (Shift+↑/↓ to scroll) ", file.target),
+        None => " Diff ".to_string(),
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(styles::title_inactive())
+                .border_style(styles::border_inactive()),
+        )
+        .scroll((app.pacnew_scroll, 0));
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_snapshots(frame: &mut Frame, app: &mut App, area: Rect) {
+    let is_active = app.tab == Tab::Snapshots;
+
+    if app.state.snapshots.is_empty() {
+        let message = if app.config.snapshot.is_none() {
+            "No snapshot provider configured - add a [snapshot] table to config.toml"
+        } else {
+            "No snapshots taken yet - one is taken before each update, reinstall, removal, rebuild"
+        };
+        draw_empty_state(frame, " Snapshots ", message, is_active, area);
+        return;
+    }
+
+    // Snapshot list on top, package changes of the one under the cursor below
+    let chunks =
+        Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)]).split(area);
+
+    let items: Vec<ListItem> = app
+        .snapshot_list()
+        .iter()
+        .enumerate()
+        .map(|(idx, snapshot)| {
+            let is_selected = app.snapshots_list_state.selected() == Some(idx);
+            let created = chrono::DateTime::from_timestamp(snapshot.created, 0)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let (status, status_style) = match snapshot.succeeded {
+                Some(true) => ("ok", styles::status_active()),
+                Some(false) => ("failed", styles::error()),
+                None => ("unknown", styles::disabled()),
+            };
+
+            let line = Line::from(vec![
+                Span::styled(format!("{} ", created), styles::disabled()),
+                Span::styled(format!("{:<8}", status), status_style),
+                Span::styled(
+                    format!("{:<24} ", truncate_with_ellipsis(&snapshot.id, 24)),
+                    if is_selected && is_active {
+                        styles::row_highlight()
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::raw(snapshot.transaction.clone()),
+                Span::styled(format!(" ({} changes)", snapshot.changes.len()), styles::disabled()),
+            ]);
+
+            ListItem::new(line)
+        })
+        .collect();
+
+    let keep = app.config.snapshot.as_ref().map(|s| s.keep);
+    let title = match keep {
+        Some(keep) => format!(" Snapshots ({}, keeping {}) ", app.state.snapshots.len(), keep),
+        None => format!(" Snapshots ({}) ", app.state.snapshots.len()),
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(if is_active {
+                    styles::title_active()
+                } else {
+                    styles::title_inactive()
+                })
+                .border_style(if is_active {
+                    styles::border_active()
+                } else {
+                    styles::border_inactive()
+                }),
+        )
+        .highlight_style(styles::row_highlight())
+        .highlight_symbol(if is_active { ">> " } else { "   " });
+
+    frame.render_stateful_widget(list, chunks[0], &mut app.snapshots_list_state);
+
+    draw_snapshot_changes(frame, app, chunks[1]);
+}
+
+fn draw_snapshot_changes(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(snapshot) = app.selected_snapshot() else {
+        draw_empty_state(frame, " Changes ", "Select a snapshot", false, area);
+        return;
+    };
+
+    let lines: Vec<Line> = if snapshot.changes.is_empty() {
+        let message = match snapshot.succeeded {
+            Some(_) => "No package changes were logged",
+            None => "Package changes are recorded when the transaction finishes",
+        };
+        vec![Line::from(Span::styled(message, styles::disabled()))]
+    } else {
+        snapshot
+            .changes
+            .iter()
+            .map(|change| {
+                let style = if change.starts_with("removed") {
+                    styles::error()
+                } else if change.starts_with("downgraded") {
+                    styles::warning()
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(change.as_str(), style))
+            })
+            .collect()
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Changes by {} (Shift+↑/↓ to scroll) ", snapshot.transaction))
+                .title_style(styles::title_inactive())
+                .border_style(styles::border_inactive()),
+        )
+        .scroll((app.snapshot_scroll, 0));
+
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    if let Some(transaction) = &app.transaction {
+        let (line1, line2) = if transaction.is_running() {
+            (
+                Line::from(Span::styled("Keys are sent to the running command", styles::help())),
+                Line::from(vec![
+                    Span::styled("Shift+PgUp/PgDn", styles::help_key()),
+                    Span::styled(" Scroll output", styles::help()),
+                ]),
+            )
+        } else {
+            (
+                Line::from(vec![
+                    Span::styled("Enter/Esc", styles::help_key()),
+                    Span::styled(" Close", styles::help()),
+                    Span::styled(" | ", styles::help()),
+                    Span::styled("PgUp/PgDn", styles::help_key()),
+                    Span::styled(" Scroll output", styles::help()),
+                ]),
+                Line::from(""),
+            )
+        };
+        let help = Paragraph::new(vec![line1, line2]).alignment(Alignment::Center);
+        frame.render_widget(help, area);
+        return;
+    }
+
+    let (line1, line2) = match app.tab {
+        Tab::Updates => (
+            Line::from(vec![
+                Span::styled("f/F", styles::help_key()),
+                Span::styled(" Filter", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("u", styles::help_key()),
+                Span::styled(" Update", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("Enter", styles::help_key()),
+                Span::styled(" Update All", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("a/n", styles::help_key()),
+                Span::styled(" All/None", styles::help()),
+            ]),
+            Line::from(vec![
+                Span::styled("Space", styles::help_key()),
+                Span::styled(" Select", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("h/H/V", styles::help_key()),
+                Span::styled(" Hold/7d/Until", styles::help()),
                 Span::styled(" | ", styles::help()),
                 Span::styled("c", styles::help_key()),
                 Span::styled(" Clean cache", styles::help()),
@@ -1049,6 +1802,15 @@ fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(" | ", styles::help()),
                 Span::styled("c/C", styles::help_key()),
                 Span::styled(" Export/Copy", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("b", styles::help_key()),
+                Span::styled(" Restore", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("v", styles::help_key()),
+                Span::styled(" Downgrade", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("p", styles::help_key()),
+                Span::styled(" AUR problems", styles::help()),
             ]),
             Line::from(vec![
                 Span::styled("Space", styles::help_key()),
@@ -1089,11 +1851,48 @@ fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(" Quit", styles::help()),
             ]),
         ),
+        Tab::Drift => (
+            Line::from(vec![
+                Span::styled("i", styles::help_key()),
+                Span::styled(" Install missing", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("m", styles::help_key()),
+                Span::styled(" Mark selected as deps", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("d/D", styles::help_key()),
+                Span::styled(" Remove selected/+Deps", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("w", styles::help_key()),
+                Span::styled(" Write packages.toml", styles::help()),
+            ]),
+            Line::from(vec![
+                Span::styled("Space", styles::help_key()),
+                Span::styled(" Select", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("a/n", styles::help_key()),
+                Span::styled(" All/None", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("?", styles::help_key()),
+                Span::styled(" Info", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("r", styles::help_key()),
+                Span::styled(" Reload", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("q", styles::help_key()),
+                Span::styled(" Quit", styles::help()),
+            ]),
+        ),
         Tab::Rebuilds => (
             Line::from(vec![
                 Span::styled("Enter", styles::help_key()),
                 Span::styled(" Fix", styles::help()),
                 Span::styled(" | ", styles::help()),
+                Span::styled("A", styles::help_key()),
+                Span::styled(" Rebuild all", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("F", styles::help_key()),
+                Span::styled(" Run all checks", styles::help()),
+                Span::styled(" | ", styles::help()),
                 Span::styled("a/n", styles::help_key()),
                 Span::styled(" All/None", styles::help()),
             ]),
@@ -1101,6 +1900,9 @@ fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled("Space", styles::help_key()),
                 Span::styled(" Select", styles::help()),
                 Span::styled(" | ", styles::help()),
+                Span::styled("t", styles::help_key()),
+                Span::styled(" Add checks", styles::help()),
+                Span::styled(" | ", styles::help()),
                 Span::styled("?", styles::help_key()),
                 Span::styled(" Info", styles::help()),
                 Span::styled(" | ", styles::help()),
@@ -1154,6 +1956,75 @@ fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled("r", styles::help_key()),
                 Span::styled(" Refresh", styles::help()),
                 Span::styled(" | ", styles::help()),
+                Span::styled("M", styles::help_key()),
+                Span::styled(" Mark all read", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("q", styles::help_key()),
+                Span::styled(" Quit", styles::help()),
+            ]),
+        ),
+        Tab::History => (
+            Line::from(vec![
+                Span::styled("f/F", styles::help_key()),
+                Span::styled(" Filter", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("↑/↓", styles::help_key()),
+                Span::styled(" Navigate", styles::help()),
+            ]),
+            Line::from(vec![
+                Span::styled("?", styles::help_key()),
+                Span::styled(" Info", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("r", styles::help_key()),
+                Span::styled(" Refresh", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("q", styles::help_key()),
+                Span::styled(" Quit", styles::help()),
+            ]),
+        ),
+        Tab::Pacnew => (
+            Line::from(vec![
+                Span::styled("o", styles::help_key()),
+                Span::styled(" Keep current", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("t", styles::help_key()),
+                Span::styled(" Take new", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("e", styles::help_key()),
+                Span::styled(" Merge", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("d", styles::help_key()),
+                Span::styled(" Delete", styles::help()),
+            ]),
+            Line::from(vec![
+                Span::styled("Space", styles::help_key()),
+                Span::styled(" Select", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("Shift+↑/↓", styles::help_key()),
+                Span::styled(" Scroll diff", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("r", styles::help_key()),
+                Span::styled(" Refresh", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("q", styles::help_key()),
+                Span::styled(" Quit", styles::help()),
+            ]),
+        ),
+        Tab::Snapshots => (
+            Line::from(vec![
+                Span::styled("d", styles::help_key()),
+                Span::styled(" Delete", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("P", styles::help_key()),
+                Span::styled(" Prune old", styles::help()),
+                Span::styled(" | ", styles::help()),
+                Span::styled("↑/↓", styles::help_key()),
+                Span::styled(" Navigate", styles::help()),
+            ]),
+            Line::from(vec![
+                Span::styled("Shift+↑/↓", styles::help_key()),
+                Span::styled(" Scroll changes", styles::help()),
+                Span::styled(" | ", styles::help()),
                 Span::styled("q", styles::help_key()),
                 Span::styled(" Quit", styles::help()),
             ]),
@@ -1164,3 +2035,70 @@ fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
 
     frame.render_widget(help, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::updates::{FakeBackend, InstalledPackage, Package, PackageSource};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(110, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn loaded_app() -> App {
+        let backend = FakeBackend {
+            updates: vec![Package::new(
+                "linux".into(),
+                "6.7.1-1".into(),
+                "6.7.2-1".into(),
+                PackageSource::Pacman,
+            )],
+            installed: vec![InstalledPackage::new("yay".into(), "12.3.0-1".into(), PackageSource::Aur)],
+            ..Default::default()
+        };
+        let mut app = App::with_backend(Config::default(), Vec::new(), Arc::new(backend));
+        app.refresh();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.loading == LoadingState::Loading && Instant::now() < deadline {
+            app.poll_tasks();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        app
+    }
+
+    #[test]
+    fn test_draw_updates_tab() {
+        let mut app = loaded_app();
+        let screen = render(&mut app);
+
+        assert!(screen.contains("Pacman: 1 updates"));
+        assert!(screen.contains("Installed: 1 (1 AUR)"));
+        assert!(screen.contains("linux"));
+        assert!(screen.contains("6.7.2-1"));
+    }
+
+    #[test]
+    fn test_draw_confirmation_overlay() {
+        let mut app = loaded_app();
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let screen = render(&mut app);
+
+        assert!(screen.contains("Update All Packages"));
+        assert!(screen.contains("1 package(s) will be updated"));
+    }
+}
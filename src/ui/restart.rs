@@ -0,0 +1,113 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::updates::RestartStatus;
+
+use super::{confirm::centered_rect, styles};
+
+pub fn draw_restart(frame: &mut Frame, status: Option<&RestartStatus>, scroll: u16, area: Rect) {
+    let dialog_width = 80.min(area.width.saturating_sub(4));
+    let dialog_height = 24.min(area.height.saturating_sub(2));
+    let dialog_area = centered_rect(dialog_width, dialog_height, area);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(styles::border_active())
+        .title(" Restart Check ")
+        .title_style(styles::title_active());
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let [content_area, help_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(2)]).areas(inner.inner(Margin::new(1, 0)));
+
+    let lines = match status {
+        Some(status) => status_lines(status),
+        None => vec![Line::from(Span::styled("Checking...", styles::warning()))],
+    };
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)), content_area);
+
+    let can_restart = status.is_some_and(|s| !s.system_units().is_empty());
+    let mut help = Vec::new();
+    if can_restart {
+        help.push(Span::styled("[Enter]", styles::help_key()));
+        help.push(Span::styled(" Restart system services  ", styles::help()));
+    }
+    help.push(Span::styled("[j/k]", styles::help_key()));
+    help.push(Span::styled(" Scroll  ", styles::help()));
+    help.push(Span::styled("[Esc]", styles::help_key()));
+    help.push(Span::styled(" Close", styles::help()));
+    frame.render_widget(Paragraph::new(vec![Line::from(""), Line::from(help)]), help_area);
+}
+
+fn status_lines(status: &RestartStatus) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+
+    // Kernel
+    match &status.kernel {
+        Some(kernel) if kernel.is_stale() => {
+            let installed: Vec<String> = kernel
+                .installed
+                .iter()
+                .map(|(package, release)| format!("{} {}", package, release))
+                .collect();
+            lines.push(Line::from(vec![
+                Span::styled("Kernel: ", styles::help()),
+                Span::styled(format!("running {}", kernel.running), styles::error()),
+                Span::styled(format!(", installed {}", installed.join(", ")), styles::help()),
+            ]));
+            lines.push(Line::from(Span::styled(
+                "  Reboot to load the new kernel and its modules",
+                styles::warning(),
+            )));
+        }
+        Some(kernel) => lines.push(Line::from(vec![
+            Span::styled("Kernel: ", styles::help()),
+            Span::styled(format!("running {} (up to date)", kernel.running), styles::status_active()),
+        ])),
+        None => lines.push(Line::from(Span::styled("Kernel: unknown", styles::disabled()))),
+    }
+    lines.push(Line::from(""));
+
+    // Processes using deleted files
+    if status.units.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No processes are using replaced libraries",
+            styles::status_active(),
+        )));
+        return lines;
+    }
+
+    lines.push(Line::from(Span::styled(
+        "Processes still using replaced libraries:",
+        styles::help(),
+    )));
+    for unit in &status.units {
+        let processes: Vec<String> = unit
+            .processes
+            .iter()
+            .map(|(pid, name)| format!("{} ({})", name, pid))
+            .collect();
+        let name = unit.unit.as_deref().unwrap_or("outside any service");
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {}", name), styles::warning()),
+            Span::styled(format!(" - {}", processes.join(", ")), styles::disabled()),
+        ]));
+        let files: Vec<&str> = unit
+            .files
+            .iter()
+            .map(|f| f.rsplit('/').next().unwrap_or(f))
+            .collect();
+        lines.push(Line::from(Span::styled(format!("    uses {}", files.join(", ")), styles::disabled())));
+        let suggestion = unit
+            .restart_command()
+            .unwrap_or_else(|| "restart these programs or log out and back in".to_string());
+        lines.push(Line::from(Span::styled(format!("    {}", suggestion), styles::help())));
+    }
+
+    lines
+}
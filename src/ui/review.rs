@@ -0,0 +1,124 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::app::ReviewState;
+use crate::updates::AurReview;
+
+use super::{confirm::centered_rect, diff_style, styles};
+
+pub fn draw_review(frame: &mut Frame, state: &ReviewState, area: Rect) {
+    let dialog_width = 100.min(area.width.saturating_sub(4));
+    let dialog_height = area.height.saturating_sub(2);
+    let dialog_area = centered_rect(dialog_width, dialog_height, area);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(styles::border_active())
+        .title(" Review AUR Packages ")
+        .title_style(styles::title_active());
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let [packages_area, content_area, help_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(2),
+    ])
+    .areas(inner.inner(Margin::new(1, 0)));
+
+    // One entry per package: the current one highlighted, warnings counted
+    let mut packages = Vec::new();
+    for (i, (name, review)) in state.names.iter().zip(&state.reviews).enumerate() {
+        let style = if i == state.current {
+            styles::row_highlight()
+        } else {
+            styles::help()
+        };
+        packages.push(Span::styled(format!(" {} ", name), style));
+        match review {
+            Some(Ok(review)) if !review.warnings.is_empty() => {
+                packages.push(Span::styled(format!("!{} ", review.warnings.len()), styles::error()));
+            }
+            Some(Err(_)) => packages.push(Span::styled("? ", styles::warning())),
+            _ => {}
+        }
+    }
+    frame.render_widget(Paragraph::new(Line::from(packages)), packages_area);
+
+    let lines = match state.reviews.get(state.current) {
+        Some(Some(Ok(review))) => review_lines(review),
+        Some(Some(Err(e))) => vec![Line::from(Span::styled(e.as_str(), styles::error()))],
+        _ => vec![Line::from(Span::styled("Fetching PKGBUILD...", styles::warning()))],
+    };
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((state.scroll, 0)),
+        content_area,
+    );
+
+    let mut help = vec![
+        Span::styled("[Tab]", styles::help_key()),
+        Span::styled(" Next package  ", styles::help()),
+        Span::styled("[j/k]", styles::help_key()),
+        Span::styled(" Scroll  ", styles::help()),
+        Span::styled("[Enter]", styles::help_key()),
+        Span::styled(" Accept all  ", styles::help()),
+    ];
+    if state.reviews.iter().any(|r| matches!(r, Some(Err(_)))) {
+        help.push(Span::styled("[!]", styles::help_key()));
+        help.push(Span::styled(" Build unreviewed  ", styles::error()));
+    }
+    help.push(Span::styled("[Esc]", styles::help_key()));
+    help.push(Span::styled(" Cancel", styles::help()));
+    frame.render_widget(Paragraph::new(vec![Line::from(""), Line::from(help)]), help_area);
+}
+
+fn review_lines(review: &AurReview) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+
+    for warning in &review.warnings {
+        lines.push(Line::from(Span::styled(format!("! {}", warning), styles::error())));
+    }
+
+    lines.push(Line::from(vec![
+        Span::styled("Maintainer: ", styles::help()),
+        Span::raw(review.source.maintainer.as_deref().unwrap_or("none")),
+        Span::styled("  Base: ", styles::help()),
+        Span::raw(review.source.pkgbase.as_str()),
+    ]));
+
+    match &review.previous {
+        None => {
+            lines.push(Line::from(Span::styled(
+                "First review - full PKGBUILD:",
+                styles::warning(),
+            )));
+            lines.push(Line::from(""));
+            lines.extend(review.source.pkgbuild.lines().map(Line::raw));
+        }
+        Some(_) if review.diff.is_empty() => {
+            lines.push(Line::from(Span::styled(
+                "PKGBUILD unchanged since your last review",
+                styles::status_active(),
+            )));
+        }
+        Some(_) => {
+            lines.push(Line::from(Span::styled(
+                "Changes since your last review:",
+                styles::help(),
+            )));
+            lines.push(Line::from(""));
+            lines.extend(
+                review
+                    .diff
+                    .iter()
+                    .map(|line| Line::from(Span::styled(line.as_str(), diff_style(line)))),
+            );
+        }
+    }
+
+    lines
+}
@@ -82,3 +82,8 @@ pub fn news_attention() -> Style {
 pub fn news_related() -> Style {
     Style::default().fg(Color::Blue)
 }
+
+/// Bold for unread news items
+pub fn news_unread() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
@@ -0,0 +1,74 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+};
+
+use crate::app::TemplatePickerState;
+
+use super::{confirm::centered_rect, styles};
+
+pub fn draw_templates(frame: &mut Frame, state: &mut TemplatePickerState, area: Rect) {
+    let list_height = state.templates.len().clamp(1, 15) as u16;
+    let dialog_width = 90.min(area.width.saturating_sub(4));
+    let dialog_height = (list_height + 7).min(area.height.saturating_sub(2));
+    let dialog_area = centered_rect(dialog_width, dialog_height, area);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(styles::border_active())
+        .title(" Add Rebuild Checks ")
+        .title_style(styles::title_active());
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let [header_area, list_area, help_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(2),
+    ])
+    .areas(inner.inner(Margin::new(1, 0)));
+
+    let header = Span::styled("Templates for installed software, appended to checks.toml", styles::help());
+    frame.render_widget(Paragraph::new(header), header_area);
+
+    if state.loading {
+        let loading = Paragraph::new(Span::styled("Looking for installed packages...", styles::warning()));
+        frame.render_widget(loading, list_area);
+    } else if state.templates.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            "No templates match your installed packages, or they are already configured",
+            styles::disabled(),
+        ));
+        frame.render_widget(empty, list_area);
+    } else {
+        let items: Vec<ListItem> = state
+            .templates
+            .iter()
+            .zip(&state.chosen)
+            .map(|(template, chosen)| {
+                let checkbox = if *chosen { "[x] " } else { "[ ] " };
+                ListItem::new(Line::from(vec![
+                    Span::styled(checkbox, styles::help_key()),
+                    Span::raw(format!("{:<24}", template.name)),
+                    Span::styled(template.description, styles::disabled()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(styles::row_highlight())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut state.list_state);
+    }
+
+    let help = Line::from(vec![
+        Span::styled("[Space]", styles::help_key()),
+        Span::styled(" Select  ", styles::help()),
+        Span::styled("[Enter]", styles::help_key()),
+        Span::styled(" Add  ", styles::help()),
+        Span::styled("[Esc]", styles::help_key()),
+        Span::styled(" Cancel", styles::help()),
+    ]);
+    frame.render_widget(Paragraph::new(vec![Line::from(""), help]), help_area);
+}
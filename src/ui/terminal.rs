@@ -0,0 +1,104 @@
+use ratatui::{
+    layout::{Margin, Position},
+    prelude::*,
+    widgets::{Block, Borders, Clear},
+};
+
+use crate::transaction::Transaction;
+
+use super::styles;
+
+/// Area of the terminal pane inside the content area
+/// Inset so the current tab stays visible around the pane
+pub fn pane_area(content: Rect) -> Rect {
+    content.inner(Margin::new(2, 1))
+}
+
+/// Rows and columns available to the child process for a given pane
+pub fn inner_size(pane: Rect) -> (u16, u16) {
+    (pane.height.saturating_sub(2), pane.width.saturating_sub(2))
+}
+
+pub fn draw_transaction(frame: &mut Frame, transaction: &mut Transaction, content: Rect) {
+    let area = pane_area(content);
+    let (rows, cols) = inner_size(area);
+    transaction.resize(rows, cols);
+
+    frame.render_widget(Clear, area);
+
+    let (title, border_style) = if transaction.is_running() {
+        (format!(" {} ", transaction.title), styles::border_active())
+    } else if transaction.succeeded() {
+        (format!(" {} - finished ", transaction.title), styles::border_active())
+    } else {
+        (
+            format!(
+                " {} - failed (exit {}) ",
+                transaction.title,
+                transaction.exit_code().unwrap_or(1)
+            ),
+            styles::error(),
+        )
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(styles::title_active())
+        .border_style(border_style);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let parser = transaction.screen();
+    let screen = parser.screen();
+    let buf = frame.buffer_mut();
+
+    for row in 0..inner.height {
+        for col in 0..inner.width {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+
+            let mut style = Style::default()
+                .fg(convert_color(cell.fgcolor()))
+                .bg(convert_color(cell.bgcolor()));
+            if cell.bold() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if cell.italic() {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            if cell.underline() {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if cell.inverse() {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+
+            let contents = cell.contents();
+            let symbol = if contents.is_empty() { " " } else { contents.as_str() };
+            if let Some(target) = buf.cell_mut(Position::new(inner.x + col, inner.y + row)) {
+                target.set_symbol(symbol).set_style(style);
+            }
+        }
+    }
+
+    // Show the child's cursor while it is still running and viewing live output
+    if transaction.is_running() && !screen.hide_cursor() && screen.scrollback() == 0 {
+        let (row, col) = screen.cursor_position();
+        if row < inner.height && col < inner.width {
+            frame.set_cursor_position(Position::new(inner.x + col, inner.y + row));
+        }
+    }
+}
+
+fn convert_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
@@ -1,6 +1,6 @@
 use super::types::{Package, PackageSource};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::process::Command;
 
 const AUR_API_URL: &str = "https://aur.archlinux.org/rpc/v5/info";
@@ -17,6 +17,44 @@ struct AurPackage {
     name: String,
     #[serde(rename = "Version")]
     version: String,
+    #[serde(rename = "OutOfDate")]
+    out_of_date: Option<i64>,
+    #[serde(rename = "Maintainer")]
+    maintainer: Option<String>,
+    #[serde(rename = "Popularity", default)]
+    popularity: f64,
+    #[serde(rename = "NumVotes", default)]
+    num_votes: u32,
+}
+
+/// What the AUR says about an installed foreign package
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AurStatus {
+    /// Unix time the package was flagged out of date
+    pub out_of_date: Option<i64>,
+    /// None for orphaned packages
+    pub maintainer: Option<String>,
+    pub popularity: f64,
+    pub votes: u32,
+    /// Missing from the AUR: deleted, or built locally and never published
+    pub deleted: bool,
+}
+
+impl AurStatus {
+    /// Short labels for the states worth attention
+    pub fn badges(&self) -> Vec<&'static str> {
+        if self.deleted {
+            return vec!["not in AUR"];
+        }
+        let mut badges = Vec::new();
+        if self.maintainer.is_none() {
+            badges.push("orphaned");
+        }
+        if self.out_of_date.is_some() {
+            badges.push("out of date");
+        }
+        badges
+    }
 }
 
 pub fn check_aur_updates(aur_helper: &str) -> Vec<Package> {
@@ -27,7 +65,13 @@ pub fn check_aur_updates(aur_helper: &str) -> Vec<Package> {
 
     // Try AUR API first
     match query_aur_api(&local_packages) {
-        Ok(aur_versions) => find_updates(&local_packages, &aur_versions),
+        Ok(aur_packages) => {
+            let aur_versions: HashMap<String, String> = aur_packages
+                .into_iter()
+                .map(|(name, pkg)| (name, pkg.version))
+                .collect();
+            find_updates(&local_packages, &aur_versions)
+        }
         Err(_) => {
             // Fall back to configured AUR helper
             check_aur_updates_fallback(aur_helper)
@@ -35,6 +79,42 @@ pub fn check_aur_updates(aur_helper: &str) -> Vec<Package> {
     }
 }
 
+/// AUR status of every installed foreign package
+pub fn check_aur_status() -> Result<BTreeMap<String, AurStatus>, String> {
+    let local_packages = get_local_aur_packages();
+    if local_packages.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let mut aur_packages = query_aur_api(&local_packages).map_err(|e| e.to_string())?;
+    Ok(local_packages
+        .into_iter()
+        .map(|(name, _)| {
+            let status = match aur_packages.remove(&name) {
+                Some(pkg) => AurStatus {
+                    out_of_date: pkg.out_of_date,
+                    maintainer: pkg.maintainer,
+                    popularity: pkg.popularity,
+                    votes: pkg.num_votes,
+                    deleted: false,
+                },
+                None => AurStatus {
+                    deleted: true,
+                    ..AurStatus::default()
+                },
+            };
+            (name, status)
+        })
+        .collect())
+}
+
+/// Which of `names` are in the AUR
+pub fn find_aur_packages(names: &[String]) -> Result<BTreeSet<String>, String> {
+    let packages: Vec<(String, String)> = names.iter().map(|n| (n.clone(), String::new())).collect();
+    let found = query_aur_api(&packages).map_err(|e| e.to_string())?;
+    Ok(found.into_keys().collect())
+}
+
 fn get_local_aur_packages() -> Vec<(String, String)> {
     let output = Command::new("pacman").arg("-Qm").output();
 
@@ -61,7 +141,7 @@ fn get_local_aur_packages() -> Vec<(String, String)> {
         .collect()
 }
 
-fn query_aur_api(packages: &[(String, String)]) -> Result<HashMap<String, String>, reqwest::Error> {
+fn query_aur_api(packages: &[(String, String)]) -> Result<HashMap<String, AurPackage>, reqwest::Error> {
     let client = reqwest::blocking::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()?;
@@ -75,7 +155,7 @@ fn query_aur_api(packages: &[(String, String)]) -> Result<HashMap<String, String
         let response: AurResponse = client.get(AUR_API_URL).query(&params).send()?.json()?;
 
         for pkg in response.results {
-            results.insert(pkg.name, pkg.version);
+            results.insert(pkg.name.clone(), pkg);
         }
     }
 
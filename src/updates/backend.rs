@@ -0,0 +1,218 @@
+use super::aur::{check_aur_status, check_aur_updates, AurStatus};
+use super::devel::{check_vcs_heads, VcsHeads};
+use super::downgrade::{find_versions, DowngradeCandidate};
+use super::info::PackageInfo;
+use super::installed::{get_installed_packages, get_package_versions, InstalledPackage};
+use super::orphans::get_orphan_packages;
+use super::pacman::check_pacman_updates;
+use super::review::{fetch_aur_source, AurSource};
+use super::search::{locate_packages, search_packages, SearchResult};
+use super::types::{Package, PackageSource};
+use std::collections::BTreeMap;
+
+/// Source of package data for the app
+/// The system backend shells out to pacman and the AUR helper; tests swap in a fake
+pub trait PackageBackend: Send + Sync {
+    fn pacman_updates(&self) -> Vec<Package>;
+    fn aur_updates(&self) -> Vec<Package>;
+    /// Maintainer, out-of-date flag and votes of installed foreign packages
+    fn aur_status(&self) -> Result<BTreeMap<String, AurStatus>, String>;
+    fn installed(&self) -> Vec<InstalledPackage>;
+    /// Installed versions of any packages (not just explicit ones), keyed by name
+    fn package_versions(&self, names: &[String]) -> BTreeMap<String, String>;
+    fn orphans(&self) -> Vec<InstalledPackage>;
+    fn search(&self, query: &str) -> Vec<SearchResult>;
+    /// Where packages can be installed from (repositories or AUR); unknown names are left out
+    fn locate(&self, names: &[String]) -> Result<BTreeMap<String, PackageSource>, String>;
+    fn info(&self, name: &str) -> Option<PackageInfo>;
+    /// Installable versions of a package (cache and optional archive mirror), newest first
+    fn versions(&self, name: &str, archive_url: Option<&str>) -> Result<Vec<DowngradeCandidate>, String>;
+    /// PKGBUILD and .SRCINFO of an AUR package, from the AUR at `aur_url`
+    fn aur_source(&self, name: &str, aur_url: &str) -> Result<AurSource, String>;
+    /// Upstream revisions of installed VCS packages, by package name
+    fn vcs_heads(&self, aur_url: &str) -> BTreeMap<String, VcsHeads>;
+}
+
+/// Backend for a real Arch system using pacman, checkupdates and the AUR helper
+pub struct SystemBackend {
+    aur_helper: String,
+}
+
+impl SystemBackend {
+    pub fn new(aur_helper: &str) -> Self {
+        Self {
+            aur_helper: aur_helper.to_string(),
+        }
+    }
+}
+
+impl PackageBackend for SystemBackend {
+    fn pacman_updates(&self) -> Vec<Package> {
+        check_pacman_updates()
+    }
+
+    fn aur_updates(&self) -> Vec<Package> {
+        check_aur_updates(&self.aur_helper)
+    }
+
+    fn aur_status(&self) -> Result<BTreeMap<String, AurStatus>, String> {
+        check_aur_status()
+    }
+
+    fn installed(&self) -> Vec<InstalledPackage> {
+        get_installed_packages()
+    }
+
+    fn package_versions(&self, names: &[String]) -> BTreeMap<String, String> {
+        get_package_versions(names)
+    }
+
+    fn orphans(&self) -> Vec<InstalledPackage> {
+        get_orphan_packages()
+    }
+
+    fn search(&self, query: &str) -> Vec<SearchResult> {
+        search_packages(query)
+    }
+
+    fn locate(&self, names: &[String]) -> Result<BTreeMap<String, PackageSource>, String> {
+        locate_packages(names)
+    }
+
+    fn info(&self, name: &str) -> Option<PackageInfo> {
+        PackageInfo::fetch(name)
+    }
+
+    fn versions(&self, name: &str, archive_url: Option<&str>) -> Result<Vec<DowngradeCandidate>, String> {
+        find_versions(name, archive_url)
+    }
+
+    fn aur_source(&self, name: &str, aur_url: &str) -> Result<AurSource, String> {
+        fetch_aur_source(name, aur_url)
+    }
+
+    fn vcs_heads(&self, aur_url: &str) -> BTreeMap<String, VcsHeads> {
+        check_vcs_heads(aur_url)
+    }
+}
+
+/// In-memory backend for tests - returns whatever it was built with
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeBackend {
+    pub updates: Vec<Package>,
+    pub aur_updates: Vec<Package>,
+    pub aur_status: BTreeMap<String, AurStatus>,
+    pub installed: Vec<InstalledPackage>,
+    pub orphans: Vec<InstalledPackage>,
+    /// Packages available to search (installed flag is taken from `installed`)
+    pub available: Vec<SearchResult>,
+    /// Downgrade candidates for any package (filtered by name)
+    pub versions: Vec<DowngradeCandidate>,
+    /// AUR build files by package name
+    pub aur_sources: Vec<(String, AurSource)>,
+    /// Upstream revisions of VCS packages
+    pub vcs_heads: BTreeMap<String, VcsHeads>,
+}
+
+#[cfg(test)]
+impl PackageBackend for FakeBackend {
+    fn pacman_updates(&self) -> Vec<Package> {
+        self.updates.clone()
+    }
+
+    fn aur_updates(&self) -> Vec<Package> {
+        self.aur_updates.clone()
+    }
+
+    fn aur_status(&self) -> Result<BTreeMap<String, AurStatus>, String> {
+        Ok(self.aur_status.clone())
+    }
+
+    fn installed(&self) -> Vec<InstalledPackage> {
+        self.installed.clone()
+    }
+
+    fn package_versions(&self, names: &[String]) -> BTreeMap<String, String> {
+        self.installed
+            .iter()
+            .chain(&self.orphans)
+            .filter(|p| names.contains(&p.name))
+            .map(|p| (p.name.clone(), p.version.clone()))
+            .collect()
+    }
+
+    fn orphans(&self) -> Vec<InstalledPackage> {
+        self.orphans.clone()
+    }
+
+    fn search(&self, query: &str) -> Vec<SearchResult> {
+        let query = query.to_lowercase();
+        self.available
+            .iter()
+            .filter(|r| r.name.to_lowercase().contains(&query))
+            .map(|r| SearchResult {
+                installed: self.installed.iter().any(|p| p.name == r.name),
+                ..r.clone()
+            })
+            .collect()
+    }
+
+    fn locate(&self, names: &[String]) -> Result<BTreeMap<String, PackageSource>, String> {
+        Ok(self
+            .available
+            .iter()
+            .filter(|r| names.contains(&r.name))
+            .map(|r| {
+                let source =
+                    if r.repository == "AUR" { PackageSource::Aur } else { PackageSource::Pacman };
+                (r.name.clone(), source)
+            })
+            .collect())
+    }
+
+    fn info(&self, name: &str) -> Option<PackageInfo> {
+        let installed = self.installed.iter().chain(&self.orphans).find(|p| p.name == name);
+        let update = self.updates.iter().chain(&self.aur_updates).find(|p| p.name == name);
+        let (version, repository) = if let Some(pkg) = installed {
+            (pkg.version.clone(), pkg.source.to_string())
+        } else if let Some(pkg) = update {
+            (pkg.old_version.clone(), pkg.source.to_string())
+        } else {
+            let result = self.available.iter().find(|r| r.name == name)?;
+            (result.version.clone(), result.repository.clone())
+        };
+
+        Some(PackageInfo {
+            name: name.to_string(),
+            version,
+            description: String::new(),
+            size: String::new(),
+            repository,
+            install_date: None,
+            install_reason: None,
+            url: None,
+            build_date: None,
+            maintainer: None,
+            votes: None,
+            required_by: Vec::new(),
+            optional_for: Vec::new(),
+        })
+    }
+
+    fn versions(&self, name: &str, _archive_url: Option<&str>) -> Result<Vec<DowngradeCandidate>, String> {
+        Ok(self.versions.iter().filter(|v| v.name == name).cloned().collect())
+    }
+
+    fn aur_source(&self, name: &str, _aur_url: &str) -> Result<AurSource, String> {
+        self.aur_sources
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, source)| source.clone())
+            .ok_or_else(|| format!("{} is not in the AUR", name))
+    }
+
+    fn vcs_heads(&self, _aur_url: &str) -> BTreeMap<String, VcsHeads> {
+        self.vcs_heads.clone()
+    }
+}
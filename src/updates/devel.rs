@@ -0,0 +1,405 @@
+use super::installed::get_foreign_packages;
+use super::review::source_urls;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use crate::process::run_with_timeout;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Lookups mostly wait on the network, so a few more workers than cores is fine
+const MAX_WORKERS: usize = 8;
+
+/// Seconds a `git ls-remote`, `svn info` or `hg identify` may take before it is killed
+const REMOTE_TIMEOUT: u64 = 30;
+
+/// Upstream revision of each VCS source of a package, keyed by the source as written in .SRCINFO
+pub type VcsHeads = BTreeMap<String, String>;
+
+/// Version control system a source is fetched with
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VcsKind {
+    Git,
+    Svn,
+    Hg,
+}
+
+/// A `source` entry that follows a branch rather than a fixed release
+#[derive(Debug, PartialEq)]
+struct VcsSource {
+    kind: VcsKind,
+    /// Repository URL without the `git+` prefix and fragment
+    url: String,
+    /// Branch to follow; None for the default one
+    branch: Option<String>,
+}
+
+/// Packages built from a VCS checkout (`foo-git`, `foo-svn`, `foo-hg`)
+pub fn is_vcs_package(name: &str) -> bool {
+    ["-git", "-svn", "-hg"].iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Shorten a revision for display: 7 characters of a commit hash, "r1234" for Subversion
+pub fn short_revision(revision: &str) -> String {
+    if revision.chars().all(|c| c.is_ascii_digit()) {
+        format!("r{}", revision)
+    } else {
+        revision.chars().take(7).collect()
+    }
+}
+
+/// Current upstream revisions of every installed VCS package, like `yay --devel`
+///
+/// Sources come from the .SRCINFO published on the AUR, fetched again only when the AUR
+/// version changes; each one is resolved with `git ls-remote`, `svn info` or `hg identify`.
+/// Packages that could not be looked up (offline, removed from the AUR) are left out.
+pub fn check_vcs_heads(aur_url: &str) -> BTreeMap<String, VcsHeads> {
+    let mut names: Vec<String> =
+        get_foreign_packages().into_iter().filter(|name| is_vcs_package(name)).collect();
+    names.sort();
+    if names.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let srcinfos = load_srcinfos(&names, aur_url);
+    let sources: Vec<(&str, &str, VcsSource)> = srcinfos
+        .iter()
+        .flat_map(|(name, cached)| {
+            source_urls(&cached.srcinfo)
+                .into_iter()
+                .filter_map(move |entry| Some((name.as_str(), entry, parse_vcs_source(entry)?)))
+        })
+        .collect();
+    let revisions = parallel_map(&sources, |(_, _, source)| remote_revision(source));
+
+    let mut heads: BTreeMap<String, VcsHeads> = BTreeMap::new();
+    for ((name, entry, _), revision) in sources.iter().zip(revisions) {
+        if let Some(revision) = revision {
+            heads.entry(name.to_string()).or_default().insert(entry.to_string(), revision);
+        }
+    }
+    heads
+}
+
+/// Revision an installed VCS package was built from, as its version records it by the
+/// usual pkgver() conventions: the commit hash of "1.2.r45.g3f2a9c1" for git and Mercurial,
+/// the revision of "r1234" for Subversion. None when the version does not say.
+fn version_revision(version: &str, source: &str) -> Option<String> {
+    let kind = parse_vcs_source(source)?.kind;
+    let version = version.split_once(':').map_or(version, |(_, version)| version);
+    let pkgver = version.rsplit_once('-').map_or(version, |(pkgver, _)| pkgver);
+    let mut segments = pkgver.split(['.', '_', '+']);
+
+    match kind {
+        VcsKind::Svn => segments.find_map(|segment| {
+            let revision = segment.strip_prefix('r')?;
+            (!revision.is_empty() && revision.chars().all(|c| c.is_ascii_digit()))
+                .then(|| revision.to_string())
+        }),
+        VcsKind::Git | VcsKind::Hg => segments.find_map(|segment| {
+            let hash = segment.strip_prefix('g').unwrap_or(segment);
+            // Dates and commit counts are all digits
+            let is_hash = hash.len() >= 7
+                && hash.chars().all(|c| c.is_ascii_hexdigit())
+                && !hash.chars().all(|c| c.is_ascii_digit());
+            is_hash.then(|| hash.to_lowercase())
+        }),
+    }
+}
+
+/// Revisions an installed package was built from, going by what its version records
+/// The version names one revision, so with several sources it stands for all of them: when
+/// it matches none of `heads`, each source it applies to is taken as built from it.
+/// None when the version names no revision at all.
+pub fn built_revisions(version: &str, heads: &VcsHeads) -> Option<VcsHeads> {
+    let revisions: BTreeMap<&String, String> = heads
+        .keys()
+        .filter_map(|source| Some((source, version_revision(version, source)?)))
+        .collect();
+    let matches = |head: &str, revision: &str| {
+        // Hashes may be abbreviated; Subversion revisions may not
+        let is_hash = !revision.chars().all(|c| c.is_ascii_digit());
+        head == revision || (is_hash && head.starts_with(revision))
+    };
+    if revisions.is_empty() {
+        return None;
+    }
+    let current = heads.iter().any(|(source, head)| {
+        revisions.get(source).is_some_and(|revision| matches(head, revision))
+    });
+
+    Some(
+        heads
+            .iter()
+            .map(|(source, head)| {
+                let built = match revisions.get(source) {
+                    Some(revision) if !current => revision.clone(),
+                    _ => head.clone(),
+                };
+                (source.clone(), built)
+            })
+            .collect(),
+    )
+}
+
+/// .SRCINFO of an AUR package as of one version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSrcinfo {
+    version: String,
+    srcinfo: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    results: Vec<RpcPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcPackage {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "PackageBase")]
+    package_base: String,
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+/// .SRCINFO of each package, from the cache unless the AUR has a newer version
+fn load_srcinfos(names: &[String], aur_url: &str) -> BTreeMap<String, CachedSrcinfo> {
+    let aur_url = aur_url.trim_end_matches('/');
+    let Ok(client) = reqwest::blocking::Client::builder().timeout(Duration::from_secs(15)).build()
+    else {
+        return BTreeMap::new();
+    };
+
+    let mut packages = Vec::new();
+    for batch in names.chunks(100) {
+        let params: Vec<(&str, &str)> = batch.iter().map(|name| ("arg[]", name.as_str())).collect();
+        let response: Result<RpcResponse, _> = client
+            .get(format!("{}/rpc/v5/info", aur_url))
+            .query(&params)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json());
+        match response {
+            Ok(response) => packages.extend(response.results),
+            Err(_) => return BTreeMap::new(),
+        }
+    }
+
+    let cache = load_cache();
+    let srcinfos = parallel_map(&packages, |package| {
+        if let Some(cached) = cache.get(&package.name).filter(|c| c.version == package.version) {
+            return Some(cached.clone());
+        }
+        let srcinfo = client
+            .get(format!("{}/cgit/aur.git/plain/.SRCINFO", aur_url))
+            .query(&[("h", package.package_base.as_str())])
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .ok()?;
+        Some(CachedSrcinfo {
+            version: package.version.clone(),
+            srcinfo,
+        })
+    });
+
+    let srcinfos: BTreeMap<String, CachedSrcinfo> = packages
+        .iter()
+        .zip(srcinfos)
+        .filter_map(|(package, srcinfo)| Some((package.name.clone(), srcinfo?)))
+        .collect();
+    // Best effort - a failed cache write only costs a refetch
+    let _ = save_cache(&srcinfos);
+    srcinfos
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("upkeep")
+        .join("srcinfo.json")
+}
+
+fn load_cache() -> BTreeMap<String, CachedSrcinfo> {
+    std::fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(srcinfos: &BTreeMap<String, CachedSrcinfo>) -> std::io::Result<()> {
+    let path = cache_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(srcinfos)?)
+}
+
+/// `f` applied to every item on a few worker threads, in the order of `items`
+fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let workers = thread::available_parallelism()
+        .map_or(4, |n| n.get() * 2)
+        .min(MAX_WORKERS)
+        .min(items.len());
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                if let Ok(mut results) = results.lock() {
+                    results[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    // A panic in `f` has already been propagated by the scope, so every slot is filled
+    results.into_inner().unwrap_or_default().into_iter().flatten().collect()
+}
+
+/// Parse "git+https://github.com/foo/bar.git#branch=dev"; None for regular downloads and
+/// sources pinned to a tag, commit or revision
+fn parse_vcs_source(entry: &str) -> Option<VcsSource> {
+    let (kind, rest) = if let Some(rest) = entry.strip_prefix("git+") {
+        (VcsKind::Git, rest)
+    } else if entry.starts_with("git://") {
+        (VcsKind::Git, entry)
+    } else if let Some(rest) = entry.strip_prefix("svn+") {
+        (VcsKind::Svn, rest)
+    } else if entry.starts_with("svn://") {
+        (VcsKind::Svn, entry)
+    } else if let Some(rest) = entry.strip_prefix("hg+") {
+        (VcsKind::Hg, rest)
+    } else {
+        return None;
+    };
+
+    let (url, fragment) = rest.split_once('#').unwrap_or((rest, ""));
+    // "?signed" asks makepkg to verify signatures
+    let url = url.split('?').next().unwrap_or(url);
+    let branch = match fragment.split_once('=') {
+        None => None,
+        Some(("branch", branch)) => Some(branch.to_string()),
+        Some(_) => return None,
+    };
+
+    Some(VcsSource {
+        kind,
+        url: url.to_string(),
+        branch,
+    })
+}
+
+/// Latest revision on the followed branch, without cloning
+fn remote_revision(source: &VcsSource) -> Option<String> {
+    let mut command = match source.kind {
+        VcsKind::Git => {
+            let mut command = Command::new("git");
+            let reference = match &source.branch {
+                Some(branch) => format!("refs/heads/{}", branch),
+                None => "HEAD".to_string(),
+            };
+            command.args(["ls-remote", &source.url, &reference]);
+            // Private or vanished repositories must fail rather than ask for a password
+            // or to trust a host key
+            command.env("GIT_TERMINAL_PROMPT", "0");
+            command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+            command
+        }
+        VcsKind::Svn => {
+            let mut command = Command::new("svn");
+            command.args(["info", "--non-interactive", "--show-item", "last-changed-revision"]);
+            command.arg(&source.url);
+            command.env("SVN_SSH", "ssh -o BatchMode=yes");
+            command
+        }
+        VcsKind::Hg => {
+            let mut command = Command::new("hg");
+            command.args(["identify", "--noninteractive", "--id", &source.url]);
+            command.args(["--config", "ui.ssh=ssh -o BatchMode=yes"]);
+            if let Some(branch) = &source.branch {
+                command.args(["-r", branch]);
+            }
+            command
+        }
+    };
+
+    // A dead host would otherwise hold up the whole devel check
+    let output = run_with_timeout(&mut command, Duration::from_secs(REMOTE_TIMEOUT)).ok()?;
+    if output.timed_out || !output.status.success() {
+        return None;
+    }
+    output
+        .stdout
+        .split_whitespace()
+        .next()
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vcs_source() {
+        assert_eq!(
+            parse_vcs_source("git+https://github.com/foo/bar.git#branch=dev"),
+            Some(VcsSource {
+                kind: VcsKind::Git,
+                url: "https://github.com/foo/bar.git".to_string(),
+                branch: Some("dev".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_vcs_source("git+https://gitlab.com/foo/bar.git?signed"),
+            Some(VcsSource {
+                kind: VcsKind::Git,
+                url: "https://gitlab.com/foo/bar.git".to_string(),
+                branch: None,
+            })
+        );
+        let svn = parse_vcs_source("svn+https://svn.example.org/trunk").unwrap();
+        assert_eq!((svn.kind, svn.url.as_str()), (VcsKind::Svn, "https://svn.example.org/trunk"));
+
+        // Pinned or not version controlled at all
+        assert_eq!(parse_vcs_source("git+https://github.com/foo/bar.git#tag=v1.0"), None);
+        assert_eq!(parse_vcs_source("git+https://github.com/foo/bar.git#commit=abc123"), None);
+        assert_eq!(parse_vcs_source("https://example.org/bar-1.0.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_vcs_names_and_revisions() {
+        assert!(is_vcs_package("neovim-git"));
+        assert!(is_vcs_package("foo-hg"));
+        assert!(!is_vcs_package("git"));
+        assert!(!is_vcs_package("legit"));
+
+        assert_eq!(short_revision("3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f"), "3f2a9c1");
+        assert_eq!(short_revision("1234"), "r1234");
+    }
+
+    #[test]
+    fn test_version_revision() {
+        let git = "git+https://github.com/neovim/neovim.git";
+        assert_eq!(version_revision("0.11.0.r45.g3f2a9c1-1", git).as_deref(), Some("3f2a9c1"));
+        assert_eq!(version_revision("1:r812.3F2A9C1d-2", git).as_deref(), Some("3f2a9c1d"));
+        assert_eq!(version_revision("20240101.r45-1", git), None);
+        assert_eq!(version_revision("0.11.0-1", git), None);
+
+        let svn = "svn+https://svn.example.org/trunk";
+        assert_eq!(version_revision("1.0.r1234-1", svn).as_deref(), Some("1234"));
+        assert_eq!(version_revision("1.0-1", svn), None);
+        assert_eq!(version_revision("r1234-1", "https://example.org/foo.tar.gz"), None);
+    }
+}
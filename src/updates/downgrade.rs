@@ -0,0 +1,177 @@
+use super::util::vercmp;
+use std::process::Command;
+
+const PACMAN_CACHE: &str = "/var/cache/pacman/pkg";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionSource {
+    Cache,
+    Archive,
+}
+
+impl VersionSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VersionSource::Cache => "cache",
+            VersionSource::Archive => "archive",
+        }
+    }
+}
+
+/// A package file that can be installed with `pacman -U`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DowngradeCandidate {
+    pub name: String,
+    pub version: String,
+    /// Local path (cache) or URL (archive) passed to pacman -U
+    pub location: String,
+    pub source: VersionSource,
+}
+
+/// Collect installable versions of a package, newest first
+/// Cache files come first for a version; the archive is only queried when configured
+pub fn find_versions(name: &str, archive_url: Option<&str>) -> Result<Vec<DowngradeCandidate>, String> {
+    let mut candidates = cached_versions(name);
+
+    if let Some(url) = archive_url {
+        match archive_versions(url, name) {
+            Ok(archived) => candidates.extend(archived),
+            // An unreachable archive is only fatal if the cache had nothing either
+            Err(e) if candidates.is_empty() => return Err(e),
+            Err(_) => {}
+        }
+    }
+
+    candidates.sort_by(|a, b| vercmp(&b.version, &a.version));
+    candidates.dedup_by(|later, earlier| later.version == earlier.version);
+    Ok(candidates)
+}
+
+/// Package files for `name` in the pacman cache
+fn cached_versions(name: &str) -> Vec<DowngradeCandidate> {
+    let Ok(entries) = std::fs::read_dir(PACMAN_CACHE) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let version = parse_package_file(&file_name, name)?;
+            Some(DowngradeCandidate {
+                name: name.to_string(),
+                version,
+                location: entry.path().to_string_lossy().into_owned(),
+                source: VersionSource::Cache,
+            })
+        })
+        .collect()
+}
+
+/// Package files for `name` listed in an Arch Linux Archive mirror
+/// Layout: {url}/packages/{first letter}/{name}/
+fn archive_versions(base_url: &str, name: &str) -> Result<Vec<DowngradeCandidate>, String> {
+    let first = name.chars().next().ok_or("empty package name")?;
+    let dir_url = format!("{}/packages/{}/{}/", base_url.trim_end_matches('/'), first, name);
+
+    let output = Command::new("curl")
+        .args(["-s", "-f", "-m", "15", &dir_url])
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Archive request failed with status: {}",
+            output.status.code().unwrap_or(-1)
+        ));
+    }
+
+    let html = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_archive_listing(&html, name)
+        .into_iter()
+        .map(|(href, version)| DowngradeCandidate {
+            name: name.to_string(),
+            version,
+            location: format!("{}{}", dir_url, href),
+            source: VersionSource::Archive,
+        })
+        .collect())
+}
+
+/// Extract (href, version) pairs for `name` from a directory index page
+fn parse_archive_listing(html: &str, name: &str) -> Vec<(String, String)> {
+    html.split("href=\"")
+        .skip(1)
+        .filter_map(|rest| {
+            let href = rest.split('"').next()?;
+            // Epochs are percent-encoded in archive links
+            let file_name = href.replace("%3A", ":").replace("%3a", ":");
+            let version = parse_package_file(&file_name, name)?;
+            Some((href.to_string(), version))
+        })
+        .collect()
+}
+
+/// "name-1:2.0-1-x86_64.pkg.tar.zst" -> "1:2.0-1" when the name matches
+/// Signatures and packages for other architectures are skipped
+fn parse_package_file(file_name: &str, name: &str) -> Option<String> {
+    if file_name.ends_with(".sig") {
+        return None;
+    }
+    let (stem, _) = file_name.split_once(".pkg.tar")?;
+
+    let mut parts = stem.rsplitn(4, '-');
+    let arch = parts.next()?;
+    let pkgrel = parts.next()?;
+    let pkgver = parts.next()?;
+    let pkgname = parts.next()?;
+
+    if pkgname != name || (arch != "any" && arch != std::env::consts::ARCH) {
+        return None;
+    }
+    Some(format!("{}-{}", pkgver, pkgrel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_file() {
+        let arch = std::env::consts::ARCH;
+        let file = |s: &str| s.replace("ARCH", arch);
+
+        assert_eq!(
+            parse_package_file(&file("linux-6.7.1.arch1-1-ARCH.pkg.tar.zst"), "linux"),
+            Some("6.7.1.arch1-1".to_string())
+        );
+        assert_eq!(
+            parse_package_file("python-pip-24.0-1-any.pkg.tar.zst", "python-pip"),
+            Some("24.0-1".to_string())
+        );
+        assert_eq!(
+            parse_package_file(&file("ffmpeg-2:6.1.1-3-ARCH.pkg.tar.zst"), "ffmpeg"),
+            Some("2:6.1.1-3".to_string())
+        );
+        // Other packages sharing a prefix, signatures and foreign architectures
+        assert_eq!(parse_package_file(&file("linux-headers-6.7.1-1-ARCH.pkg.tar.zst"), "linux"), None);
+        assert_eq!(parse_package_file(&file("linux-6.7.1-1-ARCH.pkg.tar.zst.sig"), "linux"), None);
+        assert_eq!(parse_package_file("linux-6.7.1-1-riscv128.pkg.tar.zst", "linux"), None);
+    }
+
+    #[test]
+    fn test_parse_archive_listing() {
+        let arch = std::env::consts::ARCH;
+        let html = format!(
+            r#"<a href="../">../</a>
+<a href="ffmpeg-2%3A6.1.1-3-{arch}.pkg.tar.zst">ffmpeg-2:6.1.1-3-{arch}.pkg.tar.zst</a>
+<a href="ffmpeg-2%3A6.1.1-3-{arch}.pkg.tar.zst.sig">ffmpeg-2:6.1.1-3-{arch}.pkg.tar.zst.sig</a>
+<a href="ffmpeg-2%3A6.1.1-2-{arch}.pkg.tar.zst">ffmpeg-2:6.1.1-2-{arch}.pkg.tar.zst</a>"#
+        );
+        let versions = parse_archive_listing(&html, "ffmpeg");
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].0, format!("ffmpeg-2%3A6.1.1-3-{}.pkg.tar.zst", arch));
+        assert_eq!(versions[0].1, "2:6.1.1-3");
+        assert_eq!(versions[1].1, "2:6.1.1-2");
+    }
+}
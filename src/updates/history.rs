@@ -0,0 +1,291 @@
+use super::types::Filterable;
+
+const PACMAN_LOG: &str = "/var/log/pacman.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAction {
+    Installed,
+    Upgraded,
+    Downgraded,
+    Reinstalled,
+    Removed,
+}
+
+impl HistoryAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HistoryAction::Installed => "installed",
+            HistoryAction::Upgraded => "upgraded",
+            HistoryAction::Downgraded => "downgraded",
+            HistoryAction::Reinstalled => "reinstalled",
+            HistoryAction::Removed => "removed",
+        }
+    }
+}
+
+/// A single package change from pacman.log
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Transaction this change belongs to (entries sharing an id were applied together)
+    pub transaction: usize,
+    /// Local time of the transaction, "YYYY-MM-DD HH:MM"
+    pub timestamp: String,
+    /// Command that triggered the transaction (e.g. "pacman -Syu")
+    pub command: String,
+    pub action: HistoryAction,
+    pub name: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Version change for display, e.g. "1.0-1 -> 1.1-1" or "1.0-1"
+    pub fn version_display(&self) -> String {
+        match (&self.old_version, &self.new_version) {
+            (Some(old), Some(new)) => format!("{} -> {}", old, new),
+            (Some(v), None) | (None, Some(v)) => v.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+impl Filterable for HistoryEntry {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Read and parse pacman.log, newest changes first
+pub fn load_history() -> Result<Vec<HistoryEntry>, String> {
+    let content = std::fs::read(PACMAN_LOG)
+        .map_err(|e| format!("Failed to read {}: {}", PACMAN_LOG, e))?;
+    let mut entries = parse_log(&String::from_utf8_lossy(&content));
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Current size of pacman.log, to pick up the changes made after this point
+pub fn pacman_log_size() -> u64 {
+    std::fs::metadata(PACMAN_LOG).map_or(0, |m| m.len())
+}
+
+/// Package changes logged after `offset` (a size from `pacman_log_size`), oldest first
+pub fn load_history_since(offset: u64) -> Vec<HistoryEntry> {
+    let Ok(content) = std::fs::read(PACMAN_LOG) else {
+        return Vec::new();
+    };
+    // A rotated log starts over
+    let start = if offset as usize > content.len() { 0 } else { offset as usize };
+    parse_log(&String::from_utf8_lossy(&content[start..]))
+}
+
+/// Time of the most recent full system upgrade (-Syu) in the history, as Unix seconds
+/// Entries are newest first, as returned by `load_history`
+pub fn last_full_upgrade(entries: &[HistoryEntry]) -> Option<i64> {
+    let entry = entries.iter().find(|e| is_full_upgrade(&e.command))?;
+    let time = chrono::NaiveDateTime::parse_from_str(&entry.timestamp, "%Y-%m-%d %H:%M").ok()?;
+    time.and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|t| t.timestamp())
+}
+
+/// Whether a pacman command line upgrades the whole system ("-Syu", "-S -y -u", "--sysupgrade")
+fn is_full_upgrade(command: &str) -> bool {
+    let mut sync = false;
+    let mut sysupgrade = false;
+    for arg in command.split_whitespace() {
+        match arg {
+            "--sync" => sync = true,
+            "--sysupgrade" => sysupgrade = true,
+            _ if arg.starts_with('-') && !arg.starts_with("--") => {
+                sync |= arg.contains('S');
+                sysupgrade |= arg.contains('u');
+            }
+            _ => {}
+        }
+    }
+    sync && sysupgrade
+}
+
+/// Parse pacman.log into package changes grouped by transaction (oldest first)
+///
+/// Format:
+/// [2024-01-15T10:23:45+0100] [PACMAN] Running 'pacman -Syu'
+/// [2024-01-15T10:24:01+0100] [ALPM] transaction started
+/// [2024-01-15T10:24:01+0100] [ALPM] upgraded linux (6.7.0-1 -> 6.7.1-1)
+/// [2024-01-15T10:24:02+0100] [ALPM] transaction completed
+fn parse_log(content: &str) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new();
+    let mut command = String::new();
+    let mut transaction = 0;
+    let mut in_transaction = false;
+    let mut transaction_time = String::new();
+
+    for line in content.lines() {
+        let Some((timestamp, source, message)) = split_line(line) else {
+            continue;
+        };
+
+        match source {
+            "PACMAN" => {
+                if let Some(cmd) = message
+                    .strip_prefix("Running '")
+                    .and_then(|m| m.strip_suffix('\''))
+                {
+                    command = cmd.to_string();
+                }
+            }
+            "ALPM" => {
+                if message == "transaction started" {
+                    transaction += 1;
+                    in_transaction = true;
+                    transaction_time = timestamp;
+                    continue;
+                }
+                if message.starts_with("transaction ") {
+                    in_transaction = false;
+                    continue;
+                }
+
+                let Some((action, name, old_version, new_version)) = parse_change(message) else {
+                    continue;
+                };
+
+                // Old logs have no transaction markers - group by time and command instead
+                if !in_transaction {
+                    let same_group = entries
+                        .last()
+                        .is_some_and(|e: &HistoryEntry| e.timestamp == timestamp && e.command == command);
+                    if !same_group {
+                        transaction += 1;
+                    }
+                    transaction_time = timestamp;
+                }
+
+                entries.push(HistoryEntry {
+                    transaction,
+                    timestamp: transaction_time.clone(),
+                    command: command.clone(),
+                    action,
+                    name,
+                    old_version,
+                    new_version,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Split "[timestamp] [SOURCE] message" into its parts, normalizing the timestamp
+fn split_line(line: &str) -> Option<(String, &str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (raw_time, rest) = rest.split_once("] ")?;
+    let rest = rest.strip_prefix('[')?;
+    let (source, message) = rest.split_once("] ")?;
+    Some((normalize_timestamp(raw_time), source, message.trim_end()))
+}
+
+/// "2024-01-15T10:23:45+0100" or "2024-01-15 10:23" -> "2024-01-15 10:23"
+fn normalize_timestamp(raw: &str) -> String {
+    let normalized = raw.replacen('T', " ", 1);
+    normalized.chars().take(16).collect()
+}
+
+/// Parse "upgraded name (old -> new)" style messages
+fn parse_change(message: &str) -> Option<(HistoryAction, String, Option<String>, Option<String>)> {
+    let (verb, rest) = message.split_once(' ')?;
+    let action = match verb {
+        "installed" => HistoryAction::Installed,
+        "upgraded" => HistoryAction::Upgraded,
+        "downgraded" => HistoryAction::Downgraded,
+        "reinstalled" => HistoryAction::Reinstalled,
+        "removed" => HistoryAction::Removed,
+        _ => return None,
+    };
+
+    let (name, versions) = rest.split_once(" (")?;
+    let versions = versions.strip_suffix(')')?;
+
+    let (old_version, new_version) = match versions.split_once(" -> ") {
+        Some((old, new)) => (Some(old.to_string()), Some(new.to_string())),
+        None if action == HistoryAction::Removed => (Some(versions.to_string()), None),
+        None => (None, Some(versions.to_string())),
+    };
+
+    Some((action, name.to_string(), old_version, new_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+[2024-01-15T10:23:45+0100] [PACMAN] Running 'pacman -Syu'
+[2024-01-15T10:23:45+0100] [PACMAN] synchronizing package lists
+[2024-01-15T10:24:01+0100] [ALPM] transaction started
+[2024-01-15T10:24:01+0100] [ALPM] upgraded linux (6.7.0-1 -> 6.7.1-1)
+[2024-01-15T10:24:02+0100] [ALPM] installed libnew (1.0-1)
+[2024-01-15T10:24:02+0100] [ALPM-SCRIPTLET] ==> Generating initramfs
+[2024-01-15T10:24:03+0100] [ALPM] transaction completed
+[2024-01-16T09:00:00+0100] [PACMAN] Running 'pacman -R oldpkg'
+[2024-01-16T09:00:01+0100] [ALPM] transaction started
+[2024-01-16T09:00:01+0100] [ALPM] removed oldpkg (2.0-1)
+[2024-01-16T09:00:01+0100] [ALPM] transaction completed
+";
+
+    #[test]
+    fn test_parse_log_groups_transactions() {
+        let entries = parse_log(LOG);
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].transaction, entries[1].transaction);
+        assert_ne!(entries[1].transaction, entries[2].transaction);
+
+        assert_eq!(entries[0].action, HistoryAction::Upgraded);
+        assert_eq!(entries[0].name, "linux");
+        assert_eq!(entries[0].version_display(), "6.7.0-1 -> 6.7.1-1");
+        assert_eq!(entries[0].command, "pacman -Syu");
+        assert_eq!(entries[0].timestamp, "2024-01-15 10:24");
+
+        assert_eq!(entries[1].action, HistoryAction::Installed);
+        assert_eq!(entries[1].new_version.as_deref(), Some("1.0-1"));
+
+        assert_eq!(entries[2].action, HistoryAction::Removed);
+        assert_eq!(entries[2].old_version.as_deref(), Some("2.0-1"));
+        assert_eq!(entries[2].command, "pacman -R oldpkg");
+    }
+
+    #[test]
+    fn test_parse_log_without_transaction_markers() {
+        let log = "\
+[2013-01-01 10:00] [PACMAN] Running 'pacman -Syu'
+[2013-01-01 10:00] [ALPM] upgraded foo (1-1 -> 2-1)
+[2013-01-01 10:00] [ALPM] upgraded bar (1-1 -> 2-1)
+[2013-01-02 11:00] [ALPM] upgraded baz (1-1 -> 2-1)
+";
+        let entries = parse_log(log);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].transaction, entries[1].transaction);
+        assert_ne!(entries[1].transaction, entries[2].transaction);
+        assert_eq!(entries[2].timestamp, "2013-01-02 11:00");
+    }
+
+    #[test]
+    fn test_last_full_upgrade() {
+        assert!(is_full_upgrade("pacman -Syu"));
+        assert!(is_full_upgrade("pacman -S -y -u --config /etc/pacman.conf --"));
+        assert!(!is_full_upgrade("pacman -S linux"));
+        assert!(!is_full_upgrade("pacman -R oldpkg"));
+
+        let mut entries = parse_log(LOG);
+        entries.reverse();
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 1, 15)
+            .and_then(|d| d.and_hms_opt(10, 24, 0))
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.timestamp());
+        assert_eq!(last_full_upgrade(&entries), expected);
+    }
+}
@@ -1,12 +1,14 @@
 use super::types::{Filterable, PackageSource};
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
 use std::process::Command;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InstalledPackage {
     pub name: String,
     pub version: String,
     pub source: PackageSource,
+    #[serde(skip)]
     pub selected: bool,
 }
 
@@ -23,7 +25,7 @@ impl InstalledPackage {
     pub fn source_label(&self) -> &'static str {
         match self.source {
             PackageSource::Pacman => "",
-            PackageSource::Aur => " (AUR)",
+            PackageSource::Aur | PackageSource::Devel => " (AUR)",
         }
     }
 }
@@ -82,6 +84,26 @@ fn get_explicit_packages() -> Vec<(String, String)> {
         .collect()
 }
 
+/// Installed versions of the given packages; names that are not installed are left out
+pub fn get_package_versions(names: &[String]) -> BTreeMap<String, String> {
+    if names.is_empty() {
+        return BTreeMap::new();
+    }
+
+    // pacman reports missing packages on stderr and still lists the rest
+    let Ok(output) = Command::new("pacman").arg("-Q").args(names).output() else {
+        return BTreeMap::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (name, version) = line.split_once(' ')?;
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
 pub fn get_foreign_packages() -> HashSet<String> {
     let output = Command::new("pacman")
         .args(["-Qm"])
@@ -1,18 +1,38 @@
 mod aur;
+mod backend;
+mod devel;
+mod downgrade;
+mod history;
 mod info;
 mod installed;
 mod news;
 mod orphans;
+mod pacnew;
 mod pacman;
+mod restart;
+mod review;
 mod search;
 mod types;
 mod util;
 
-pub use aur::check_aur_updates;
+pub use aur::AurStatus;
+#[cfg(test)]
+pub use backend::FakeBackend;
+pub use backend::{PackageBackend, SystemBackend};
+pub use devel::{built_revisions, is_vcs_package, short_revision, VcsHeads};
+pub use downgrade::{DowngradeCandidate, VersionSource};
+pub use history::{
+    last_full_upgrade, load_history, load_history_since, pacman_log_size, HistoryAction,
+    HistoryEntry,
+};
 pub use info::PackageInfo;
-pub use installed::{get_installed_packages, InstalledPackage};
-pub use news::{fetch_news, find_related_packages, format_short_date, NewsInfo, NewsItem};
-pub use orphans::get_orphan_packages;
-pub use pacman::check_pacman_updates;
-pub use search::{search_packages, SearchResult};
+pub use installed::InstalledPackage;
+pub use news::{find_related_packages, format_short_date, load_news, NewsFeed, NewsInfo, NewsItem};
+pub use pacnew::{diff_pacnew, find_pacnew_files, PacnewFile, PacnewKind};
+#[cfg(test)]
+pub use restart::StaleUnit;
+pub use restart::{check_restart, installed_kernels, RestartStatus};
+pub use review::{AurReview, AurSource};
+pub use search::SearchResult;
 pub use types::{filter_items, Package, PackageSource};
+pub use util::vercmp;
@@ -1,13 +1,17 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::Command;
 
 /// A news item from the Arch Linux news feed
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewsItem {
     pub title: String,
     pub link: String,
     pub description: String,
     pub author: String,
     pub pub_date: String,
+    /// Publication time as Unix seconds (None if the feed date could not be parsed)
+    pub published: Option<i64>,
     pub requires_attention: bool,
     pub related_packages: Vec<String>,
 }
@@ -54,8 +58,59 @@ const ATTENTION_KEYWORDS: &[&str] = &[
     "breaking change",
 ];
 
+/// News items plus where they came from
+pub struct NewsFeed {
+    pub items: Vec<NewsItem>,
+    /// Set to the fetch error when the items were loaded from the offline cache
+    pub offline: Option<String>,
+}
+
+/// Fetch news, falling back to the last cached copy when the network is unavailable
+pub fn load_news(installed_packages: &[String]) -> Result<NewsFeed, String> {
+    match fetch_news(installed_packages) {
+        Ok(items) => {
+            // Best effort - a failed cache write only costs offline access
+            let _ = save_cache(&items);
+            Ok(NewsFeed { items, offline: None })
+        }
+        Err(e) => match load_cache(installed_packages) {
+            Some(items) => Ok(NewsFeed {
+                items,
+                offline: Some(e),
+            }),
+            None => Err(e),
+        },
+    }
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("upkeep")
+        .join("news.json")
+}
+
+fn save_cache(items: &[NewsItem]) -> std::io::Result<()> {
+    let path = cache_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(items)?)
+}
+
+/// Load cached items, re-matching them against the current installed packages
+fn load_cache(installed_packages: &[String]) -> Option<Vec<NewsItem>> {
+    let content = std::fs::read_to_string(cache_path()).ok()?;
+    let mut items: Vec<NewsItem> = serde_json::from_str(&content).ok()?;
+    for item in &mut items {
+        let full_text = format!("{} {}", item.title, item.description);
+        item.related_packages = find_related_packages(&full_text, installed_packages);
+    }
+    Some(items)
+}
+
 /// Fetch and parse news from Arch Linux RSS feed
-pub fn fetch_news(installed_packages: &[String]) -> Result<Vec<NewsItem>, String> {
+fn fetch_news(installed_packages: &[String]) -> Result<Vec<NewsItem>, String> {
     let output = Command::new("curl")
         .args(["-s", "-m", "10", "https://archlinux.org/feeds/news/"])
         .output()
@@ -91,7 +146,11 @@ fn parse_rss_feed(xml: &str, installed_packages: &[String]) -> Result<Vec<NewsIt
                 .and_then(|dc| dc.creators().first().map(|s| s.as_str()))
                 .unwrap_or("")
                 .to_string();
-            let pub_date = format_pub_date(item.pub_date().unwrap_or(""));
+            let raw_date = item.pub_date().unwrap_or("");
+            let pub_date = format_pub_date(raw_date);
+            let published = chrono::DateTime::parse_from_rfc2822(raw_date)
+                .ok()
+                .map(|date| date.timestamp());
 
             let full_text = format!("{} {}", title, description);
             let requires_attention = check_requires_attention(&full_text);
@@ -103,6 +162,7 @@ fn parse_rss_feed(xml: &str, installed_packages: &[String]) -> Result<Vec<NewsIt
                 description,
                 author,
                 pub_date,
+                published,
                 requires_attention,
                 related_packages,
             }
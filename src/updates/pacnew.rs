@@ -0,0 +1,136 @@
+use super::types::Filterable;
+use std::path::Path;
+use std::process::Command;
+
+const CONFIG_ROOT: &str = "/etc";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacnewKind {
+    /// New default config pacman installed next to a modified one
+    Pacnew,
+    /// Modified config pacman saved when its package was removed or replaced
+    Pacsave,
+}
+
+impl PacnewKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PacnewKind::Pacnew => "pacnew",
+            PacnewKind::Pacsave => "pacsave",
+        }
+    }
+}
+
+/// A .pacnew/.pacsave file and the live config it belongs to
+#[derive(Debug, Clone)]
+pub struct PacnewFile {
+    pub path: String,
+    pub target: String,
+    pub kind: PacnewKind,
+    pub selected: bool,
+}
+
+impl Filterable for PacnewFile {
+    fn name(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Find .pacnew and .pacsave files under /etc, sorted by path
+pub fn find_pacnew_files() -> Vec<PacnewFile> {
+    let mut files = Vec::new();
+    walk(Path::new(CONFIG_ROOT), &mut files);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn walk(dir: &Path, files: &mut Vec<PacnewFile>) {
+    // Unreadable directories (e.g. /etc/sudoers.d) are skipped
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            walk(&path, files);
+        } else if let Some((target, kind)) = split_pacnew_path(&path.to_string_lossy()) {
+            files.push(PacnewFile {
+                path: path.to_string_lossy().into_owned(),
+                target,
+                kind,
+                selected: false,
+            });
+        }
+    }
+}
+
+/// "/etc/foo.conf.pacnew" -> ("/etc/foo.conf", Pacnew)
+/// Numbered saves ("/etc/foo.conf.pacsave.1") map to the same live file
+pub fn split_pacnew_path(path: &str) -> Option<(String, PacnewKind)> {
+    if let Some(target) = path.strip_suffix(".pacnew") {
+        return Some((target.to_string(), PacnewKind::Pacnew));
+    }
+
+    let without_number = match path.rsplit_once('.') {
+        Some((rest, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => rest,
+        _ => path,
+    };
+    without_number
+        .strip_suffix(".pacsave")
+        .map(|target| (target.to_string(), PacnewKind::Pacsave))
+}
+
+/// Unified diff of the live file against the pacnew/pacsave file
+pub fn diff_pacnew(file: &PacnewFile) -> Vec<String> {
+    if !Path::new(&file.target).exists() {
+        return vec![format!("{} does not exist - the package that owned it is gone", file.target)];
+    }
+
+    let output = match Command::new("diff")
+        .args(["-u", "--", &file.target, &file.path])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return vec![format!("Failed to run diff: {}", e)],
+    };
+
+    // diff exits 0 for identical files, 1 for differences and 2 for trouble
+    match output.status.code() {
+        Some(0) => vec!["Files are identical".to_string()],
+        Some(1) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect(),
+        _ => String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(String::from)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pacnew_path() {
+        assert_eq!(
+            split_pacnew_path("/etc/pacman.conf.pacnew"),
+            Some(("/etc/pacman.conf".to_string(), PacnewKind::Pacnew))
+        );
+        assert_eq!(
+            split_pacnew_path("/etc/ssh/sshd_config.pacsave"),
+            Some(("/etc/ssh/sshd_config".to_string(), PacnewKind::Pacsave))
+        );
+        assert_eq!(
+            split_pacnew_path("/etc/foo.conf.pacsave.2"),
+            Some(("/etc/foo.conf".to_string(), PacnewKind::Pacsave))
+        );
+        assert_eq!(split_pacnew_path("/etc/pacman.conf"), None);
+        assert_eq!(split_pacnew_path("/etc/locale.gen.1"), None);
+    }
+}
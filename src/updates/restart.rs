@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+const MODULES_DIR: &str = "/usr/lib/modules";
+
+/// Services whose restart ends the graphical or login session (and upkeep with it);
+/// a trailing `*` matches any suffix
+const SESSION_CRITICAL: &[&str] = &[
+    "dbus*",
+    "systemd-logind.service",
+    "display-manager.service",
+    "gdm.service",
+    "sddm.service",
+    "lightdm.service",
+    "lxdm.service",
+    "greetd.service",
+    "ly.service",
+];
+
+/// What needs restarting after packages changed underneath the running system
+#[derive(Debug, Clone, Default)]
+pub struct RestartStatus {
+    /// None when the running kernel could not be determined
+    pub kernel: Option<KernelStatus>,
+    /// Processes still using deleted files, grouped by systemd unit
+    pub units: Vec<StaleUnit>,
+}
+
+impl RestartStatus {
+    pub fn reboot_needed(&self) -> bool {
+        self.kernel.as_ref().is_some_and(|k| k.is_stale())
+    }
+
+    pub fn needs_attention(&self) -> bool {
+        self.reboot_needed() || !self.units.is_empty()
+    }
+
+    /// System services that can be restarted with systemctl without ending the session
+    pub fn system_units(&self) -> Vec<String> {
+        self.units
+            .iter()
+            .filter(|u| !u.user && !u.is_session_critical())
+            .filter_map(|u| u.unit.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KernelStatus {
+    /// Release of the running kernel (`uname -r`)
+    pub running: String,
+    /// Kernels with a modules directory, as (package, release)
+    pub installed: Vec<(String, String)>,
+}
+
+impl KernelStatus {
+    /// The running kernel's modules are gone once its package has been upgraded
+    /// An empty install list (containers, custom kernels) is never reported as stale
+    pub fn is_stale(&self) -> bool {
+        !self.installed.is_empty() && !self.installed.iter().any(|(_, release)| *release == self.running)
+    }
+}
+
+/// A systemd unit (or loose processes) still mapping files that were replaced on disk
+#[derive(Debug, Clone)]
+pub struct StaleUnit {
+    /// None for processes outside a service (login sessions, terminals)
+    pub unit: Option<String>,
+    /// Unit runs under the user manager rather than the system one
+    pub user: bool,
+    /// (pid, command name)
+    pub processes: Vec<(u32, String)>,
+    pub files: BTreeSet<String>,
+}
+
+impl StaleUnit {
+    /// Restarting it would end the session: D-Bus, logind, display managers, or the
+    /// service upkeep itself runs in
+    pub fn is_session_critical(&self) -> bool {
+        let Some(unit) = &self.unit else {
+            return false;
+        };
+        let own = std::process::id();
+        self.processes.iter().any(|(pid, _)| *pid == own)
+            || SESSION_CRITICAL.iter().any(|name| match name.strip_suffix('*') {
+                Some(prefix) => unit.starts_with(prefix),
+                None => unit == name,
+            })
+    }
+
+    /// Suggested way to pick up the new files
+    pub fn restart_command(&self) -> Option<String> {
+        let unit = self.unit.as_ref()?;
+        if self.is_session_critical() {
+            return Some("reboot recommended (restarting it would end the session)".to_string());
+        }
+        Some(if self.user {
+            format!("systemctl --user restart {}", unit)
+        } else {
+            format!("sudo systemctl restart {}", unit)
+        })
+    }
+}
+
+/// Check the running kernel and all visible processes
+/// Processes of other users are only visible when running as root
+pub fn check_restart() -> RestartStatus {
+    RestartStatus {
+        kernel: kernel_status(),
+        units: stale_units(),
+    }
+}
+
+fn kernel_status() -> Option<KernelStatus> {
+    let running = std::fs::read_to_string("/proc/sys/kernel/osrelease").ok()?;
+    let running = running.trim().to_string();
+
+    Some(KernelStatus {
+        running,
+        installed: installed_kernels(),
+    })
+}
+
+/// Installed kernels of every flavor, as (package, release)
+pub fn installed_kernels() -> Vec<(String, String)> {
+    // Arch kernel packages ship vmlinuz and pkgbase in their modules directory;
+    // directories left behind by DKMS or out-of-tree modules have neither
+    let mut installed: Vec<(String, String)> = std::fs::read_dir(MODULES_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("vmlinuz").exists())
+        .map(|entry| {
+            let release = entry.file_name().to_string_lossy().into_owned();
+            let package = std::fs::read_to_string(entry.path().join("pkgbase"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| "linux".to_string());
+            (package, release)
+        })
+        .collect();
+    installed.sort();
+    installed
+}
+
+fn stale_units() -> Vec<StaleUnit> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut units: BTreeMap<(Option<String>, bool), StaleUnit> = BTreeMap::new();
+
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let dir = entry.path();
+
+        // Unreadable maps belong to other users' processes
+        let Ok(maps) = std::fs::read_to_string(dir.join("maps")) else {
+            continue;
+        };
+        let files = deleted_files(&maps);
+        if files.is_empty() {
+            continue;
+        }
+
+        let name = read_trimmed(&dir.join("comm")).unwrap_or_else(|| pid.to_string());
+        let (unit, user) = read_trimmed(&dir.join("cgroup"))
+            .and_then(|cgroup| unit_from_cgroup(&cgroup))
+            .map_or((None, false), |(unit, user)| (Some(unit), user));
+
+        let stale = units.entry((unit.clone(), user)).or_insert_with(|| StaleUnit {
+            unit,
+            user,
+            processes: Vec::new(),
+            files: BTreeSet::new(),
+        });
+        stale.processes.push((pid, name));
+        stale.files.extend(files);
+    }
+
+    // Services first, loose processes last
+    let mut units: Vec<StaleUnit> = units.into_values().collect();
+    units.sort_by_key(|u| u.unit.is_none());
+    units
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Files under /usr that a process maps but that have been replaced on disk
+///
+/// Format of /proc/PID/maps:
+/// 7f2c1a000000-7f2c1a022000 r--p 00000000 fd:01 1234 /usr/lib/libc.so.6 (deleted)
+fn deleted_files(maps: &str) -> BTreeSet<String> {
+    maps.lines()
+        .filter_map(|line| {
+            let path = line.splitn(6, ' ').nth(5)?.trim_start();
+            let path = path.strip_suffix(" (deleted)")?;
+            // Skips memfd, shared memory and other anonymous "deleted" mappings
+            path.starts_with("/usr/").then(|| path.to_string())
+        })
+        .collect()
+}
+
+/// Service a process belongs to, from /proc/PID/cgroup, and whether it is a user unit
+///
+/// cgroup v2: "0::/system.slice/sshd.service"
+/// cgroup v1: "1:name=systemd:/user.slice/user-1000.slice/user@1000.service/app.slice/foo.service"
+fn unit_from_cgroup(cgroup: &str) -> Option<(String, bool)> {
+    let path = cgroup.lines().find_map(|line| {
+        line.strip_prefix("0::")
+            .or_else(|| line.split_once(":name=systemd:").map(|(_, path)| path))
+    })?;
+
+    let unit = path.rsplit('/').find(|part| part.ends_with(".service"))?;
+    // user@UID.service is the user manager itself, not one of its units
+    let user = path.contains("/user@") && !unit.starts_with("user@");
+    Some((unit.to_string(), user))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deleted_files() {
+        let maps = "\
+55d0c0a00000-55d0c0a2a000 r--p 00000000 fd:01 1001 /usr/bin/sshd
+7f2c1a000000-7f2c1a022000 r--p 00000000 fd:01 1234                       /usr/lib/libc.so.6 (deleted)
+7f2c1a100000-7f2c1a122000 r-xp 00022000 fd:01 1234                       /usr/lib/libc.so.6 (deleted)
+7f2c1b000000-7f2c1b100000 rw-s 00000000 00:01 99                         /memfd:wayland-cursor (deleted)
+7f2c1c000000-7f2c1c100000 rw-s 00000000 00:19 98                         /dev/shm/pulse-shm-1 (deleted)
+7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0                          [stack]
+";
+        let files = deleted_files(maps);
+        assert_eq!(files.into_iter().collect::<Vec<_>>(), vec!["/usr/lib/libc.so.6".to_string()]);
+    }
+
+    #[test]
+    fn test_unit_from_cgroup() {
+        assert_eq!(
+            unit_from_cgroup("0::/system.slice/sshd.service"),
+            Some(("sshd.service".to_string(), false))
+        );
+        assert_eq!(
+            unit_from_cgroup("0::/user.slice/user-1000.slice/user@1000.service/app.slice/pipewire.service"),
+            Some(("pipewire.service".to_string(), true))
+        );
+        assert_eq!(
+            unit_from_cgroup("12:pids:/\n1:name=systemd:/system.slice/cronie.service"),
+            Some(("cronie.service".to_string(), false))
+        );
+        // Login sessions are scopes, not restartable services
+        assert_eq!(unit_from_cgroup("0::/user.slice/user-1000.slice/session-2.scope"), None);
+    }
+
+    #[test]
+    fn test_session_critical_units() {
+        let unit = |name: &str, pid: u32| StaleUnit {
+            unit: Some(name.to_string()),
+            user: false,
+            processes: vec![(pid, "proc".to_string())],
+            files: BTreeSet::new(),
+        };
+        let status = RestartStatus {
+            kernel: None,
+            units: vec![
+                unit("dbus-broker.service", 1),
+                unit("systemd-logind.service", 2),
+                unit("sddm.service", 3),
+                unit("sshd.service", 4),
+                unit("tmux.service", std::process::id()),
+            ],
+        };
+        assert_eq!(status.system_units(), vec!["sshd.service".to_string()]);
+        assert!(status.units[2].restart_command().unwrap().starts_with("reboot recommended"));
+    }
+
+    #[test]
+    fn test_kernel_stale() {
+        let mut kernel = KernelStatus {
+            running: "6.7.1-arch1-1".to_string(),
+            installed: vec![("linux".to_string(), "6.7.2-arch1-1".to_string())],
+        };
+        assert!(kernel.is_stale());
+
+        kernel.installed.push(("linux".to_string(), "6.7.1-arch1-1".to_string()));
+        assert!(!kernel.is_stale());
+
+        kernel.installed.clear();
+        assert!(!kernel.is_stale());
+    }
+}
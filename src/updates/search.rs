@@ -1,6 +1,8 @@
+use super::aur::find_aur_packages;
+use super::types::PackageSource;
 use super::util::url_encode;
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::process::Command;
 
 #[derive(Debug, Clone)]
@@ -154,6 +156,34 @@ fn get_installed_names() -> HashSet<String> {
     }
 }
 
+/// Where each of `names` can be installed from: the sync repositories, or else the AUR
+/// Names found in neither are left out
+pub fn locate_packages(names: &[String]) -> Result<BTreeMap<String, PackageSource>, String> {
+    let output = Command::new("pacman")
+        .args(["-Slq"])
+        .output()
+        .map_err(|e| format!("Failed to run pacman: {}", e))?;
+    let repo: HashSet<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect();
+
+    let mut located: BTreeMap<String, PackageSource> = BTreeMap::new();
+    let mut rest = Vec::new();
+    for name in names {
+        if repo.contains(name) {
+            located.insert(name.clone(), PackageSource::Pacman);
+        } else {
+            rest.push(name.clone());
+        }
+    }
+    if !rest.is_empty() {
+        let aur = find_aur_packages(&rest).map_err(|e| format!("AUR lookup failed: {}", e))?;
+        located.extend(aur.into_iter().map(|name| (name, PackageSource::Aur)));
+    }
+    Ok(located)
+}
+
 /// Search for packages in both official repos and AUR
 pub fn search_packages(query: &str) -> Vec<SearchResult> {
     if query.len() < 2 {
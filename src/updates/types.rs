@@ -1,3 +1,6 @@
+use serde::Serialize;
+use std::fmt;
+
 pub trait Filterable {
     fn name(&self) -> &str;
 }
@@ -15,18 +18,32 @@ pub fn filter_items<'a, T: Filterable>(items: &'a [T], query: &str) -> Vec<(usiz
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PackageSource {
     Pacman,
     Aur,
+    /// VCS package (`-git`, `-svn`, ...) with new upstream commits; versions are revisions
+    Devel,
+}
+
+impl fmt::Display for PackageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackageSource::Pacman => write!(f, "pacman"),
+            PackageSource::Aur => write!(f, "aur"),
+            PackageSource::Devel => write!(f, "devel"),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Package {
     pub name: String,
     pub old_version: String,
     pub new_version: String,
     pub source: PackageSource,
+    #[serde(skip)]
     pub selected: bool,
 }
 
@@ -45,6 +62,7 @@ impl Package {
         match self.source {
             PackageSource::Pacman => "",
             PackageSource::Aur => " (AUR)",
+            PackageSource::Devel => " (devel)",
         }
     }
 }
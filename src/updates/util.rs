@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 /// Simple URL encoding for query strings
 pub fn url_encode(s: &str) -> String {
     let mut result = String::with_capacity(s.len() * 3);
@@ -14,3 +16,137 @@ pub fn url_encode(s: &str) -> String {
     }
     result
 }
+
+/// Compare two package versions the way pacman's vercmp does
+/// Handles epochs ("1:2.0-1"), release suffixes and mixed alpha/numeric segments
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let (epoch_a, ver_a, rel_a) = split_evr(a);
+    let (epoch_b, ver_b, rel_b) = split_evr(b);
+
+    rpmvercmp(epoch_a, epoch_b)
+        .then_with(|| rpmvercmp(ver_a, ver_b))
+        .then_with(|| match (rel_a, rel_b) {
+            (Some(rel_a), Some(rel_b)) => rpmvercmp(rel_a, rel_b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Split "epoch:version-release" into its parts (epoch defaults to "0")
+fn split_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match evr.split_once(':') {
+        Some((epoch, rest)) if epoch.bytes().all(|b| b.is_ascii_digit()) => {
+            (if epoch.is_empty() { "0" } else { epoch }, rest)
+        }
+        _ => ("0", evr),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Segment-by-segment comparison from libalpm's rpmvercmp
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let (mut one, mut two) = (0, 0);
+    let (mut prev_one, mut prev_two) = (0, 0);
+
+    while one < a.len() && two < b.len() {
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+        if one >= a.len() || two >= b.len() {
+            break;
+        }
+
+        // Differing separator lengths decide the comparison
+        if one - prev_one != two - prev_two {
+            return (one - prev_one).cmp(&(two - prev_two));
+        }
+
+        let is_num = a[one].is_ascii_digit();
+        let segment_end = |s: &[u8], start: usize| {
+            let mut end = start;
+            while end < s.len()
+                && (if is_num { s[end].is_ascii_digit() } else { s[end].is_ascii_alphabetic() })
+            {
+                end += 1;
+            }
+            end
+        };
+        let end_one = segment_end(a, one);
+        let end_two = segment_end(b, two);
+
+        // Segments of different types: numeric is newer than alpha
+        if end_two == two {
+            return if is_num { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let mut seg_one = &a[one..end_one];
+        let mut seg_two = &b[two..end_two];
+        if is_num {
+            while seg_one.len() > 1 && seg_one[0] == b'0' {
+                seg_one = &seg_one[1..];
+            }
+            while seg_two.len() > 1 && seg_two[0] == b'0' {
+                seg_two = &seg_two[1..];
+            }
+            let by_len = seg_one.len().cmp(&seg_two.len());
+            if by_len != Ordering::Equal {
+                return by_len;
+            }
+        }
+        let by_value = seg_one.cmp(seg_two);
+        if by_value != Ordering::Equal {
+            return by_value;
+        }
+
+        one = end_one;
+        two = end_two;
+        prev_one = one;
+        prev_two = two;
+    }
+
+    let one_done = one >= a.len();
+    let two_done = two >= b.len();
+    if one_done && two_done {
+        return Ordering::Equal;
+    }
+
+    // A remaining alpha segment never beats an empty string
+    if (one_done && !b[two].is_ascii_alphabetic()) || (!one_done && a[one].is_ascii_alphabetic()) {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vercmp() {
+        assert_eq!(vercmp("1.0-1", "1.0-1"), Ordering::Equal);
+        assert_eq!(vercmp("1.0-1", "1.0-2"), Ordering::Less);
+        assert_eq!(vercmp("1.10-1", "1.9-1"), Ordering::Greater);
+        assert_eq!(vercmp("1:1.0-1", "2.0-1"), Ordering::Greater);
+        assert_eq!(vercmp("1.0a-1", "1.0-1"), Ordering::Less);
+        assert_eq!(vercmp("1.0.1-1", "1.0-1"), Ordering::Greater);
+        assert_eq!(vercmp("6.7.1.arch1-1", "6.7.2.arch1-1"), Ordering::Less);
+        assert_eq!(vercmp("1.0", "1.0-5"), Ordering::Equal);
+        assert_eq!(vercmp("1.001", "1.1"), Ordering::Equal);
+    }
+}